@@ -0,0 +1,131 @@
+// src/allow_list.rs
+// Merkle allow-lists for promotions. Each leaf is a (wallet, quantity) pair and
+// Promotion.allow_list_root commits to the whole list, so a merchant can hand a
+// promotion to thousands of wallets without one account per wallet.
+//
+// Leaves and inner nodes are sha256 with distinct prefixes, so a node can never
+// pass for a leaf. Pairs are hashed in sorted order, so proofs carry no
+// left/right flags.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+// Passed to mint_coupon by allow-listed recipients
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AllowListProof {
+    pub quantity: u32,            // Coupons the leaf grants the recipient in total
+    pub proof: Vec<[u8; 32]>,     // Sibling hashes from the leaf up to the root
+}
+
+pub fn hash_leaf(wallet: &Pubkey, quantity: u32) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, wallet.as_ref(), &quantity.to_le_bytes()]).to_bytes()
+}
+
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, low, high]).to_bytes()
+}
+
+pub fn verify(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling)) == *root
+}
+
+// Off-chain builder for the root and proofs, e.g. from a merchant's CSV of
+// wallets, sharing the hashing above. A node without a sibling moves up to the
+// next level unchanged.
+pub struct AllowListTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl AllowListTree {
+    // Panics on an empty list, which has no root
+    pub fn new(entries: &[(Pubkey, u32)]) -> Self {
+        Self::from_leaves(entries.iter().map(|(wallet, quantity)| hash_leaf(wallet, *quantity)).collect())
+    }
+
+    // Tree over already-hashed leaves, e.g. promo_codes::hash_code
+    pub fn from_leaves(leaves: Vec<[u8; 32]>) -> Self {
+        assert!(!leaves.is_empty(), "a merkle tree needs at least one leaf");
+
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Self { levels }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    // Proof for the entry at `index` in the list the tree was built from
+    pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        let mut index = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(count: usize) -> Vec<(Pubkey, u32)> {
+        (0..count).map(|i| (Pubkey::new_unique(), i as u32 % 3 + 1)).collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_against_the_root() {
+        // Powers of two and the odd sizes that carry nodes up unchanged
+        for count in [1, 2, 3, 5, 8, 13, 100] {
+            let entries = entries(count);
+            let tree = AllowListTree::new(&entries);
+            for (index, (wallet, quantity)) in entries.iter().enumerate() {
+                assert!(
+                    verify(&tree.root(), hash_leaf(wallet, *quantity), &tree.proof(index)),
+                    "leaf {} of {}",
+                    index,
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_a_different_quantity_or_wallet() {
+        let entries = entries(10);
+        let tree = AllowListTree::new(&entries);
+        let (wallet, quantity) = entries[4];
+        let proof = tree.proof(4);
+
+        assert!(!verify(&tree.root(), hash_leaf(&wallet, quantity + 1), &proof));
+        assert!(!verify(&tree.root(), hash_leaf(&Pubkey::new_unique(), quantity), &proof));
+        assert!(!verify(&tree.root(), hash_leaf(&wallet, quantity), &tree.proof(5)));
+    }
+
+    #[test]
+    fn single_wallet_root_is_its_leaf() {
+        let entries = entries(1);
+        let tree = AllowListTree::new(&entries);
+
+        assert_eq!(tree.root(), hash_leaf(&entries[0].0, entries[0].1));
+        assert!(tree.proof(0).is_empty());
+    }
+}
@@ -42,8 +42,102 @@ pub enum CouponError {
     NotListingSeller,
     #[msg("Invalid coordinates")]
     InvalidCoordinates,
-    #[msg("Location not supported")]
+    #[msg("Location is outside the promotion's redemption area")]
     LocationNotSupported,
     #[msg("Invalid input: string exceeds maximum length")]
     InvalidInput,
-}
\ No newline at end of file
+    #[msg("Coupon has no token mint")]
+    CouponNotTokenized,
+    #[msg("Coupon is listed, auctioned or staked")]
+    CouponLocked,
+    #[msg("Transfer hook called outside of a token transfer")]
+    NotTransferring,
+    #[msg("Marketplace is paused")]
+    MarketplacePaused,
+    #[msg("Fee exceeds the maximum allowed")]
+    FeeTooHigh,
+    #[msg("Not the pending marketplace authority")]
+    NotPendingAuthority,
+    #[msg("Invalid platform config")]
+    InvalidConfig,
+    #[msg("Payment mint does not match")]
+    PaymentMintMismatch,
+    #[msg("Missing token accounts for an SPL payment")]
+    MissingPaymentAccount,
+    #[msg("Payment account does not belong to the expected wallet")]
+    InvalidPaymentAccount,
+    #[msg("Royalty exceeds the platform maximum")]
+    RoyaltyTooHigh,
+    #[msg("Promotion has not expired yet")]
+    PromotionNotExpired,
+    #[msg("Staff roles must be a non-empty set of known roles")]
+    InvalidStaffRoles,
+    #[msg("Branch index is out of range")]
+    InvalidBranch,
+    #[msg("Too many branches for one promotion")]
+    TooManyBranches,
+    #[msg("Branch is not active")]
+    BranchInactive,
+    #[msg("Coupon cannot be redeemed at this branch")]
+    BranchNotAllowed,
+    #[msg("Promotion is geo-fenced; a location is required")]
+    LocationRequired,
+    #[msg("Promotion already has a geofence")]
+    GeofenceAlreadySet,
+    #[msg("Promotion has no geofence")]
+    GeofenceNotSet,
+    #[msg("Geofence radius must be greater than zero")]
+    InvalidRadius,
+    #[msg("GeoCell entry does not match the promotion or cell")]
+    InvalidGeoCellEntry,
+    #[msg("Invalid redemption schedule")]
+    InvalidSchedule,
+    #[msg("Coupon cannot be redeemed at this time")]
+    OutsideRedemptionWindow,
+    #[msg("Invalid launch window")]
+    InvalidLaunchWindow,
+    #[msg("Promotion is not open for minting yet")]
+    PromotionNotStarted,
+    #[msg("Per-wallet limit must be greater than zero")]
+    InvalidWalletLimit,
+    #[msg("Wallet has reached this promotion's mint limit")]
+    WalletLimitReached,
+    #[msg("Recipient is not on the promotion's allow-list")]
+    NotOnAllowList,
+    #[msg("Recipient has already claimed its allow-listed coupons")]
+    AllowListClaimed,
+    #[msg("Voucher does not match this promotion or recipient")]
+    InvalidVoucher,
+    #[msg("Voucher has expired")]
+    VoucherExpired,
+    #[msg("Expected an ed25519 signature over the voucher in the preceding instruction")]
+    MissingVoucherSignature,
+    #[msg("Voucher is not signed by the merchant or a voucher-issuing staff key")]
+    InvalidVoucherSigner,
+    #[msg("Promo code is not valid for this promotion")]
+    InvalidPromoCode,
+    #[msg("Revealed code does not match the commitment")]
+    CommitmentMismatch,
+    #[msg("Promo code must be revealed in a later slot than its commitment")]
+    RevealTooEarly,
+    #[msg("Discount cannot change once coupons have been minted")]
+    DiscountLocked,
+    #[msg("Uses per coupon must be greater than zero")]
+    InvalidUses,
+    #[msg("Multi-use coupons need their metadata account to record the uses left")]
+    MetadataRequired,
+    #[msg("Stamps per reward must be greater than zero")]
+    InvalidStampThreshold,
+    #[msg("Loyalty program is paused")]
+    LoyaltyProgramInactive,
+    #[msg("Not enough stamps for a reward")]
+    NotEnoughStamps,
+    #[msg("Bill is below the coupon's minimum spend or buy quantity")]
+    BelowMinimumSpend,
+    #[msg("Marketplace fee and royalty exceed the sale price")]
+    InvalidSettlement,
+    #[msg("Coupon NFT has not been deposited in the vault")]
+    NftNotEscrowed,
+    #[msg("Loyalty program account is required to stamp a loyalty card")]
+    LoyaltyProgramRequired,
+}
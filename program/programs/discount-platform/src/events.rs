@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
+use crate::state::{PresaleWindow, RedemptionSchedule, DiscountKind};
 
 // Re-export enums from state for use in events
-pub use crate::state::{BadgeType, DealSource, AuctionType};  
+pub use crate::state::{BadgeType, DealSource, AuctionType, PlatformSettings};  
 
 #[event]
 pub struct MarketplaceInitialized {
@@ -20,6 +21,140 @@ pub struct MerchantRegistered {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct MerchantRoyaltyUpdated {
+    pub merchant: Pubkey,
+    pub old_royalty_basis_points: u16,
+    pub new_royalty_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MerchantStaffAdded {
+    pub merchant: Pubkey,
+    pub staff: Pubkey,
+    pub roles: u8,
+    pub expires_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MerchantStaffRevoked {
+    pub merchant: Pubkey,
+    pub staff: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MerchantBranchAdded {
+    pub merchant: Pubkey,
+    pub branch: Pubkey,
+    pub index: u32,
+    pub name: String,
+    pub latitude: i32,
+    pub longitude: i32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MerchantBranchUpdated {
+    pub merchant: Pubkey,
+    pub branch: Pubkey,
+    pub name: String,
+    pub latitude: i32,
+    pub longitude: i32,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionGeofenceSet {
+    pub promotion: Pubkey,
+    pub latitude: i32,
+    pub longitude: i32,
+    pub geo_cell_id: u64,
+    pub radius_meters: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionGeofenceCleared {
+    pub promotion: Pubkey,
+    pub geo_cell_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionDiscountKindUpdated {
+    pub promotion: Pubkey,
+    pub discount_kind: DiscountKind,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionUsesUpdated {
+    pub promotion: Pubkey,
+    pub uses_per_coupon: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionWalletLimitUpdated {
+    pub promotion: Pubkey,
+    pub max_per_wallet: Option<u32>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionAllowListUpdated {
+    pub promotion: Pubkey,
+    pub allow_list_root: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionPromoCodesUpdated {
+    pub promotion: Pubkey,
+    pub promo_code_root: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionLaunchUpdated {
+    pub promotion: Pubkey,
+    pub start_timestamp: i64,
+    pub presale: Option<PresaleWindow>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PresaleWalletAdded {
+    pub promotion: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PresaleWalletRemoved {
+    pub promotion: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionScheduleUpdated {
+    pub promotion: Pubkey,
+    pub redemption_schedule: Option<RedemptionSchedule>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionBranchesUpdated {
+    pub promotion: Pubkey,
+    pub allowed_branches: Vec<u32>,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct PromotionCreated {
     pub promotion: Pubkey,
@@ -28,6 +163,46 @@ pub struct PromotionCreated {
     pub max_supply: u32,
     pub expiry_timestamp: i64,
     pub price: u64,
+    pub payment_mint: Option<Pubkey>,
+}
+
+#[event]
+pub struct PromotionUpdated {
+    pub promotion: Pubkey,
+    pub category: String,
+    pub description: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionStatusChanged {
+    pub promotion: Pubkey,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionExtended {
+    pub promotion: Pubkey,
+    pub old_expiry_timestamp: i64,
+    pub new_expiry_timestamp: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionSupplyIncreased {
+    pub promotion: Pubkey,
+    pub old_max_supply: u32,
+    pub new_max_supply: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromotionClosed {
+    pub promotion: Pubkey,
+    pub merchant: Pubkey,
+    pub coupons_minted: u32,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -38,6 +213,56 @@ pub struct CouponMinted {
     pub recipient: Pubkey,
     pub merchant: Pubkey,
     pub discount_percentage: u8,
+    pub price: u64,
+    pub marketplace_fee: u64,
+}
+
+#[event]
+pub struct VoucherClaimed {
+    pub merchant: Pubkey,
+    pub promotion: Pubkey,
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub signer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PromoCodeClaimed {
+    pub promotion: Pubkey,
+    pub claimer: Pubkey,
+    pub code_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LoyaltyProgramUpdated {
+    pub loyalty_program: Pubkey,
+    pub merchant: Pubkey,
+    pub reward_promotion: Pubkey,
+    pub stamps_required: u32,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LoyaltyCardStamped {
+    pub loyalty_card: Pubkey,
+    pub merchant: Pubkey,
+    pub user: Pubkey,
+    pub stamps: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LoyaltyRewardClaimed {
+    pub loyalty_card: Pubkey,
+    pub merchant: Pubkey,
+    pub user: Pubkey,
+    pub coupon: Pubkey,
+    pub stamps_spent: u32,
+    pub stamps_left: u32,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -56,7 +281,26 @@ pub struct CouponRedeemed {
     pub user: Pubkey,
     pub merchant: Pubkey,
     pub discount_percentage: u8,
+    pub discount_kind: DiscountKind,
     pub redemption_code: String,
+    pub purchase_amount: Option<u64>,   // Bill the merchant reported, if any
+    pub discount_amount: u64,
+    pub branch: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+// One use of a coupon; CouponRedeemed follows on the last
+#[event]
+pub struct CouponUsed {
+    pub coupon: Pubkey,
+    pub nft_mint: Pubkey,
+    pub user: Pubkey,
+    pub merchant: Pubkey,
+    pub use_number: u16,
+    pub uses_remaining: u16,
+    pub purchase_amount: Option<u64>,   // Bill the merchant reported, if any
+    pub discount_amount: u64,
+    pub branch: Option<Pubkey>,
     pub timestamp: i64,
 }
 
@@ -67,6 +311,7 @@ pub struct CouponListed {
     pub nft_mint: Pubkey,
     pub seller: Pubkey,
     pub price: u64,
+    pub payment_mint: Option<Pubkey>,
 }
 
 #[event]
@@ -86,6 +331,7 @@ pub struct CouponSold {
     pub buyer: Pubkey,
     pub price: u64,
     pub marketplace_fee: u64,
+    pub royalty: u64,
 }
 
 #[event]
@@ -170,6 +416,10 @@ pub struct TicketRedeemed {
     pub nft_mint: Pubkey,
     pub user: Pubkey,
     pub merchant: Pubkey,
+    pub discount_kind: DiscountKind,
+    pub purchase_amount: Option<u64>,   // Bill the merchant reported, if any
+    pub discount_amount: u64,
+    pub branch: Option<Pubkey>,
     pub redeemed_at: i64,
 }
 
@@ -182,6 +432,7 @@ pub struct GroupDealCreated {
     pub target_participants: u32,
     pub base_price: u64,
     pub deadline: i64,
+    pub payment_mint: Option<Pubkey>,
 }
 
 #[event]
@@ -219,6 +470,7 @@ pub struct AuctionCreated {
     pub starting_price: u64,
     pub reserve_price: u64,
     pub end_time: i64,
+    pub payment_mint: Option<Pubkey>,
 }
 
 #[event]
@@ -235,6 +487,7 @@ pub struct AuctionFinalized {
     pub auction: Pubkey,
     pub winner: Pubkey,
     pub final_price: u64,
+    pub royalty: u64,
     pub auction_type: AuctionType,
     pub finalized_at: i64,
 }
@@ -244,4 +497,43 @@ pub struct AuctionCancelled {
     pub auction: Pubkey,
     pub reason: String,
     pub timestamp: i64,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct MarketplaceFeeUpdated {
+    pub marketplace: Pubkey,
+    pub old_fee_basis_points: u16,
+    pub new_fee_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketplaceAuthorityProposed {
+    pub marketplace: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketplaceAuthorityTransferred {
+    pub marketplace: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketplacePauseChanged {
+    pub marketplace: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlatformConfigUpdated {
+    pub platform_config: Pubkey,
+    pub version: u32,
+    pub settings: PlatformSettings,
+    pub timestamp: i64,
+}
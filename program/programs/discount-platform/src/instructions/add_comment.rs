@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{Comment, Promotion, Merchant, UserStats,BadgeType,ReputationTier};
+use crate::state::{Comment, Promotion, Merchant, PlatformConfig, UserStats,BadgeType,ReputationTier};
 use crate::errors::CouponError;
 use crate::events::CommentAdded;
 
@@ -20,6 +20,12 @@ pub struct AddComment<'info> {
     /// CHECK: Merchant account to verify if user is the merchant authority
     pub merchant: UncheckedAccount<'info>,
     
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         init_if_needed,
         payer = user,
@@ -62,7 +68,7 @@ pub fn handler(
 
     // Update user stats for comment
     user_stats.total_comments += 1;
-    user_stats.add_reputation(1); // 1 point per comment
+    user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_comment);
     user_stats.last_activity = current_time;
 
     let comment = &mut ctx.accounts.comment;
@@ -1,16 +1,20 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::token::{Token, TokenAccount, Mint};
+use anchor_spl::token_interface::{self, TokenInterface};
 use crate::state::{
     CouponAuction,
     Bid,
     AuctionType,
     Coupon,
+    CouponLock,
     Marketplace,
+    Merchant,
+    PlatformConfig,
     UserStats,
     ReputationTier,
 };
 use crate::errors::CouponError;
+use super::payments::Currency;
 use crate::events::{
     AuctionCreated,
     BidPlaced,
@@ -35,12 +39,22 @@ pub struct CreateAuction<'info> {
     pub auction: Account<'info, CouponAuction>,
     
     #[account(
+        mut,
         constraint = coupon.owner == seller.key() @ CouponError::NotCouponOwner,
         constraint = !coupon.is_redeemed @ CouponError::CouponAlreadyRedeemed,
         constraint = coupon.expiry_timestamp > Clock::get()?.unix_timestamp @ CouponError::CouponExpired
     )]
     pub coupon: Account<'info, Coupon>,
     
+    /// SPL mint bids are paid in; omit for SOL
+    pub payment_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         init_if_needed,
         payer = seller,
@@ -66,14 +80,15 @@ pub fn create_auction(
     min_bid_increment: u64,
 ) -> Result<()> {
     let auction = &mut ctx.accounts.auction;
-    let coupon = &ctx.accounts.coupon;
+    let coupon = &mut ctx.accounts.coupon;
+    let settings = &ctx.accounts.platform_config.settings;
     let clock = Clock::get()?;
     
     // Validation
     require!(starting_price > 0, CouponError::InvalidPrice);
     require!(reserve_price > 0, CouponError::InvalidPrice);
-    require!(duration_seconds >= 300, CouponError::InvalidExpiry); // Min 5 minutes
-    require!(duration_seconds <= 604800, CouponError::InvalidExpiry); // Max 7 days
+    require!(duration_seconds >= settings.auction_min_duration, CouponError::InvalidExpiry);
+    require!(duration_seconds <= settings.auction_max_duration, CouponError::InvalidExpiry);
     
     match auction_type {
         AuctionType::English => {
@@ -96,6 +111,8 @@ pub fn create_auction(
         },
     }
     
+    require!(coupon.lock == CouponLock::None, CouponError::CouponLocked);
+    
     // Initialize auction
     auction.coupon = coupon.key();
     auction.seller = ctx.accounts.seller.key();
@@ -114,8 +131,12 @@ pub fn create_auction(
     auction.is_active = true;
     auction.is_finalized = false;
     auction.auto_extend = auto_extend;
-    auction.extension_seconds = 300; // 5 minute extension
+    auction.extension_seconds = settings.auction_extension_seconds;
     auction.min_bid_increment = min_bid_increment;
+    auction.payment_mint = ctx.accounts.payment_mint.as_ref().map(|mint| mint.key());
+    
+    // Hold the coupon until the auction settles
+    coupon.lock = CouponLock::Auctioned;
     
     // Update user stats
     let user_stats = &mut ctx.accounts.user_stats;
@@ -133,7 +154,7 @@ pub fn create_auction(
     }
     
     user_stats.total_listings += 1;
-    user_stats.add_reputation(5); // Reputation for creating auction
+    user_stats.add_reputation(settings.reputation_per_auction);
     user_stats.last_activity = clock.unix_timestamp;
     
     emit!(AuctionCreated {
@@ -144,6 +165,7 @@ pub fn create_auction(
         starting_price,
         reserve_price,
         end_time: auction.end_time,
+        payment_mint: auction.payment_mint,
     });
     
     // Fixed: Remove Debug format for auction_type
@@ -182,6 +204,13 @@ pub struct PlaceBid<'info> {
     )]
     pub auction: Account<'info, CouponAuction>,
     
+    #[account(
+        seeds = [b"marketplace"],
+        bump,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
+    pub marketplace: Account<'info, Marketplace>,
+    
     /// CHECK: Escrow account for bid (PDA)
     #[account(
         mut,
@@ -194,6 +223,22 @@ pub struct PlaceBid<'info> {
     #[account(mut)]
     pub previous_bidder: UncheckedAccount<'info>,
     
+    // SPL payment accounts, only needed when auction.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub bidder_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub escrow_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub previous_bidder_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         init_if_needed,
         payer = bidder,
@@ -233,22 +278,35 @@ pub fn place_bid(
         },
     }
     
+    let currency = Currency::resolve(
+        auction.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let bidder = ctx.accounts.bidder.to_account_info();
+    let escrow = ctx.accounts.escrow.to_account_info();
+    let escrow_account = currency.account_for(&escrow, ctx.accounts.escrow_payment_account.as_deref())?;
+    
     // Escrow the bid
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.bidder.to_account_info(),
-                to: ctx.accounts.escrow.to_account_info(),
-            },
-        ),
+    currency.transfer(
+        currency.account_for(&bidder, ctx.accounts.bidder_payment_account.as_deref())?,
+        escrow_account.clone(),
+        bidder,
+        &[],
         bid_amount,
     )?;
     
     // For English auctions, refund previous bidder
     if auction.auction_type == AuctionType::English {
-        if let Some(_prev_bidder) = auction.highest_bidder {
+        if let Some(prev_bidder) = auction.highest_bidder {
             if auction.current_bid > 0 {
+                require_keys_eq!(
+                    ctx.accounts.previous_bidder.key(),
+                    prev_bidder,
+                    CouponError::InvalidPaymentAccount
+                );
+                
                 // FIX: Store auction key before creating seeds
                 let auction_key = auction.key();
                 let escrow_seeds = &[
@@ -258,15 +316,14 @@ pub fn place_bid(
                 ];
                 let signer_seeds = &[&escrow_seeds[..]];
                 
-                transfer(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.system_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.escrow.to_account_info(),
-                            to: ctx.accounts.previous_bidder.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
+                currency.transfer(
+                    escrow_account,
+                    currency.account_for(
+                        &ctx.accounts.previous_bidder.to_account_info(),
+                        ctx.accounts.previous_bidder_payment_account.as_deref(),
+                    )?,
+                    escrow,
+                    signer_seeds,
                     auction.current_bid,
                 )?;
                 
@@ -310,7 +367,7 @@ pub fn place_bid(
         user_stats.joined_at = clock.unix_timestamp;
     }
     
-    user_stats.add_reputation(2); // Reputation for participating
+    user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_bid);
     user_stats.last_activity = clock.unix_timestamp;
     
     emit!(BidPlaced {
@@ -346,6 +403,11 @@ pub struct BuyDutchAuction<'info> {
     )]
     pub coupon: Account<'info, Coupon>,
     
+    #[account(
+        seeds = [b"marketplace"],
+        bump,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
     pub marketplace: Account<'info, Marketplace>,
     
     /// CHECK: Seller receiving payment
@@ -362,6 +424,36 @@ pub struct BuyDutchAuction<'info> {
     )]
     pub marketplace_authority: SystemAccount<'info>,
     
+    #[account(
+        constraint = coupon.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+    
+    /// CHECK: Merchant authority receiving the royalty
+    #[account(
+        mut,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant_authority: UncheckedAccount<'info>,
+    
+    // SPL payment accounts, only needed when auction.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub buyer_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub seller_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub marketplace_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub merchant_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         init_if_needed,
         payer = buyer,
@@ -392,34 +484,65 @@ pub fn buy_dutch_auction(ctx: Context<BuyDutchAuction>) -> Result<()> {
     // Calculate fees
     let marketplace = &ctx.accounts.marketplace;
     let marketplace_fee = (current_price * marketplace.fee_basis_points as u64) / 10000;
-    let seller_amount = current_price - marketplace_fee;
+    let royalty = ctx.accounts.merchant.royalty_for(
+        current_price,
+        coupon.last_sale_price,
+        ctx.accounts.platform_config.settings.max_royalty_basis_points,
+    );
+    let seller_amount = current_price
+        .checked_sub(marketplace_fee)
+        .and_then(|amount| amount.checked_sub(royalty))
+        .ok_or(CouponError::InvalidSettlement)?;
+    
+    let currency = Currency::resolve(
+        auction.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let buyer = ctx.accounts.buyer.to_account_info();
+    let buyer_account = currency.account_for(&buyer, ctx.accounts.buyer_payment_account.as_deref())?;
     
     // Pay seller
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: ctx.accounts.seller.to_account_info(),
-            },
-        ),
+    currency.transfer(
+        buyer_account.clone(),
+        currency.account_for(
+            &ctx.accounts.seller.to_account_info(),
+            ctx.accounts.seller_payment_account.as_deref(),
+        )?,
+        buyer.clone(),
+        &[],
         seller_amount,
     )?;
     
     // Pay marketplace fee
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: ctx.accounts.marketplace_authority.to_account_info(),
-            },
-        ),
+    currency.transfer(
+        buyer_account.clone(),
+        currency.account_for(
+            &ctx.accounts.marketplace_authority.to_account_info(),
+            ctx.accounts.marketplace_payment_account.as_deref(),
+        )?,
+        buyer.clone(),
+        &[],
         marketplace_fee,
     )?;
     
+    // Pay merchant royalty
+    currency.transfer(
+        buyer_account,
+        currency.account_for(
+            &ctx.accounts.merchant_authority.to_account_info(),
+            ctx.accounts.merchant_payment_account.as_deref(),
+        )?,
+        buyer,
+        &[],
+        royalty,
+    )?;
+    
     // Transfer coupon ownership
     coupon.owner = ctx.accounts.buyer.key();
+    coupon.last_sale_price = current_price;
+    coupon.lock = CouponLock::None;
     
     // Finalize auction
     auction.is_active = false;
@@ -443,13 +566,14 @@ pub fn buy_dutch_auction(ctx: Context<BuyDutchAuction>) -> Result<()> {
     }
     
     user_stats.total_purchases += 1;
-    user_stats.add_reputation(5);
+    user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_purchase);
     user_stats.last_activity = clock.unix_timestamp;
     
     emit!(AuctionFinalized {
         auction: auction.key(),
         winner: ctx.accounts.buyer.key(),
         final_price: current_price,
+        royalty,
         auction_type: AuctionType::Dutch,
         finalized_at: clock.unix_timestamp,
     });
@@ -505,6 +629,38 @@ pub struct FinalizeAuction<'info> {
     )]
     pub marketplace_authority: SystemAccount<'info>,
     
+    #[account(
+        constraint = coupon.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+    
+    /// CHECK: Merchant authority receiving the royalty
+    #[account(
+        mut,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant_authority: UncheckedAccount<'info>,
+    
+    // SPL payment accounts, only needed when auction.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub escrow_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub seller_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub winner_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub marketplace_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub merchant_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         mut,
         seeds = [b"user_stats", winner.key().as_ref()],
@@ -521,13 +677,28 @@ pub fn finalize_auction(ctx: Context<FinalizeAuction>) -> Result<()> {
     let coupon = &mut ctx.accounts.coupon;
     let clock = Clock::get()?;
     
+    let currency = Currency::resolve(
+        auction.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let escrow = ctx.accounts.escrow.to_account_info();
+    let escrow_account = currency.account_for(&escrow, ctx.accounts.escrow_payment_account.as_deref())?;
+    
     // Check if reserve price met
     if auction.current_bid < auction.reserve_price {
         msg!("⚠️ Reserve price not met. Auction cancelled.");
         
         // Refund highest bidder if exists
-        if let Some(_bidder) = auction.highest_bidder {
+        if let Some(bidder) = auction.highest_bidder {
             if auction.current_bid > 0 {
+                require_keys_eq!(
+                    ctx.accounts.winner.key(),
+                    bidder,
+                    CouponError::InvalidPaymentAccount
+                );
+                
                 // FIX: Store auction key before creating seeds
                 let auction_key = auction.key();
                 let escrow_seeds = &[
@@ -537,15 +708,14 @@ pub fn finalize_auction(ctx: Context<FinalizeAuction>) -> Result<()> {
                 ];
                 let signer_seeds = &[&escrow_seeds[..]];
                 
-                transfer(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.system_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.escrow.to_account_info(),
-                            to: ctx.accounts.winner.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
+                currency.transfer(
+                    escrow_account,
+                    currency.account_for(
+                        &ctx.accounts.winner.to_account_info(),
+                        ctx.accounts.winner_payment_account.as_deref(),
+                    )?,
+                    escrow,
+                    signer_seeds,
                     auction.current_bid,
                 )?;
                 
@@ -555,6 +725,7 @@ pub fn finalize_auction(ctx: Context<FinalizeAuction>) -> Result<()> {
         
         auction.is_active = false;
         auction.is_finalized = true;
+        coupon.lock = CouponLock::None;
         
         emit!(AuctionCancelled {
             auction: auction.key(),
@@ -574,7 +745,15 @@ pub fn finalize_auction(ctx: Context<FinalizeAuction>) -> Result<()> {
     // Calculate fees
     let marketplace = &ctx.accounts.marketplace;
     let marketplace_fee = (final_price * marketplace.fee_basis_points as u64) / 10000;
-    let seller_amount = final_price - marketplace_fee;
+    let royalty = ctx.accounts.merchant.royalty_for(
+        final_price,
+        coupon.last_sale_price,
+        ctx.accounts.platform_config.settings.max_royalty_basis_points,
+    );
+    let seller_amount = final_price
+        .checked_sub(marketplace_fee)
+        .and_then(|amount| amount.checked_sub(royalty))
+        .ok_or(CouponError::InvalidSettlement)?;
     
     // FIX: Store auction key before creating seeds
     let auction_key = auction.key();
@@ -586,38 +765,50 @@ pub fn finalize_auction(ctx: Context<FinalizeAuction>) -> Result<()> {
     let signer_seeds = &[&escrow_seeds[..]];
     
     // Pay seller
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow.to_account_info(),
-                to: ctx.accounts.seller.to_account_info(),
-            },
-            signer_seeds,
-        ),
+    currency.transfer(
+        escrow_account.clone(),
+        currency.account_for(
+            &ctx.accounts.seller.to_account_info(),
+            ctx.accounts.seller_payment_account.as_deref(),
+        )?,
+        escrow.clone(),
+        signer_seeds,
         seller_amount,
     )?;
     
     // Pay marketplace fee
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow.to_account_info(),
-                to: ctx.accounts.marketplace_authority.to_account_info(),
-            },
-            signer_seeds,
-        ),
+    currency.transfer(
+        escrow_account.clone(),
+        currency.account_for(
+            &ctx.accounts.marketplace_authority.to_account_info(),
+            ctx.accounts.marketplace_payment_account.as_deref(),
+        )?,
+        escrow.clone(),
+        signer_seeds,
         marketplace_fee,
     )?;
     
+    // Pay merchant royalty
+    currency.transfer(
+        escrow_account,
+        currency.account_for(
+            &ctx.accounts.merchant_authority.to_account_info(),
+            ctx.accounts.merchant_payment_account.as_deref(),
+        )?,
+        escrow,
+        signer_seeds,
+        royalty,
+    )?;
+    
     // Transfer coupon to winner
     coupon.owner = winner_key;
+    coupon.last_sale_price = final_price;
+    coupon.lock = CouponLock::None;
     
     // Update winner stats
     let winner_stats = &mut ctx.accounts.winner_stats;
     winner_stats.total_purchases += 1;
-    winner_stats.add_reputation(5);
+    winner_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_purchase);
     winner_stats.last_activity = clock.unix_timestamp;
     
     // Finalize auction
@@ -628,6 +819,7 @@ pub fn finalize_auction(ctx: Context<FinalizeAuction>) -> Result<()> {
         auction: auction.key(),
         winner: winner_key,
         final_price,
+        royalty,
         auction_type: auction.auction_type,
         finalized_at: clock.unix_timestamp,
     });
@@ -636,6 +828,7 @@ pub fn finalize_auction(ctx: Context<FinalizeAuction>) -> Result<()> {
     msg!("✅ Winner: {}", winner_key);
     msg!("✅ Final price: {} lamports", final_price);
     msg!("✅ Seller received: {} lamports", seller_amount);
+    msg!("✅ Merchant royalty: {} lamports", royalty);
     
     Ok(())
 }
@@ -655,12 +848,19 @@ pub struct CancelAuction<'info> {
     )]
     pub auction: Account<'info, CouponAuction>,
     
+    #[account(
+        mut,
+        constraint = coupon.key() == auction.coupon @ CouponError::WrongCoupon
+    )]
+    pub coupon: Account<'info, Coupon>,
+    
     #[account(mut)]
     pub seller: Signer<'info>,
 }
 
 pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
     let auction = &ctx.accounts.auction;
+    ctx.accounts.coupon.lock = CouponLock::None;
     
     emit!(AuctionCancelled {
         auction: auction.key(),
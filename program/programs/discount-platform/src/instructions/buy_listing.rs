@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
-use crate::state::{Coupon, Listing, Marketplace};
+use anchor_spl::token_interface::{self, TokenInterface, TokenAccount, Mint, close_account, CloseAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::{Coupon, CouponLock, Listing, Marketplace, Merchant, PlatformConfig};
 use crate::errors::CouponError;
 use crate::events::CouponSold;
+use super::escrow::NftEscrow;
+use super::payments::Currency;
 
 pub fn handler(ctx: Context<BuyListedCoupon>) -> Result<()> {
     let listing = &mut ctx.accounts.listing;
@@ -14,44 +17,111 @@ pub fn handler(ctx: Context<BuyListedCoupon>) -> Result<()> {
 
     let marketplace = &ctx.accounts.marketplace;
     let marketplace_fee = (listing.price * marketplace.fee_basis_points as u64) / 10000;
-    let seller_amount = listing.price - marketplace_fee;
+    let royalty = ctx.accounts.merchant.royalty_for(
+        listing.price,
+        coupon.last_sale_price,
+        ctx.accounts.platform_config.settings.max_royalty_basis_points,
+    );
+    let seller_amount = listing.price
+        .checked_sub(marketplace_fee)
+        .and_then(|amount| amount.checked_sub(royalty))
+        .ok_or(CouponError::InvalidSettlement)?;
+
+    let currency = Currency::resolve(
+        listing.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let buyer = ctx.accounts.buyer.to_account_info();
+    let buyer_account = currency.account_for(&buyer, ctx.accounts.buyer_payment_account.as_deref())?;
 
     // Transfer payment to seller
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: ctx.accounts.seller.to_account_info(),
-            },
-        ),
+    currency.transfer(
+        buyer_account.clone(),
+        currency.account_for(
+            &ctx.accounts.seller.to_account_info(),
+            ctx.accounts.seller_payment_account.as_deref(),
+        )?,
+        buyer.clone(),
+        &[],
         seller_amount,
     )?;
 
     // Transfer marketplace fee
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: ctx.accounts.marketplace_authority.to_account_info(),
-            },
-        ),
+    currency.transfer(
+        buyer_account.clone(),
+        currency.account_for(
+            &ctx.accounts.marketplace_authority.to_account_info(),
+            ctx.accounts.marketplace_payment_account.as_deref(),
+        )?,
+        buyer.clone(),
+        &[],
         marketplace_fee,
     )?;
 
+    // Transfer merchant royalty
+    currency.transfer(
+        buyer_account,
+        currency.account_for(
+            &ctx.accounts.merchant_authority.to_account_info(),
+            ctx.accounts.merchant_payment_account.as_deref(),
+        )?,
+        buyer,
+        &[],
+        royalty,
+    )?;
+
+    // Release the escrowed NFT to the buyer
+    let coupon_key = coupon.key();
+    let listing_seeds = &[
+        b"listing",
+        coupon_key.as_ref(),
+        &[ctx.bumps.listing],
+    ];
+    let signer_seeds = &[&listing_seeds[..]];
+
+    let escrow = NftEscrow {
+        mint: &ctx.accounts.nft_mint,
+        token_program: &ctx.accounts.token_program,
+    };
+    escrow.release(
+        ctx.accounts.listing_vault.to_account_info(),
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.buyer.to_account_info(),
+        listing.to_account_info(),
+        signer_seeds,
+    )?;
+
+    // Close the vault to refund its rent to the seller. A hooked coupon leaves
+    // with the buyer's own transfer, so that vault stays for the next listing.
+    if !escrow.is_hooked() {
+        close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.listing_vault.to_account_info(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: listing.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+
     emit!(CouponSold {
         listing: listing.key(),
         coupon: coupon.key(),
-        nft_mint: coupon.mint.unwrap_or(Pubkey::default()),
+        nft_mint: ctx.accounts.nft_mint.key(),
         seller: listing.seller,
         buyer: ctx.accounts.buyer.key(),
         price: listing.price,
         marketplace_fee,
+        royalty,
     });
 
     // Update coupon owner and deactivate listing
     coupon.owner = ctx.accounts.buyer.key();
+    coupon.last_sale_price = listing.price;
+    coupon.lock = CouponLock::None;
     listing.is_active = false;
 
     Ok(())
@@ -61,7 +131,10 @@ pub fn handler(ctx: Context<BuyListedCoupon>) -> Result<()> {
 pub struct BuyListedCoupon<'info> {
     #[account(
         mut,
-        constraint = listing.is_active @ CouponError::ListingInactive
+        seeds = [b"listing", coupon.key().as_ref()],
+        bump,
+        constraint = listing.is_active @ CouponError::ListingInactive,
+        close = seller
     )]
     pub listing: Account<'info, Listing>,
     #[account(
@@ -69,6 +142,30 @@ pub struct BuyListedCoupon<'info> {
         constraint = coupon.key() == listing.coupon @ CouponError::WrongCoupon
     )]
     pub coupon: Account<'info, Coupon>,
+    /// CHECK: NFT Mint
+    #[account(
+        constraint = coupon.mint == Some(nft_mint.key()) @ CouponError::WrongCoupon
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Listing vault token account (PDA) holding the NFT
+    #[account(
+        mut,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump,
+        constraint = listing_vault.amount == 1 @ CouponError::NftNotEscrowed
+    )]
+    pub listing_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program
+    )]
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
     pub marketplace: Account<'info, Marketplace>,
     #[account(
         mut,
@@ -84,5 +181,33 @@ pub struct BuyListedCoupon<'info> {
     )]
     /// CHECK: Marketplace authority for fee collection
     pub marketplace_authority: UncheckedAccount<'info>,
+    #[account(
+        constraint = coupon.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub merchant: Account<'info, Merchant>,
+    #[account(
+        mut,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::NotMerchantAuthority
+    )]
+    /// CHECK: Merchant authority receiving the royalty
+    pub merchant_authority: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    // SPL payment accounts, only needed when listing.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub buyer_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub seller_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub marketplace_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub merchant_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -1,9 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program::{transfer as system_transfer, Transfer as SystemTransfer};
-use anchor_spl::token::{Token, TokenAccount, Mint, transfer, Transfer};
-use crate::state::{StakingPool, StakeAccount};
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{Coupon, CouponLock, StakingPool, StakeAccount};
 use crate::errors::CouponError;
 use crate::events::RewardsClaimed;
+use super::escrow::NftEscrow;
 
 #[derive(Accounts)]
 pub struct ClaimRewards<'info> {
@@ -23,11 +24,17 @@ pub struct ClaimRewards<'info> {
     )]
     pub staking_pool: Account<'info, StakingPool>,
 
+    #[account(
+        mut,
+        constraint = coupon.key() == stake_account.coupon @ CouponError::WrongCoupon
+    )]
+    pub coupon: Account<'info, Coupon>,
+
     /// CHECK: NFT Mint
     #[account(
         constraint = nft_mint.key() == stake_account.nft_mint
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: Staking vault token account (PDA)
     #[account(
@@ -35,7 +42,7 @@ pub struct ClaimRewards<'info> {
         seeds = [b"stake_vault", nft_mint.key().as_ref()],
         bump
     )]
-    pub stake_vault: Account<'info, TokenAccount>,
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
 
     /// CHECK: User's token account
     #[account(
@@ -43,7 +50,7 @@ pub struct ClaimRewards<'info> {
         constraint = user_token_account.mint == nft_mint.key(),
         constraint = user_token_account.owner == user.key()
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub user: Signer<'info>,
@@ -55,7 +62,7 @@ pub struct ClaimRewards<'info> {
     )]
     pub reward_pool: SystemAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -78,7 +85,7 @@ pub fn handler(ctx: Context<ClaimRewards>) -> Result<()> {
 
     require!(rewards > 0, CouponError::InvalidPrice);
 
-    // Transfer NFT back to user
+    // Transfer NFT back to user; a hooked coupon is approved for the user to take out
     let nft_mint_key = ctx.accounts.nft_mint.key();
     let vault_seeds = &[
         b"stake_vault",
@@ -87,14 +94,17 @@ pub fn handler(ctx: Context<ClaimRewards>) -> Result<()> {
     ];
     let vault_signer = &[&vault_seeds[..]];
 
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.stake_vault.to_account_info(),
-        to: ctx.accounts.user_token_account.to_account_info(),
-        authority: ctx.accounts.stake_vault.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, vault_signer);
-    transfer(cpi_ctx, 1)?;
+    NftEscrow {
+        mint: &ctx.accounts.nft_mint,
+        token_program: &ctx.accounts.token_program,
+    }
+    .release(
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.user_token_account.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        vault_signer,
+    )?;
 
     // Transfer rewards (SOL) to user
     let cpi_accounts = SystemTransfer {
@@ -109,6 +119,7 @@ pub fn handler(ctx: Context<ClaimRewards>) -> Result<()> {
     stake_account.rewards_earned = rewards;
     stake_account.is_active = false;
     stake_account.claimed_at = Some(current_time);
+    ctx.accounts.coupon.lock = CouponLock::None;
 
     // Update pool stats
     staking_pool.total_staked -= stake_account.amount_staked;
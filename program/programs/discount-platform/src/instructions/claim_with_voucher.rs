@@ -0,0 +1,253 @@
+// src/instructions/claim_with_voucher.rs
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::{
+    Coupon, Promotion, Merchant, MerchantStaff, Marketplace, PlatformConfig, UserStats,
+    WalletMintCount, MintVoucher, VoucherNonce,
+};
+use crate::errors::CouponError;
+use crate::events::{CouponMinted, VoucherClaimed};
+use super::mint_coupon::{check_mintable, record_wallet_mint, record_purchase, init_coupon, MerchantSignedMint};
+
+// Free mint against a merchant-signed MintVoucher. Nobody from the merchant signs
+// the transaction, so the merchant PDA is the mint and metadata authority here
+// (see MerchantSignedMint).
+#[derive(Accounts)]
+#[instruction(coupon_id: u64, voucher: MintVoucher)]
+pub struct ClaimWithVoucher<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VoucherNonce::INIT_SPACE,
+        seeds = [b"voucher_nonce", merchant.key().as_ref(), &voucher.nonce.to_le_bytes()],
+        bump
+    )]
+    pub voucher_nonce: Box<Account<'info, VoucherNonce>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Coupon::INIT_SPACE,
+        seeds = [b"coupon", promotion.key().as_ref(), &promotion.current_supply.to_le_bytes()],
+        bump
+    )]
+    pub coupon: Box<Account<'info, Coupon>>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = merchant,
+        mint::freeze_authority = merchant
+    )]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = recipient
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metadata account
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Master Edition account
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = promotion.key() == voucher.promotion @ CouponError::InvalidVoucher
+    )]
+    pub promotion: Box<Account<'info, Promotion>>,
+
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // Staff grant of the voucher's signer; omit when the merchant authority signed
+    #[account(
+        constraint = merchant_staff.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub merchant_staff: Option<Box<Account<'info, MerchantStaff>>>,
+
+    #[account(
+        mut,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+
+    /// CHECK: Recipient named in the voucher
+    #[account(
+        constraint = recipient.key() == voucher.recipient @ CouponError::InvalidVoucher
+    )]
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [b"user_stats", recipient.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + WalletMintCount::INIT_SPACE,
+        seeds = [b"wallet_mints", promotion.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub wallet_mints: Box<Account<'info, WalletMintCount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Sysvar Instructions, read for the ed25519 signature check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<ClaimWithVoucher>, coupon_id: u64, voucher: MintVoucher) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(voucher.expires_at > current_time, CouponError::VoucherExpired);
+
+    let signer = voucher_signer(&ctx.accounts.sysvar_instructions, &voucher)?;
+    require!(
+        ctx.accounts.merchant.is_authorized_for(
+            &signer,
+            ctx.accounts.merchant_staff.as_deref().map(|staff| &**staff),
+            MerchantStaff::ROLE_ISSUE_VOUCHERS,
+            current_time,
+        ),
+        CouponError::InvalidVoucherSigner
+    );
+
+    // The voucher stands in for the allow-list and pre-sale checks: the merchant
+    // picked the recipient. Start time, supply and per-wallet limits still apply.
+    check_mintable(&ctx.accounts.promotion, current_time, false)?;
+    record_wallet_mint(
+        &mut ctx.accounts.wallet_mints,
+        &ctx.accounts.promotion,
+        ctx.accounts.recipient.key(),
+        ctx.bumps.wallet_mints,
+    )?;
+
+    let voucher_nonce = &mut ctx.accounts.voucher_nonce;
+    voucher_nonce.merchant = ctx.accounts.merchant.key();
+    voucher_nonce.nonce = voucher.nonce;
+    voucher_nonce.promotion = voucher.promotion;
+    voucher_nonce.recipient = voucher.recipient;
+    voucher_nonce.signer = signer;
+    voucher_nonce.claimed_at = current_time;
+    voucher_nonce.bump = ctx.bumps.voucher_nonce;
+
+    let user_stats = &mut ctx.accounts.user_stats;
+    record_purchase(
+        user_stats,
+        ctx.accounts.recipient.key(),
+        ctx.accounts.platform_config.settings.reputation_per_purchase,
+        current_time,
+    );
+
+    let promotion = &mut ctx.accounts.promotion;
+    let coupon = &mut ctx.accounts.coupon;
+    init_coupon(
+        coupon,
+        coupon_id,
+        promotion,
+        ctx.accounts.recipient.key(),
+        Some(ctx.accounts.nft_mint.key()),
+        current_time,
+    );
+
+    MerchantSignedMint {
+        merchant: ctx.accounts.merchant.to_account_info(),
+        merchant_authority: ctx.accounts.merchant.authority,
+        merchant_bump: ctx.bumps.merchant,
+        nft_mint: ctx.accounts.nft_mint.to_account_info(),
+        token_account: ctx.accounts.token_account.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        master_edition: ctx.accounts.master_edition.to_account_info(),
+        payer: ctx.accounts.payer.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    }
+    .invoke(
+        coupon.metadata_name(),
+        coupon.metadata_uri.clone(),
+        ctx.accounts.merchant.royalty_basis_points
+            .min(ctx.accounts.platform_config.settings.max_royalty_basis_points),
+    )?;
+
+    promotion.current_supply += 1;
+    ctx.accounts.merchant.total_coupons_created += 1;
+    ctx.accounts.marketplace.total_coupons += 1;
+
+    emit!(CouponMinted {
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        promotion: promotion.key(),
+        recipient: ctx.accounts.recipient.key(),
+        merchant: coupon.merchant,
+        discount_percentage: coupon.discount_percentage,
+        price: 0,
+        marketplace_fee: 0,
+    });
+
+    emit!(VoucherClaimed {
+        merchant: coupon.merchant,
+        promotion: promotion.key(),
+        recipient: voucher.recipient,
+        nonce: voucher.nonce,
+        signer,
+        timestamp: current_time,
+    });
+
+    msg!("✅ Voucher {} claimed for {}", voucher.nonce, voucher.recipient);
+
+    Ok(())
+}
+
+// Key that signed `voucher`, taken from the ed25519 program instruction right
+// before this one. The runtime has already rejected the transaction if that
+// signature is invalid.
+fn voucher_signer(sysvar_instructions: &AccountInfo, voucher: &MintVoucher) -> Result<Pubkey> {
+    let current_index = load_current_index_checked(sysvar_instructions)?;
+    require!(current_index > 0, CouponError::MissingVoucherSignature);
+
+    let instruction = load_instruction_at_checked(current_index as usize - 1, sysvar_instructions)?;
+    require_keys_eq!(instruction.program_id, ed25519_program::ID, CouponError::MissingVoucherSignature);
+
+    voucher
+        .signer_in(&instruction.data)
+        .ok_or(error!(CouponError::MissingVoucherSignature))
+}
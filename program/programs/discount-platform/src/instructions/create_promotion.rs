@@ -1,6 +1,7 @@
 // src/instructions/create_promotion.rs
 use anchor_lang::prelude::*;
-use crate::state::{Promotion, Merchant};
+use anchor_spl::token_interface::Mint;
+use crate::state::{Promotion, DiscountKind, Merchant, MerchantStaff};
 use crate::errors::CouponError;
 use crate::events::PromotionCreated;
 
@@ -15,8 +16,26 @@ pub struct CreateCouponPromotion<'info> {
     )]
     pub promotion: Account<'info, Promotion>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_CREATE_PROMOTIONS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
     pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+    
+    /// SPL mint the promotion is priced in; omit to price in SOL
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -41,14 +60,23 @@ pub fn handler(
     let promotion = &mut ctx.accounts.promotion;
     promotion.merchant = ctx.accounts.merchant.key();
     promotion.discount_percentage = discount_percentage;
+    promotion.discount_kind = DiscountKind::Percentage { percent: discount_percentage };
     promotion.max_supply = max_supply;
     promotion.current_supply = 0;
+    promotion.total_discount_given = 0;
+    promotion.max_per_wallet = None;
+    promotion.uses_per_coupon = 1;
     promotion.expiry_timestamp = expiry_timestamp;
     promotion.category = category;
     promotion.description = description;
     promotion.price = price;
+    promotion.payment_mint = ctx.accounts.payment_mint.as_ref().map(|mint| mint.key());
     promotion.is_active = true;
     promotion.created_at = Clock::get()?.unix_timestamp;
+    promotion.start_timestamp = promotion.created_at;
+    promotion.presale = None;
+    promotion.allow_list_root = None;
+    promotion.promo_code_root = None;
     
     // Initialize location data (default to no location)
     promotion.location = crate::state::Location {
@@ -61,6 +89,8 @@ pub fn handler(
     promotion.geo_cell_id = 0;
     promotion.radius_meters = 0;
     promotion.is_location_based = false;
+    promotion.allowed_branches = Vec::new();
+    promotion.redemption_schedule = None;
 
     emit!(PromotionCreated {
         promotion: promotion.key(),
@@ -69,6 +99,7 @@ pub fn handler(
         max_supply,
         expiry_timestamp,
         price,
+        payment_mint: promotion.payment_mint,
     });
 
     Ok(())
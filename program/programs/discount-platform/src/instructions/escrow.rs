@@ -0,0 +1,89 @@
+// src/instructions/escrow.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022;
+use anchor_spl::token_interface::{
+    Mint, TokenInterface, approve, Approve, transfer_checked, TransferChecked,
+};
+
+// Moves a coupon NFT in and out of the program's vaults (listings and stakes).
+// Classic mints move by CPI. Hooked Token-2022 mints can't: the transfer would
+// call back into this program through the hook, and Solana rejects that
+// re-entry. For those the instruction only locks or releases the coupon, and
+// the client sends a plain Token-2022 transfer right after it:
+//   - deposit: the owner transfers into the vault, which the hook allows while
+//     the coupon is locked for that vault
+//   - release: the vault approves the recipient as delegate for the NFT, and
+//     the recipient transfers it out
+pub struct NftEscrow<'a, 'info> {
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+}
+
+impl<'a, 'info> NftEscrow<'a, 'info> {
+    pub fn is_hooked(&self) -> bool {
+        *self.mint.to_account_info().owner == token_2022::ID
+    }
+
+    // Moves the NFT from `owner`'s token account into `vault`
+    pub fn deposit(
+        &self,
+        from: AccountInfo<'info>,
+        vault: AccountInfo<'info>,
+        owner: AccountInfo<'info>,
+    ) -> Result<()> {
+        if self.is_hooked() {
+            return Ok(());
+        }
+        self.transfer(from, vault, owner, &[])
+    }
+
+    // Hands the NFT in `vault` to `recipient`. `to` is the recipient's token
+    // account for classic mints; hooked mints only need the recipient wallet.
+    pub fn release(
+        &self,
+        vault: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        recipient: AccountInfo<'info>,
+        vault_authority: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        if self.is_hooked() {
+            return approve(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    Approve {
+                        to: vault,
+                        delegate: recipient,
+                        authority: vault_authority,
+                    },
+                    signer_seeds,
+                ),
+                1,
+            );
+        }
+        self.transfer(vault, to, vault_authority, signer_seeds)
+    }
+
+    fn transfer(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from,
+                    mint: self.mint.to_account_info(),
+                    to,
+                    authority,
+                },
+                signer_seeds,
+            ),
+            1,
+            self.mint.decimals,
+        )
+    }
+}
@@ -0,0 +1,236 @@
+// src/instructions/geo_cells.rs
+use anchor_lang::prelude::*;
+use crate::state::{Promotion, Merchant, MerchantStaff, Location, GeoCell, GeoCellEntry};
+use crate::errors::CouponError;
+use crate::events::{PromotionGeofenceSet, PromotionGeofenceCleared};
+
+// ============================================================================
+// Set Geofence (locates the promotion and adds it to its GeoCell)
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(latitude: f64, longitude: f64)]
+pub struct SetPromotionGeofence<'info> {
+    #[account(
+        mut,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = !promotion.is_location_based @ CouponError::GeofenceAlreadySet
+    )]
+    pub promotion: Box<Account<'info, Promotion>>,
+
+    #[account(
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_CREATE_PROMOTIONS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GeoCell::INIT_SPACE,
+        seeds = [b"geo_cell", &GeoCell::cell_id_for(latitude, longitude).to_le_bytes()],
+        bump
+    )]
+    pub geo_cell: Box<Account<'info, GeoCell>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GeoCellEntry::INIT_SPACE,
+        seeds = [
+            b"geo_cell_entry",
+            GeoCell::cell_id_for(latitude, longitude).to_le_bytes().as_ref(),
+            &geo_cell.promotion_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub geo_cell_entry: Box<Account<'info, GeoCellEntry>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_promotion_geofence(
+    ctx: Context<SetPromotionGeofence>,
+    latitude: f64,
+    longitude: f64,
+    radius_meters: u32,
+) -> Result<()> {
+    let location = Location::checked_from_coords(latitude, longitude)
+        .ok_or(CouponError::InvalidCoordinates)?;
+    require!(radius_meters > 0, CouponError::InvalidRadius);
+
+    let cell_id = GeoCell::cell_id_for(latitude, longitude);
+    let geo_cell = &mut ctx.accounts.geo_cell;
+    if geo_cell.promotion_count == 0 {
+        geo_cell.init_bounds(cell_id);
+        geo_cell.bump = ctx.bumps.geo_cell;
+    }
+
+    let promotion = &mut ctx.accounts.promotion;
+    let geo_cell_entry = &mut ctx.accounts.geo_cell_entry;
+    geo_cell_entry.cell_id = cell_id;
+    geo_cell_entry.index = geo_cell.promotion_count;
+    geo_cell_entry.promotion = promotion.key();
+    geo_cell_entry.merchant = promotion.merchant;
+    geo_cell_entry.bump = ctx.bumps.geo_cell_entry;
+    geo_cell.promotion_count += 1;
+
+    promotion.location = location;
+    promotion.geo_cell_id = cell_id;
+    promotion.radius_meters = radius_meters;
+    promotion.is_location_based = true;
+
+    emit!(PromotionGeofenceSet {
+        promotion: promotion.key(),
+        latitude: promotion.location.latitude,
+        longitude: promotion.location.longitude,
+        geo_cell_id: cell_id,
+        radius_meters,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion geofence: cell {} radius {}m", cell_id, radius_meters);
+
+    Ok(())
+}
+
+// ============================================================================
+// Clear Geofence (removes the promotion from its GeoCell)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct ClearPromotionGeofence<'info> {
+    #[account(
+        mut,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = promotion.is_location_based @ CouponError::GeofenceNotSet
+    )]
+    pub promotion: Box<Account<'info, Promotion>>,
+
+    #[account(
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_CREATE_PROMOTIONS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+
+    #[account(
+        mut,
+        seeds = [b"geo_cell", &promotion.geo_cell_id.to_le_bytes()],
+        bump = geo_cell.bump
+    )]
+    pub geo_cell: Box<Account<'info, GeoCell>>,
+
+    #[account(
+        mut,
+        seeds = [b"geo_cell_entry", geo_cell.cell_id.to_le_bytes().as_ref(), &geo_cell_entry.index.to_le_bytes()],
+        bump = geo_cell_entry.bump
+    )]
+    pub geo_cell_entry: Box<Account<'info, GeoCellEntry>>,
+
+    // The cell's last entry, moved into the freed slot; omit when it is geo_cell_entry
+    #[account(
+        mut,
+        seeds = [
+            b"geo_cell_entry",
+            geo_cell.cell_id.to_le_bytes().as_ref(),
+            &geo_cell.promotion_count.saturating_sub(1).to_le_bytes()
+        ],
+        bump = last_geo_cell_entry.bump
+    )]
+    pub last_geo_cell_entry: Option<Box<Account<'info, GeoCellEntry>>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn clear_promotion_geofence(ctx: Context<ClearPromotionGeofence>) -> Result<()> {
+    let promotion = &mut ctx.accounts.promotion;
+    remove_from_geo_cell(
+        &mut ctx.accounts.geo_cell,
+        &mut ctx.accounts.geo_cell_entry,
+        ctx.accounts.last_geo_cell_entry.as_deref_mut(),
+        promotion.key(),
+        ctx.accounts.authority.to_account_info(),
+    )?;
+
+    let geo_cell_id = promotion.geo_cell_id;
+    promotion.geo_cell_id = 0;
+    promotion.radius_meters = 0;
+    promotion.is_location_based = false;
+
+    emit!(PromotionGeofenceCleared {
+        promotion: promotion.key(),
+        geo_cell_id,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion geofence cleared from cell {}", geo_cell_id);
+
+    Ok(())
+}
+
+// Drops `promotion` from its cell: the last entry is copied over the freed slot
+// and closed, so the cell's entries stay at 0..promotion_count.
+pub(crate) fn remove_from_geo_cell<'info>(
+    geo_cell: &mut Account<'info, GeoCell>,
+    geo_cell_entry: &mut Account<'info, GeoCellEntry>,
+    last_geo_cell_entry: Option<&mut Account<'info, GeoCellEntry>>,
+    promotion: Pubkey,
+    rent_destination: AccountInfo<'info>,
+) -> Result<()> {
+    require_keys_eq!(geo_cell_entry.promotion, promotion, CouponError::InvalidGeoCellEntry);
+
+    let last_index = geo_cell.promotion_count - 1;
+    if geo_cell_entry.index == last_index {
+        geo_cell_entry.close(rent_destination)?;
+    } else {
+        let last_geo_cell_entry = last_geo_cell_entry.ok_or(CouponError::InvalidGeoCellEntry)?;
+        geo_cell_entry.promotion = last_geo_cell_entry.promotion;
+        geo_cell_entry.merchant = last_geo_cell_entry.merchant;
+        last_geo_cell_entry.close(rent_destination)?;
+    }
+    geo_cell.promotion_count = last_index;
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_set_promotion_geofence(
+    ctx: Context<SetPromotionGeofence>,
+    latitude: f64,
+    longitude: f64,
+    radius_meters: u32,
+) -> Result<()> {
+    set_promotion_geofence(ctx, latitude, longitude, radius_meters)
+}
+
+pub fn handler_clear_promotion_geofence(ctx: Context<ClearPromotionGeofence>) -> Result<()> {
+    clear_promotion_geofence(ctx)
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::token::{Token, TokenAccount, Mint};
+use anchor_spl::token_interface::{self, TokenInterface};
 use crate::state::{
     GroupDeal, 
     GroupParticipant, 
@@ -9,12 +9,18 @@ use crate::state::{
     Merchant,
     Coupon,
     Marketplace,
+    PlatformConfig,
     UserStats,
     BadgeType,
     ReputationTier,
+    WalletMintCount,
+    DiscountKind,
+    MerchantStaff,
 };
 use crate::errors::CouponError;
 use crate::events::{GroupDealCreated, GroupDealJoined, GroupDealFinalized, GroupDealRefunded};
+use super::payments::Currency;
+use super::mint_coupon::record_wallet_mint;
 
 // ============================================================================
 // Create Group Deal
@@ -43,9 +49,28 @@ pub struct CreateGroupDeal<'info> {
     pub promotion: Account<'info, Promotion>,
     
     #[account(
-        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = merchant.is_authorized_for(
+            &organizer.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_MANAGE_LISTINGS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
     )]
     pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `organizer`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), organizer.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
     
     #[account(mut)]
     pub organizer: Signer<'info>,
@@ -63,6 +88,7 @@ pub fn create_group_deal(
 ) -> Result<()> {
     let group_deal = &mut ctx.accounts.group_deal;
     let promotion = &ctx.accounts.promotion;
+    let settings = &ctx.accounts.platform_config.settings;
     let clock = Clock::get()?;
     
     // Validation
@@ -70,7 +96,7 @@ pub fn create_group_deal(
     require!(max_participants >= target_participants, CouponError::InvalidSupply);
     require!(max_participants <= promotion.max_supply, CouponError::SupplyExhausted);
     require!(base_price > 0, CouponError::InvalidPrice);
-    require!(duration_seconds >= 3600, CouponError::InvalidExpiry); // Min 1 hour
+    require!(duration_seconds >= settings.group_deal_min_duration, CouponError::InvalidExpiry);
     require!(discount_tiers.len() <= 5, CouponError::InvalidInput);
     
     // Validate discount tiers are in ascending order
@@ -82,7 +108,7 @@ pub fn create_group_deal(
             );
         }
         require!(
-            discount_tiers[i].discount_percentage <= 50, // Max 50% additional discount
+            discount_tiers[i].discount_percentage <= settings.group_deal_max_tier_discount,
             CouponError::InvalidDiscount
         );
     }
@@ -110,6 +136,7 @@ pub fn create_group_deal(
     group_deal.created_at = clock.unix_timestamp;
     group_deal.finalized_at = 0;
     group_deal.total_escrowed = 0;
+    group_deal.payment_mint = promotion.payment_mint;
     
     emit!(GroupDealCreated {
         group_deal: group_deal.key(),
@@ -119,6 +146,7 @@ pub fn create_group_deal(
         target_participants,
         base_price,
         deadline: group_deal.deadline,
+        payment_mint: group_deal.payment_mint,
     });
     
     msg!("✅ Group deal created!");
@@ -152,6 +180,13 @@ pub struct JoinGroupDeal<'info> {
     )]
     pub group_deal: Account<'info, GroupDeal>,
     
+    #[account(
+        seeds = [b"marketplace"],
+        bump,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
+    pub marketplace: Account<'info, Marketplace>,
+    
     /// CHECK: Escrow vault
     #[account(
         mut,
@@ -160,6 +195,20 @@ pub struct JoinGroupDeal<'info> {
     )]
     pub escrow_vault: UncheckedAccount<'info>,
     
+    // SPL payment accounts, only needed when group_deal.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub user_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub escrow_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         init_if_needed,
         payer = user,
@@ -189,14 +238,21 @@ pub fn join_group_deal(ctx: Context<JoinGroupDeal>) -> Result<()> {
     let current_price = group_deal.get_current_price();
     
     // Escrow payment to vault
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user.to_account_info(),
-                to: ctx.accounts.escrow_vault.to_account_info(),
-            },
-        ),
+    let currency = Currency::resolve(
+        group_deal.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let user = ctx.accounts.user.to_account_info();
+    currency.transfer(
+        currency.account_for(&user, ctx.accounts.user_payment_account.as_deref())?,
+        currency.account_for(
+            &ctx.accounts.escrow_vault.to_account_info(),
+            ctx.accounts.escrow_payment_account.as_deref(),
+        )?,
+        user,
+        &[],
         current_price,
     )?;
     
@@ -227,7 +283,7 @@ pub fn join_group_deal(ctx: Context<JoinGroupDeal>) -> Result<()> {
         user_stats.joined_at = clock.unix_timestamp;
     }
     
-    user_stats.add_reputation(3); // Reputation for joining group deals
+    user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_group_join);
     user_stats.last_activity = clock.unix_timestamp;
     
     emit!(GroupDealJoined {
@@ -262,10 +318,16 @@ pub struct FinalizeGroupDeal<'info> {
     )]
     pub group_deal: Account<'info, GroupDeal>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = promotion.key() == group_deal.promotion @ CouponError::WrongCoupon
+    )]
     pub promotion: Account<'info, Promotion>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = merchant.key() == group_deal.merchant @ CouponError::WrongMerchant
+    )]
     pub merchant: Account<'info, Merchant>,
     
     #[account(mut)]
@@ -279,6 +341,14 @@ pub struct FinalizeGroupDeal<'info> {
     )]
     pub escrow_vault: UncheckedAccount<'info>,
     
+    // SPL payment accounts, only needed when group_deal.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub escrow_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub merchant_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    
     /// CHECK: Merchant receives payment
     #[account(
         mut,
@@ -313,15 +383,21 @@ pub fn finalize_group_deal(ctx: Context<FinalizeGroupDeal>) -> Result<()> {
     ];
     let signer_seeds = &[&escrow_seeds[..]];
     
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_vault.to_account_info(),
-                to: ctx.accounts.merchant_authority.to_account_info(),
-            },
-            signer_seeds,
-        ),
+    let currency = Currency::resolve(
+        group_deal.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let escrow_vault = ctx.accounts.escrow_vault.to_account_info();
+    currency.transfer(
+        currency.account_for(&escrow_vault, ctx.accounts.escrow_payment_account.as_deref())?,
+        currency.account_for(
+            &ctx.accounts.merchant_authority.to_account_info(),
+            ctx.accounts.merchant_payment_account.as_deref(),
+        )?,
+        escrow_vault,
+        signer_seeds,
         merchant_payment,
     )?;
     
@@ -382,6 +458,14 @@ pub struct RefundGroupDeal<'info> {
     )]
     pub escrow_vault: UncheckedAccount<'info>,
     
+    // SPL payment accounts, only needed when group_deal.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub escrow_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub user_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+    
     /// CHECK: User receiving refund
     #[account(
         mut,
@@ -407,15 +491,21 @@ pub fn refund_group_deal(ctx: Context<RefundGroupDeal>) -> Result<()> {
     ];
     let signer_seeds = &[&escrow_seeds[..]];
     
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_vault.to_account_info(),
-                to: ctx.accounts.user.to_account_info(),
-            },
-            signer_seeds,
-        ),
+    let currency = Currency::resolve(
+        group_deal.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let escrow_vault = ctx.accounts.escrow_vault.to_account_info();
+    currency.transfer(
+        currency.account_for(&escrow_vault, ctx.accounts.escrow_payment_account.as_deref())?,
+        currency.account_for(
+            &ctx.accounts.user.to_account_info(),
+            ctx.accounts.user_payment_account.as_deref(),
+        )?,
+        escrow_vault,
+        signer_seeds,
         refund_amount,
     )?;
     
@@ -464,7 +554,19 @@ pub struct MintGroupCoupon<'info> {
     )]
     pub participant: Account<'info, GroupParticipant>,
     
+    #[account(
+        constraint = promotion.key() == group_deal.promotion @ CouponError::WrongCoupon
+    )]
     pub promotion: Account<'info, Promotion>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + WalletMintCount::INIT_SPACE,
+        seeds = [b"wallet_mints", promotion.key().as_ref(), participant.user.as_ref()],
+        bump
+    )]
+    pub wallet_mints: Account<'info, WalletMintCount>,
     
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -476,6 +578,8 @@ pub fn mint_group_coupon(ctx: Context<MintGroupCoupon>, coupon_id: u64) -> Resul
     let participant = &mut ctx.accounts.participant;
     let promotion = &ctx.accounts.promotion;
     let clock = Clock::get()?;
+
+    record_wallet_mint(&mut ctx.accounts.wallet_mints, promotion, participant.user, ctx.bumps.wallet_mints)?;
     
     // Initialize coupon
     coupon.id = coupon_id;
@@ -484,12 +588,20 @@ pub fn mint_group_coupon(ctx: Context<MintGroupCoupon>, coupon_id: u64) -> Resul
     coupon.merchant = promotion.merchant;
     coupon.discount_percentage = promotion.discount_percentage 
         + ctx.accounts.group_deal.get_current_discount();
+    // The group tier stacks on percentage offers; other kinds carry over as-is
+    coupon.discount_kind = match promotion.discount_kind {
+        DiscountKind::Percentage { .. } => DiscountKind::Percentage { percent: coupon.discount_percentage },
+        kind => kind,
+    };
     coupon.expiry_timestamp = promotion.expiry_timestamp;
     coupon.is_redeemed = false;
     coupon.redeemed_at = 0;
+    coupon.uses_total = promotion.uses_per_coupon;
+    coupon.uses_remaining = promotion.uses_per_coupon;
     coupon.created_at = clock.unix_timestamp;
     coupon.mint = None;
     coupon.metadata_uri = "https://example.com/group-coupon.json".to_string();
+    coupon.last_sale_price = participant.amount_escrowed;
     
     // Mark as minted
     participant.coupon_minted = Some(coupon.key());
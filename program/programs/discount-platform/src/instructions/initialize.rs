@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::Marketplace;
+use crate::state::{Marketplace, PlatformConfig, PlatformSettings};
 use crate::events::MarketplaceInitialized;
 use crate::errors::CouponError;
 
@@ -13,6 +13,14 @@ pub struct Initialize<'info> {
         bump
     )]
     pub marketplace: Account<'info, Marketplace>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PlatformConfig::INIT_SPACE,
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -24,6 +32,13 @@ pub fn handler(ctx: Context<Initialize>) -> Result<()> {
     marketplace.total_coupons = 0;
     marketplace.total_merchants = 0;
     marketplace.fee_basis_points = 250;
+    marketplace.pending_authority = None;
+    marketplace.paused = false;
+
+    let platform_config = &mut ctx.accounts.platform_config;
+    platform_config.version = 1;
+    platform_config.updated_at = Clock::get()?.unix_timestamp;
+    platform_config.settings = PlatformSettings::default();
 
     emit!(MarketplaceInitialized {
         marketplace: marketplace.key(),
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
-use crate::state::{Coupon, Listing, UserStats,ReputationTier};
+use anchor_spl::token_interface::{self, TokenInterface, TokenAccount, Mint, close_account, CloseAccount};
+use crate::state::{Coupon, CouponLock, Listing, PlatformConfig, UserStats,ReputationTier};
 use crate::errors::CouponError;
 use crate::events::{CouponListed, ListingCancelled};
+use super::escrow::NftEscrow;
 
 pub fn handler(
     ctx: Context<ListCouponForSale>,
@@ -9,13 +11,27 @@ pub fn handler(
 ) -> Result<()> {
     require!(price > 0, CouponError::InvalidPrice);
 
-    let coupon = &ctx.accounts.coupon;
+    let coupon = &mut ctx.accounts.coupon;
     require!(!coupon.is_redeemed, CouponError::CouponAlreadyRedeemed);
     require!(coupon.expiry_timestamp > Clock::get()?.unix_timestamp, CouponError::CouponExpired);
     require!(coupon.owner == ctx.accounts.seller.key(), CouponError::NotCouponOwner);
+    require!(coupon.lock == CouponLock::None, CouponError::CouponLocked);
+    coupon.lock = CouponLock::Listed;
 
     let current_time = Clock::get()?.unix_timestamp;
 
+    // Move the NFT into the listing vault until it is bought or the listing is
+    // cancelled. Hooked coupons are deposited by the seller's own transfer.
+    NftEscrow {
+        mint: &ctx.accounts.nft_mint,
+        token_program: &ctx.accounts.token_program,
+    }
+    .deposit(
+        ctx.accounts.seller_token_account.to_account_info(),
+        ctx.accounts.listing_vault.to_account_info(),
+        ctx.accounts.seller.to_account_info(),
+    )?;
+
     // Initialize UserStats if first time
     let user_stats = &mut ctx.accounts.user_stats;
     if user_stats.user == Pubkey::default() {
@@ -34,22 +50,24 @@ pub fn handler(
 
     // Update user stats for listing
     user_stats.total_listings += 1;
-    user_stats.add_reputation(3); // 3 points per listing
+    user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_listing);
     user_stats.last_activity = current_time;
 
     let listing = &mut ctx.accounts.listing;
     listing.coupon = ctx.accounts.coupon.key();
     listing.seller = ctx.accounts.seller.key();
     listing.price = price;
+    listing.payment_mint = ctx.accounts.payment_mint.as_ref().map(|mint| mint.key());
     listing.is_active = true;
     listing.created_at = current_time;
 
     emit!(CouponListed {
         listing: listing.key(),
         coupon: listing.coupon,
-        nft_mint: coupon.mint.unwrap_or(Pubkey::default()),
+        nft_mint: ctx.accounts.nft_mint.key(),
         seller: listing.seller,
         price,
+        payment_mint: listing.payment_mint,
     });
 
     msg!("Coupon listed! Total listings: {} | Reputation: {} | Tier: {:?}", 
@@ -63,7 +81,43 @@ pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
     require!(listing.is_active, CouponError::ListingInactive);
     require!(listing.seller == ctx.accounts.seller.key(), CouponError::NotListingSeller);
 
+    let coupon_key = ctx.accounts.coupon.key();
+    let listing_seeds = &[
+        b"listing",
+        coupon_key.as_ref(),
+        &[ctx.bumps.listing],
+    ];
+    let signer_seeds = &[&listing_seeds[..]];
+
+    // Return the NFT to the seller
+    let escrow = NftEscrow {
+        mint: &ctx.accounts.nft_mint,
+        token_program: &ctx.accounts.token_program,
+    };
+    escrow.release(
+        ctx.accounts.listing_vault.to_account_info(),
+        ctx.accounts.seller_token_account.to_account_info(),
+        ctx.accounts.seller.to_account_info(),
+        listing.to_account_info(),
+        signer_seeds,
+    )?;
+
+    // Close the vault to refund its rent. A hooked coupon is still in it until
+    // the seller takes it out, so that vault stays for the next listing.
+    if !escrow.is_hooked() {
+        close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.listing_vault.to_account_info(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: listing.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+
     listing.is_active = false;
+    ctx.accounts.coupon.lock = CouponLock::None;
 
     emit!(ListingCancelled {
         listing: listing.key(),
@@ -86,9 +140,46 @@ pub struct ListCouponForSale<'info> {
     )]
     pub listing: Account<'info, Listing>,
     #[account(
+        mut,
         constraint = coupon.owner == seller.key() @ CouponError::NotCouponOwner
     )]
     pub coupon: Account<'info, Coupon>,
+
+    /// CHECK: NFT Mint
+    #[account(
+        constraint = coupon.mint == Some(nft_mint.key()) @ CouponError::WrongCoupon
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Seller's token account holding the NFT
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == nft_mint.key(),
+        constraint = seller_token_account.owner == seller.key()
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Listing vault token account (PDA) that escrows the NFT. Reused
+    /// when a hooked coupon is listed again (see cancel_listing).
+    #[account(
+        init_if_needed,
+        payer = seller,
+        token::mint = nft_mint,
+        token::authority = listing,
+        token::token_program = token_program,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump
+    )]
+    pub listing_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// SPL mint the listing is priced in; omit to price in SOL
+    pub payment_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
     
     #[account(
         init_if_needed,
@@ -101,6 +192,7 @@ pub struct ListCouponForSale<'info> {
     
     #[account(mut)]
     pub seller: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -108,8 +200,42 @@ pub struct ListCouponForSale<'info> {
 pub struct CancelListing<'info> {
     #[account(
         mut,
-        constraint = listing.seller == seller.key() @ CouponError::NotListingSeller
+        seeds = [b"listing", coupon.key().as_ref()],
+        bump,
+        constraint = listing.seller == seller.key() @ CouponError::NotListingSeller,
+        close = seller
     )]
     pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        constraint = coupon.key() == listing.coupon @ CouponError::WrongCoupon
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    /// CHECK: NFT Mint
+    #[account(
+        constraint = coupon.mint == Some(nft_mint.key()) @ CouponError::WrongCoupon
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Listing vault token account (PDA)
+    #[account(
+        mut,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump
+    )]
+    pub listing_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Seller's token account receiving the NFT back
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == nft_mint.key(),
+        constraint = seller_token_account.owner == seller.key()
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
     pub seller: Signer<'info>,
-}
\ No newline at end of file
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,361 @@
+// src/instructions/loyalty.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::{
+    Coupon, Promotion, Merchant, MerchantStaff, Marketplace, PlatformConfig, UserStats, WalletMintCount,
+    LoyaltyProgram, LoyaltyCard,
+};
+use crate::errors::CouponError;
+use crate::events::{CouponMinted, LoyaltyProgramUpdated, LoyaltyCardStamped, LoyaltyRewardClaimed};
+use super::mint_coupon::{check_mintable, record_wallet_mint, record_purchase, init_coupon, MerchantSignedMint};
+
+// ============================================================================
+// Set Loyalty Program (creates or updates the merchant's stamp program)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct SetLoyaltyProgram<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + LoyaltyProgram::INIT_SPACE,
+        seeds = [b"loyalty_program", merchant.key().as_ref()],
+        bump
+    )]
+    pub loyalty_program: Account<'info, LoyaltyProgram>,
+
+    #[account(
+        constraint = reward_promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub reward_promotion: Account<'info, Promotion>,
+
+    #[account(
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_CREATE_PROMOTIONS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_loyalty_program(ctx: Context<SetLoyaltyProgram>, stamps_required: u32, is_active: bool) -> Result<()> {
+    require!(stamps_required > 0, CouponError::InvalidStampThreshold);
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let loyalty_program = &mut ctx.accounts.loyalty_program;
+    if loyalty_program.merchant == Pubkey::default() {
+        loyalty_program.merchant = ctx.accounts.merchant.key();
+        loyalty_program.created_at = current_time;
+        loyalty_program.bump = ctx.bumps.loyalty_program;
+    }
+    loyalty_program.reward_promotion = ctx.accounts.reward_promotion.key();
+    loyalty_program.stamps_required = stamps_required;
+    loyalty_program.is_active = is_active;
+
+    emit!(LoyaltyProgramUpdated {
+        loyalty_program: loyalty_program.key(),
+        merchant: loyalty_program.merchant,
+        reward_promotion: loyalty_program.reward_promotion,
+        stamps_required,
+        is_active,
+        timestamp: current_time,
+    });
+
+    msg!("✅ Loyalty program: {} stamps per reward, active: {}", stamps_required, is_active);
+
+    Ok(())
+}
+
+// ============================================================================
+// Open Loyalty Card (user side; redemptions stamp it from then on)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct OpenLoyaltyCard<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + LoyaltyCard::INIT_SPACE,
+        seeds = [b"loyalty_card", loyalty_program.merchant.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub loyalty_card: Account<'info, LoyaltyCard>,
+
+    #[account(
+        seeds = [b"loyalty_program", loyalty_program.merchant.as_ref()],
+        bump = loyalty_program.bump
+    )]
+    pub loyalty_program: Account<'info, LoyaltyProgram>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn open_loyalty_card(ctx: Context<OpenLoyaltyCard>) -> Result<()> {
+    let loyalty_card = &mut ctx.accounts.loyalty_card;
+    loyalty_card.merchant = ctx.accounts.loyalty_program.merchant;
+    loyalty_card.user = ctx.accounts.user.key();
+    loyalty_card.stamps = 0;
+    loyalty_card.lifetime_stamps = 0;
+    loyalty_card.rewards_claimed = 0;
+    loyalty_card.created_at = Clock::get()?.unix_timestamp;
+    loyalty_card.bump = ctx.bumps.loyalty_card;
+
+    msg!("✅ Loyalty card opened for {}", loyalty_card.user);
+
+    Ok(())
+}
+
+// ============================================================================
+// Claim Loyalty Reward (spends stamps on a free coupon, no merchant signature)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct ClaimLoyaltyReward<'info> {
+    #[account(
+        mut,
+        seeds = [b"loyalty_card", merchant.key().as_ref(), user.key().as_ref()],
+        bump = loyalty_card.bump
+    )]
+    pub loyalty_card: Box<Account<'info, LoyaltyCard>>,
+
+    #[account(
+        seeds = [b"loyalty_program", merchant.key().as_ref()],
+        bump = loyalty_program.bump,
+        constraint = loyalty_program.reward_promotion == promotion.key() @ CouponError::WrongCoupon
+    )]
+    pub loyalty_program: Box<Account<'info, LoyaltyProgram>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Coupon::INIT_SPACE,
+        seeds = [b"coupon", promotion.key().as_ref(), &promotion.current_supply.to_le_bytes()],
+        bump
+    )]
+    pub coupon: Box<Account<'info, Coupon>>,
+
+    #[account(
+        init,
+        payer = user,
+        mint::decimals = 0,
+        mint::authority = merchant,
+        mint::freeze_authority = merchant
+    )]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = nft_mint,
+        associated_token::authority = user
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metadata account
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Master Edition account
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub promotion: Box<Account<'info, Promotion>>,
+
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(
+        mut,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [b"user_stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + WalletMintCount::INIT_SPACE,
+        seeds = [b"wallet_mints", promotion.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub wallet_mints: Box<Account<'info, WalletMintCount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Sysvar Instructions
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn claim_loyalty_reward(ctx: Context<ClaimLoyaltyReward>, coupon_id: u64) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let user = ctx.accounts.user.key();
+
+    let loyalty_program = &ctx.accounts.loyalty_program;
+    require!(loyalty_program.is_active, CouponError::LoyaltyProgramInactive);
+    require!(ctx.accounts.loyalty_card.can_claim(loyalty_program), CouponError::NotEnoughStamps);
+
+    // The stamps are the merchant's grant, like a voucher: the allow-list and
+    // pre-sale don't apply, while start time, supply and per-wallet limits do
+    check_mintable(&ctx.accounts.promotion, current_time, false)?;
+    record_wallet_mint(&mut ctx.accounts.wallet_mints, &ctx.accounts.promotion, user, ctx.bumps.wallet_mints)?;
+
+    let loyalty_card = &mut ctx.accounts.loyalty_card;
+    loyalty_card.redeem_reward(loyalty_program);
+
+    let user_stats = &mut ctx.accounts.user_stats;
+    record_purchase(
+        user_stats,
+        user,
+        ctx.accounts.platform_config.settings.reputation_per_purchase,
+        current_time,
+    );
+
+    let promotion = &mut ctx.accounts.promotion;
+    let coupon = &mut ctx.accounts.coupon;
+    init_coupon(coupon, coupon_id, promotion, user, Some(ctx.accounts.nft_mint.key()), current_time);
+
+    MerchantSignedMint {
+        merchant: ctx.accounts.merchant.to_account_info(),
+        merchant_authority: ctx.accounts.merchant.authority,
+        merchant_bump: ctx.bumps.merchant,
+        nft_mint: ctx.accounts.nft_mint.to_account_info(),
+        token_account: ctx.accounts.token_account.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        master_edition: ctx.accounts.master_edition.to_account_info(),
+        payer: ctx.accounts.user.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    }
+    .invoke(
+        coupon.metadata_name(),
+        coupon.metadata_uri.clone(),
+        ctx.accounts.merchant.royalty_basis_points
+            .min(ctx.accounts.platform_config.settings.max_royalty_basis_points),
+    )?;
+
+    promotion.current_supply += 1;
+    ctx.accounts.merchant.total_coupons_created += 1;
+    ctx.accounts.marketplace.total_coupons += 1;
+
+    emit!(CouponMinted {
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        promotion: promotion.key(),
+        recipient: user,
+        merchant: coupon.merchant,
+        discount_percentage: coupon.discount_percentage,
+        price: 0,
+        marketplace_fee: 0,
+    });
+
+    emit!(LoyaltyRewardClaimed {
+        loyalty_card: loyalty_card.key(),
+        merchant: loyalty_card.merchant,
+        user,
+        coupon: coupon.key(),
+        stamps_spent: ctx.accounts.loyalty_program.stamps_required,
+        stamps_left: loyalty_card.stamps,
+        timestamp: current_time,
+    });
+
+    msg!("✅ Loyalty reward claimed by {} | Stamps left: {}", user, loyalty_card.stamps);
+
+    Ok(())
+}
+
+// ============================================================================
+// Shared with redeem_coupon and verify_and_redeem_ticket
+// ============================================================================
+
+// Adds a stamp when the customer has a card at the merchant and the use earns
+// one (see LoyaltyProgram::earns_stamp)
+pub fn stamp_loyalty_card(
+    loyalty_card: Option<&mut Account<LoyaltyCard>>,
+    loyalty_program: Option<&Account<LoyaltyProgram>>,
+    coupon: &Coupon,
+    current_time: i64,
+) -> Result<()> {
+    let Some(loyalty_card) = loyalty_card else {
+        return Ok(());
+    };
+    let loyalty_program = loyalty_program.ok_or(CouponError::LoyaltyProgramRequired)?;
+    if !loyalty_program.earns_stamp(coupon) {
+        return Ok(());
+    }
+    loyalty_card.stamp();
+
+    emit!(LoyaltyCardStamped {
+        loyalty_card: loyalty_card.key(),
+        merchant: loyalty_card.merchant,
+        user: loyalty_card.user,
+        stamps: loyalty_card.stamps,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_set_loyalty_program(ctx: Context<SetLoyaltyProgram>, stamps_required: u32, is_active: bool) -> Result<()> {
+    set_loyalty_program(ctx, stamps_required, is_active)
+}
+
+pub fn handler_open_loyalty_card(ctx: Context<OpenLoyaltyCard>) -> Result<()> {
+    open_loyalty_card(ctx)
+}
+
+pub fn handler_claim_loyalty_reward(ctx: Context<ClaimLoyaltyReward>, coupon_id: u64) -> Result<()> {
+    claim_loyalty_reward(ctx, coupon_id)
+}
@@ -0,0 +1,141 @@
+// src/instructions/marketplace_admin.rs
+use anchor_lang::prelude::*;
+use crate::state::Marketplace;
+use crate::errors::CouponError;
+use crate::events::{
+    MarketplaceFeeUpdated,
+    MarketplaceAuthorityProposed,
+    MarketplaceAuthorityTransferred,
+    MarketplacePauseChanged,
+};
+
+// ============================================================================
+// Authority-gated updates (fee, authority proposal, pause)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct MarketplaceAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"marketplace"],
+        bump,
+        has_one = authority @ CouponError::NotMarketplaceAuthority
+    )]
+    pub marketplace: Account<'info, Marketplace>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn set_marketplace_fee(ctx: Context<MarketplaceAdmin>, fee_basis_points: u16) -> Result<()> {
+    require!(
+        fee_basis_points <= Marketplace::MAX_FEE_BASIS_POINTS,
+        CouponError::FeeTooHigh
+    );
+
+    let marketplace = &mut ctx.accounts.marketplace;
+    let old_fee_basis_points = marketplace.fee_basis_points;
+    marketplace.fee_basis_points = fee_basis_points;
+
+    emit!(MarketplaceFeeUpdated {
+        marketplace: marketplace.key(),
+        old_fee_basis_points,
+        new_fee_basis_points: fee_basis_points,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Marketplace fee: {} -> {} bps", old_fee_basis_points, fee_basis_points);
+
+    Ok(())
+}
+
+pub fn propose_marketplace_authority(
+    ctx: Context<MarketplaceAdmin>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    let marketplace = &mut ctx.accounts.marketplace;
+    marketplace.pending_authority = Some(new_authority);
+
+    emit!(MarketplaceAuthorityProposed {
+        marketplace: marketplace.key(),
+        authority: marketplace.authority,
+        pending_authority: new_authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Marketplace authority proposed: {}", new_authority);
+
+    Ok(())
+}
+
+pub fn set_marketplace_paused(ctx: Context<MarketplaceAdmin>, paused: bool) -> Result<()> {
+    let marketplace = &mut ctx.accounts.marketplace;
+    marketplace.paused = paused;
+
+    emit!(MarketplacePauseChanged {
+        marketplace: marketplace.key(),
+        paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Marketplace paused: {}", paused);
+
+    Ok(())
+}
+
+// ============================================================================
+// Accept Authority (second step of the handover)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct AcceptMarketplaceAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"marketplace"],
+        bump,
+        constraint = marketplace.pending_authority == Some(new_authority.key()) @ CouponError::NotPendingAuthority
+    )]
+    pub marketplace: Account<'info, Marketplace>,
+
+    pub new_authority: Signer<'info>,
+}
+
+pub fn accept_marketplace_authority(ctx: Context<AcceptMarketplaceAuthority>) -> Result<()> {
+    let marketplace = &mut ctx.accounts.marketplace;
+    let old_authority = marketplace.authority;
+    marketplace.authority = ctx.accounts.new_authority.key();
+    marketplace.pending_authority = None;
+
+    emit!(MarketplaceAuthorityTransferred {
+        marketplace: marketplace.key(),
+        old_authority,
+        new_authority: marketplace.authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Marketplace authority transferred to {}", marketplace.authority);
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_set_marketplace_fee(ctx: Context<MarketplaceAdmin>, fee_basis_points: u16) -> Result<()> {
+    set_marketplace_fee(ctx, fee_basis_points)
+}
+
+pub fn handler_propose_marketplace_authority(
+    ctx: Context<MarketplaceAdmin>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    propose_marketplace_authority(ctx, new_authority)
+}
+
+pub fn handler_accept_marketplace_authority(ctx: Context<AcceptMarketplaceAuthority>) -> Result<()> {
+    accept_marketplace_authority(ctx)
+}
+
+pub fn handler_set_marketplace_paused(ctx: Context<MarketplaceAdmin>, paused: bool) -> Result<()> {
+    set_marketplace_paused(ctx, paused)
+}
@@ -0,0 +1,146 @@
+// src/instructions/merchant_branches.rs
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, MerchantBranch, Location};
+use crate::errors::CouponError;
+use crate::events::{MerchantBranchAdded, MerchantBranchUpdated};
+
+// ============================================================================
+// Add Branch (next index in the merchant's branch list)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct AddMerchantBranch<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MerchantBranch::INIT_SPACE,
+        seeds = [b"branch", merchant.key().as_ref(), &merchant.branch_count.to_le_bytes()],
+        bump
+    )]
+    pub branch: Account<'info, MerchantBranch>,
+
+    // Owner only: branches are the merchant's own storefronts, which
+    // branch-restricted promotions and redemptions are checked against
+    #[account(
+        mut,
+        constraint = merchant.is_authorized(&authority.key()) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_merchant_branch(
+    ctx: Context<AddMerchantBranch>,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+) -> Result<()> {
+    require!(name.len() <= 50, CouponError::NameTooLong);
+    let location = Location::checked_from_coords(latitude, longitude)
+        .ok_or(CouponError::InvalidCoordinates)?;
+
+    let merchant = &mut ctx.accounts.merchant;
+    let branch = &mut ctx.accounts.branch;
+    branch.merchant = merchant.key();
+    branch.index = merchant.branch_count;
+    branch.name = name;
+    branch.location = location;
+    branch.is_active = true;
+    branch.created_at = Clock::get()?.unix_timestamp;
+    branch.bump = ctx.bumps.branch;
+
+    merchant.branch_count += 1;
+
+    emit!(MerchantBranchAdded {
+        merchant: branch.merchant,
+        branch: branch.key(),
+        index: branch.index,
+        name: branch.name.clone(),
+        latitude: branch.location.latitude,
+        longitude: branch.location.longitude,
+        timestamp: branch.created_at,
+    });
+
+    msg!("✅ Branch #{} added: {}", branch.index, branch.name);
+
+    Ok(())
+}
+
+// ============================================================================
+// Update Branch (name, location, active flag)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct UpdateMerchantBranch<'info> {
+    #[account(
+        mut,
+        seeds = [b"branch", merchant.key().as_ref(), &branch.index.to_le_bytes()],
+        bump = branch.bump
+    )]
+    pub branch: Account<'info, MerchantBranch>,
+
+    // Owner only, like AddMerchantBranch
+    #[account(
+        constraint = merchant.is_authorized(&authority.key()) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn update_merchant_branch(
+    ctx: Context<UpdateMerchantBranch>,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    is_active: bool,
+) -> Result<()> {
+    require!(name.len() <= 50, CouponError::NameTooLong);
+    let location = Location::checked_from_coords(latitude, longitude)
+        .ok_or(CouponError::InvalidCoordinates)?;
+
+    let branch = &mut ctx.accounts.branch;
+    branch.name = name;
+    branch.location = location;
+    branch.is_active = is_active;
+
+    emit!(MerchantBranchUpdated {
+        merchant: branch.merchant,
+        branch: branch.key(),
+        name: branch.name.clone(),
+        latitude: branch.location.latitude,
+        longitude: branch.location.longitude,
+        is_active,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Branch #{} updated (active: {})", branch.index, is_active);
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_add_merchant_branch(
+    ctx: Context<AddMerchantBranch>,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+) -> Result<()> {
+    add_merchant_branch(ctx, name, latitude, longitude)
+}
+
+pub fn handler_update_merchant_branch(
+    ctx: Context<UpdateMerchantBranch>,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    is_active: bool,
+) -> Result<()> {
+    update_merchant_branch(ctx, name, latitude, longitude, is_active)
+}
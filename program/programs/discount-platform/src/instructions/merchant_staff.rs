@@ -0,0 +1,124 @@
+// src/instructions/merchant_staff.rs
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, MerchantStaff};
+use crate::errors::CouponError;
+use crate::events::{MerchantStaffAdded, MerchantStaffRevoked};
+
+// ============================================================================
+// Add Staff (merchant authority delegates roles to another key)
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(staff: Pubkey)]
+pub struct AddMerchantStaff<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MerchantStaff::INIT_SPACE,
+        seeds = [b"merchant_staff", merchant.key().as_ref(), staff.as_ref()],
+        bump
+    )]
+    pub merchant_staff: Account<'info, MerchantStaff>,
+
+    // Owner only: staff can't grant or revoke roles
+    #[account(
+        constraint = merchant.is_authorized(&authority.key()) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_merchant_staff(
+    ctx: Context<AddMerchantStaff>,
+    staff: Pubkey,
+    roles: u8,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    require!(
+        roles != 0 && roles & !MerchantStaff::ALL_ROLES == 0,
+        CouponError::InvalidStaffRoles
+    );
+
+    let current_time = Clock::get()?.unix_timestamp;
+    if let Some(expires_at) = expires_at {
+        require!(expires_at > current_time, CouponError::InvalidExpiry);
+    }
+
+    let merchant_staff = &mut ctx.accounts.merchant_staff;
+    merchant_staff.merchant = ctx.accounts.merchant.key();
+    merchant_staff.staff = staff;
+    merchant_staff.roles = roles;
+    merchant_staff.expires_at = expires_at;
+    merchant_staff.created_at = current_time;
+    merchant_staff.bump = ctx.bumps.merchant_staff;
+
+    emit!(MerchantStaffAdded {
+        merchant: merchant_staff.merchant,
+        staff,
+        roles,
+        expires_at,
+        timestamp: current_time,
+    });
+
+    msg!("✅ Staff {} added with roles {:#05b}", staff, roles);
+
+    Ok(())
+}
+
+// ============================================================================
+// Revoke Staff (closes the grant, rent back to the merchant authority)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct RevokeMerchantStaff<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant_staff", merchant.key().as_ref(), merchant_staff.staff.as_ref()],
+        bump = merchant_staff.bump,
+        close = authority
+    )]
+    pub merchant_staff: Account<'info, MerchantStaff>,
+
+    // Owner only: staff can't grant or revoke roles
+    #[account(
+        constraint = merchant.is_authorized(&authority.key()) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn revoke_merchant_staff(ctx: Context<RevokeMerchantStaff>) -> Result<()> {
+    let merchant_staff = &ctx.accounts.merchant_staff;
+
+    emit!(MerchantStaffRevoked {
+        merchant: merchant_staff.merchant,
+        staff: merchant_staff.staff,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Staff {} revoked", merchant_staff.staff);
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_add_merchant_staff(
+    ctx: Context<AddMerchantStaff>,
+    staff: Pubkey,
+    roles: u8,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    add_merchant_staff(ctx, staff, roles, expires_at)
+}
+
+pub fn handler_revoke_merchant_staff(ctx: Context<RevokeMerchantStaff>) -> Result<()> {
+    revoke_merchant_staff(ctx)
+}
@@ -1,12 +1,15 @@
 // src/instructions/mint_coupon.rs
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount, mint_to, MintTo};
+use anchor_spl::token_interface::{self, TokenInterface};
 use anchor_spl::associated_token::AssociatedToken;
 use mpl_token_metadata::instructions::CreateV1CpiBuilder;
 use mpl_token_metadata::types::{TokenStandard, PrintSupply};
-use crate::state::{Coupon, Promotion, Merchant, Marketplace, UserStats, BadgeType, ReputationTier};
+use crate::state::{Coupon, CouponLock, Promotion, Merchant, MerchantStaff, Marketplace, PlatformConfig, UserStats, BadgeType, BadgeNFT, PresaleAllowance, WalletMintCount, ReputationTier};
+use crate::allow_list::{self, AllowListProof};
 use crate::errors::CouponError;
 use crate::events::CouponMinted;
+use super::payments::Currency;
 
 #[derive(Accounts)]
 pub struct MintCoupon<'info> {
@@ -49,15 +52,72 @@ pub struct MintCoupon<'info> {
     #[account(mut)]
     pub promotion: Account<'info, Promotion>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref().map(|staff| &**staff),
+            MerchantStaff::ROLE_ISSUE_VOUCHERS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
     pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Box<Account<'info, MerchantStaff>>>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
     pub marketplace: Account<'info, Marketplace>,
     
     /// CHECK: Recipient of the NFT
     pub recipient: UncheckedAccount<'info>,
     
+    /// CHECK: Merchant authority receiving the sale proceeds
+    #[account(
+        mut,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: Marketplace authority for fee collection
+    #[account(
+        mut,
+        constraint = marketplace.authority == marketplace_authority.key() @ CouponError::NotMarketplaceAuthority
+    )]
+    pub marketplace_authority: UncheckedAccount<'info>,
+    
+    // SPL payment accounts, only needed when promotion.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub payer_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub merchant_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub marketplace_payment_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Pre-sale access for `recipient`; only needed before promotion.start_timestamp
+    #[account(
+        seeds = [b"presale_allowance", promotion.key().as_ref(), recipient.key().as_ref()],
+        bump = presale_allowance.bump
+    )]
+    pub presale_allowance: Option<Account<'info, PresaleAllowance>>,
+    pub badge_nft: Option<Account<'info, BadgeNFT>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         init_if_needed,
         payer = payer,
@@ -66,6 +126,15 @@ pub struct MintCoupon<'info> {
         bump
     )]
     pub user_stats: Account<'info, UserStats>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + WalletMintCount::INIT_SPACE,
+        seeds = [b"wallet_mints", promotion.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub wallet_mints: Account<'info, WalletMintCount>,
     
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -85,52 +154,76 @@ pub struct MintCoupon<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn handler(ctx: Context<MintCoupon>, coupon_id: u64) -> Result<()> {
-    let promotion = &mut ctx.accounts.promotion;
-    require!(promotion.is_active, CouponError::PromotionInactive);
-    require!(promotion.current_supply < promotion.max_supply, CouponError::SupplyExhausted);
-    require!(promotion.expiry_timestamp > Clock::get()?.unix_timestamp, CouponError::PromotionExpired);
-
+pub fn handler(
+    ctx: Context<MintCoupon>,
+    coupon_id: u64,
+    allow_list_proof: Option<AllowListProof>,
+) -> Result<()> {
     let current_time = Clock::get()?.unix_timestamp;
+    let presale_access = has_presale_access(
+        &ctx.accounts.promotion,
+        ctx.accounts.recipient.key(),
+        ctx.accounts.presale_allowance.as_ref(),
+        ctx.accounts.badge_nft.as_ref(),
+    );
+    check_mintable(&ctx.accounts.promotion, current_time, presale_access)?;
+    check_allow_list(
+        &ctx.accounts.promotion,
+        ctx.accounts.recipient.key(),
+        ctx.accounts.wallet_mints.minted,
+        allow_list_proof.as_ref(),
+    )?;
 
-    // Initialize UserStats if first time
-    let user_stats = &mut ctx.accounts.user_stats;
-    if user_stats.user == Pubkey::default() {
-        user_stats.user = ctx.accounts.recipient.key();
-        user_stats.total_purchases = 0;
-        user_stats.total_redemptions = 0;
-        user_stats.total_ratings_given = 0;
-        user_stats.total_comments = 0;
-        user_stats.total_listings = 0;
-        user_stats.reputation_score = 0;
-        user_stats.tier = ReputationTier::Bronze;
-        user_stats.badges_earned = Vec::new();
-        user_stats.joined_at = current_time;
-        user_stats.last_activity = current_time;
-    }
+    // Collect the primary sale price from the payer
+    let currency = Currency::resolve(
+        ctx.accounts.promotion.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let payer = ctx.accounts.payer.to_account_info();
+    let (price, marketplace_fee) = collect_mint_payment(
+        &ctx.accounts.promotion,
+        &ctx.accounts.marketplace,
+        &currency,
+        currency.account_for(&payer, ctx.accounts.payer_payment_account.as_deref())?,
+        currency.account_for(
+            &ctx.accounts.merchant_authority.to_account_info(),
+            ctx.accounts.merchant_payment_account.as_deref(),
+        )?,
+        currency.account_for(
+            &ctx.accounts.marketplace_authority.to_account_info(),
+            ctx.accounts.marketplace_payment_account.as_deref(),
+        )?,
+        payer,
+    )?;
 
-    // Update user stats for purchase
-    user_stats.total_purchases += 1;
-    user_stats.add_reputation(5); // 5 points per purchase
-    user_stats.last_activity = current_time;
+    record_wallet_mint(
+        &mut ctx.accounts.wallet_mints,
+        &ctx.accounts.promotion,
+        ctx.accounts.recipient.key(),
+        ctx.bumps.wallet_mints,
+    )?;
 
-    // Check for FirstPurchase badge eligibility
-    if user_stats.total_purchases == 1 && !user_stats.has_badge(BadgeType::FirstPurchase as u8) {
-        msg!("🏆 User eligible for FirstPurchase badge! Total purchases: {}", user_stats.total_purchases);
-    }
+    let user_stats = &mut ctx.accounts.user_stats;
+    record_purchase(
+        user_stats,
+        ctx.accounts.recipient.key(),
+        ctx.accounts.platform_config.settings.reputation_per_purchase,
+        current_time,
+    );
 
+    let promotion = &mut ctx.accounts.promotion;
     let coupon = &mut ctx.accounts.coupon;
-    coupon.id = coupon_id;
-    coupon.promotion = promotion.key();
-    coupon.owner = ctx.accounts.recipient.key();
-    coupon.merchant = ctx.accounts.merchant.key();
-    coupon.discount_percentage = promotion.discount_percentage;
-    coupon.expiry_timestamp = promotion.expiry_timestamp;
-    coupon.is_redeemed = false;
-    coupon.redeemed_at = 0;
-    coupon.created_at = current_time;
-    coupon.mint = Some(ctx.accounts.nft_mint.key());
-    coupon.metadata_uri = "https://example.com/metadata.json".to_string();
+    init_coupon(
+        coupon,
+        coupon_id,
+        promotion,
+        ctx.accounts.recipient.key(),
+        Some(ctx.accounts.nft_mint.key()),
+        current_time,
+    );
+    coupon.last_sale_price = price;
 
     // Mint NFT to recipient
     mint_to(
@@ -152,14 +245,19 @@ pub fn handler(ctx: Context<MintCoupon>, coupon_id: u64) -> Result<()> {
         .mint(&ctx.accounts.nft_mint.to_account_info(), true)
         .authority(&ctx.accounts.authority.to_account_info())
         .payer(&ctx.accounts.payer.to_account_info())
-        .update_authority(&ctx.accounts.authority.to_account_info(), true)
+        // The merchant PDA holds update authority so redemptions can rewrite
+        // the uses left (see redeem_coupon::update_uses_left)
+        .update_authority(&ctx.accounts.merchant.to_account_info(), false)
         .system_program(&ctx.accounts.system_program.to_account_info())
         .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
         .spl_token_program(Some(&ctx.accounts.token_program.to_account_info()))
-        .name("Discount Coupon".to_string())
+        .name(coupon.metadata_name())
         .symbol("DC".to_string())
         .uri(coupon.metadata_uri.clone())
-        .seller_fee_basis_points(0)
+        .seller_fee_basis_points(
+            ctx.accounts.merchant.royalty_basis_points
+                .min(ctx.accounts.platform_config.settings.max_royalty_basis_points),
+        )
         .token_standard(TokenStandard::NonFungible)
         .print_supply(PrintSupply::Zero)
         .invoke()?;
@@ -175,10 +273,217 @@ pub fn handler(ctx: Context<MintCoupon>, coupon_id: u64) -> Result<()> {
         recipient: ctx.accounts.recipient.key(),
         merchant: coupon.merchant,
         discount_percentage: coupon.discount_percentage,
+        price,
+        marketplace_fee,
     });
 
     msg!("Coupon minted! Purchases: {} | Reputation: {} | Tier: {:?}", 
         user_stats.total_purchases, user_stats.reputation_score, user_stats.tier);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// ============================================================================
+// Shared with the Token-2022 mint path and the voucher / promo-code claims
+// ============================================================================
+
+pub fn check_mintable(promotion: &Promotion, current_time: i64, presale_access: bool) -> Result<()> {
+    require!(promotion.is_active, CouponError::PromotionInactive);
+    require!(promotion.is_mintable_at(current_time, presale_access), CouponError::PromotionNotStarted);
+    require!(promotion.current_supply < promotion.max_supply, CouponError::SupplyExhausted);
+    require!(promotion.expiry_timestamp > current_time, CouponError::PromotionExpired);
+    Ok(())
+}
+
+// Coupon NFT whose mint and update authority is the merchant PDA, for claims
+// that carry no merchant signature (claim_with_voucher, claim_with_code).
+// Callers must have checked `merchant` against its seeds to get the bump.
+pub struct MerchantSignedMint<'info> {
+    pub merchant: AccountInfo<'info>,
+    pub merchant_authority: Pubkey,
+    pub merchant_bump: u8,
+    pub nft_mint: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub master_edition: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_metadata_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub sysvar_instructions: AccountInfo<'info>,
+}
+
+impl MerchantSignedMint<'_> {
+    pub fn invoke(&self, name: String, uri: String, seller_fee_basis_points: u16) -> Result<()> {
+        let merchant_seeds: &[&[u8]] = &[b"merchant", self.merchant_authority.as_ref(), &[self.merchant_bump]];
+
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                MintTo {
+                    mint: self.nft_mint.clone(),
+                    to: self.token_account.clone(),
+                    authority: self.merchant.clone(),
+                },
+                &[merchant_seeds],
+            ),
+            1,
+        )?;
+
+        CreateV1CpiBuilder::new(&self.token_metadata_program)
+            .metadata(&self.metadata)
+            .master_edition(Some(&self.master_edition))
+            .mint(&self.nft_mint, true)
+            .authority(&self.merchant)
+            .payer(&self.payer)
+            .update_authority(&self.merchant, true)
+            .system_program(&self.system_program)
+            .sysvar_instructions(&self.sysvar_instructions)
+            .spl_token_program(Some(&self.token_program))
+            .name(name)
+            .symbol("DC".to_string())
+            .uri(uri)
+            .seller_fee_basis_points(seller_fee_basis_points)
+            .token_standard(TokenStandard::NonFungible)
+            .print_supply(PrintSupply::Zero)
+            .invoke_signed(&[merchant_seeds])?;
+
+        Ok(())
+    }
+}
+
+// When the promotion has an allow-list, `recipient` must prove a leaf and may
+// mint up to its quantity. WalletMintCount doubles as the claim receipt:
+// `minted` is what the recipient has claimed so far.
+pub fn check_allow_list(
+    promotion: &Promotion,
+    recipient: Pubkey,
+    minted: u32,
+    allow_list_proof: Option<&AllowListProof>,
+) -> Result<()> {
+    if let Some(root) = &promotion.allow_list_root {
+        let claim = allow_list_proof.ok_or(CouponError::NotOnAllowList)?;
+        require!(
+            allow_list::verify(root, allow_list::hash_leaf(&recipient, claim.quantity), &claim.proof),
+            CouponError::NotOnAllowList
+        );
+        require!(minted < claim.quantity, CouponError::AllowListClaimed);
+    }
+    Ok(())
+}
+
+// Whether `recipient` is in the promotion's pre-sale audience: allow-listed via
+// a PresaleAllowance, or holding the pre-sale badge
+pub fn has_presale_access(
+    promotion: &Promotion,
+    recipient: Pubkey,
+    presale_allowance: Option<&Account<PresaleAllowance>>,
+    badge_nft: Option<&Account<BadgeNFT>>,
+) -> bool {
+    match &promotion.presale {
+        Some(presale) => presale.admits(
+            presale_allowance.is_some(),
+            badge_nft.filter(|badge| badge.user == recipient).map(|badge| badge.badge_type),
+        ),
+        None => false,
+    }
+}
+
+// Pays Promotion.price from the payer to the merchant, minus the marketplace fee.
+// The payment accounts come from Currency::account_for. Returns (price, marketplace_fee).
+pub fn collect_mint_payment<'info>(
+    promotion: &Promotion,
+    marketplace: &Marketplace,
+    currency: &Currency<'_, 'info>,
+    from: AccountInfo<'info>,
+    merchant_account: AccountInfo<'info>,
+    marketplace_account: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+) -> Result<(u64, u64)> {
+    let price = promotion.price;
+    let marketplace_fee = (price * marketplace.fee_basis_points as u64) / 10000;
+    let merchant_amount = price - marketplace_fee;
+
+    if price > 0 {
+        // Transfer payment to merchant
+        currency.transfer(from.clone(), merchant_account, payer.clone(), &[], merchant_amount)?;
+
+        // Transfer marketplace fee
+        currency.transfer(from, marketplace_account, payer, &[], marketplace_fee)?;
+    }
+
+    Ok((price, marketplace_fee))
+}
+
+// Counts a mint against the recipient's Promotion.max_per_wallet allowance
+pub fn record_wallet_mint(
+    wallet_mints: &mut Account<WalletMintCount>,
+    promotion: &Account<Promotion>,
+    recipient: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    if wallet_mints.wallet == Pubkey::default() {
+        wallet_mints.promotion = promotion.key();
+        wallet_mints.wallet = recipient;
+        wallet_mints.minted = 0;
+        wallet_mints.bump = bump;
+    }
+
+    require!(promotion.allows_wallet_mint(wallet_mints.minted), CouponError::WalletLimitReached);
+    wallet_mints.minted += 1;
+
+    Ok(())
+}
+
+pub fn record_purchase(user_stats: &mut UserStats, user: Pubkey, reputation: u64, current_time: i64) {
+    // Initialize UserStats if first time
+    if user_stats.user == Pubkey::default() {
+        user_stats.user = user;
+        user_stats.total_purchases = 0;
+        user_stats.total_redemptions = 0;
+        user_stats.total_ratings_given = 0;
+        user_stats.total_comments = 0;
+        user_stats.total_listings = 0;
+        user_stats.reputation_score = 0;
+        user_stats.tier = ReputationTier::Bronze;
+        user_stats.badges_earned = Vec::new();
+        user_stats.joined_at = current_time;
+        user_stats.last_activity = current_time;
+    }
+
+    // Update user stats for purchase
+    user_stats.total_purchases += 1;
+    user_stats.add_reputation(reputation);
+    user_stats.last_activity = current_time;
+
+    // Check for FirstPurchase badge eligibility
+    if user_stats.total_purchases == 1 && !user_stats.has_badge(BadgeType::FirstPurchase as u8) {
+        msg!("🏆 User eligible for FirstPurchase badge! Total purchases: {}", user_stats.total_purchases);
+    }
+}
+
+pub fn init_coupon(
+    coupon: &mut Account<Coupon>,
+    coupon_id: u64,
+    promotion: &Account<Promotion>,
+    owner: Pubkey,
+    mint: Option<Pubkey>,
+    current_time: i64,
+) {
+    coupon.id = coupon_id;
+    coupon.promotion = promotion.key();
+    coupon.owner = owner;
+    coupon.merchant = promotion.merchant;
+    coupon.discount_percentage = promotion.discount_percentage;
+    coupon.discount_kind = promotion.discount_kind;
+    coupon.expiry_timestamp = promotion.expiry_timestamp;
+    coupon.is_redeemed = false;
+    coupon.redeemed_at = 0;
+    coupon.uses_total = promotion.uses_per_coupon;
+    coupon.uses_remaining = promotion.uses_per_coupon;
+    coupon.created_at = current_time;
+    coupon.mint = mint;
+    coupon.metadata_uri = "https://example.com/metadata.json".to_string();
+    coupon.lock = CouponLock::None;
+    coupon.last_sale_price = 0;
+}
@@ -0,0 +1,329 @@
+// src/instructions/mint_hooked_coupon.rs
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, mint_to, MintTo, token_metadata_initialize, TokenMetadataInitialize,
+};
+use anchor_spl::token_interface::spl_pod::optional_keys::OptionalNonZeroPubkey;
+use anchor_spl::token_interface::spl_token_metadata_interface::state::TokenMetadata;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use crate::state::{Coupon, Promotion, Merchant, MerchantStaff, Marketplace, PlatformConfig, UserStats, PresaleAllowance, BadgeNFT, WalletMintCount};
+use crate::allow_list::AllowListProof;
+use crate::errors::CouponError;
+use crate::events::CouponMinted;
+use super::mint_coupon::{check_mintable, check_allow_list, has_presale_access, record_wallet_mint, collect_mint_payment, record_purchase, init_coupon};
+use super::payments::Currency;
+
+// Token-2022 variant of mint_coupon. The mint carries a transfer hook pointing
+// back at this program, so wallet-to-wallet transfers keep Coupon.owner in sync
+// (see transfer_hook.rs). Metadata lives on the mint itself instead of Metaplex.
+// Listings and stakes escrow these with the client's own transfers (see escrow.rs).
+#[derive(Accounts)]
+pub struct MintHookedCoupon<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Coupon::INIT_SPACE,
+        seeds = [b"coupon", promotion.key().as_ref(), &promotion.current_supply.to_le_bytes()],
+        bump
+    )]
+    pub coupon: Box<Account<'info, Coupon>>,
+
+    /// CHECK: Token-2022 mint for the NFT
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = merchant,
+        mint::freeze_authority = merchant,
+        mint::token_program = token_program,
+        extensions::transfer_hook::authority = merchant,
+        extensions::transfer_hook::program_id = crate::ID,
+        extensions::metadata_pointer::authority = merchant,
+        extensions::metadata_pointer::metadata_address = nft_mint,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Associated Token Account for recipient
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: ExtraAccountMetaList read by Token-2022 to resolve the hook's accounts
+    #[account(
+        init,
+        payer = payer,
+        space = ExtraAccountMetaList::size_of(1).unwrap(),
+        seeds = [b"extra-account-metas", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub promotion: Box<Account<'info, Promotion>>,
+
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref().map(|staff| &**staff),
+            MerchantStaff::ROLE_ISSUE_VOUCHERS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Box<Account<'info, MerchantStaff>>>,
+
+    #[account(
+        mut,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+
+    /// CHECK: Recipient of the NFT
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Merchant authority receiving the sale proceeds
+    #[account(
+        mut,
+        constraint = merchant.authority == merchant_authority.key() @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Marketplace authority for fee collection
+    #[account(
+        mut,
+        constraint = marketplace.authority == marketplace_authority.key() @ CouponError::NotMarketplaceAuthority
+    )]
+    pub marketplace_authority: UncheckedAccount<'info>,
+
+    // SPL payment accounts, only needed when promotion.payment_mint is set
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub payer_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub merchant_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub marketplace_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Pre-sale access for `recipient`; only needed before promotion.start_timestamp
+    #[account(
+        seeds = [b"presale_allowance", promotion.key().as_ref(), recipient.key().as_ref()],
+        bump = presale_allowance.bump
+    )]
+    pub presale_allowance: Option<Box<Account<'info, PresaleAllowance>>>,
+    pub badge_nft: Option<Box<Account<'info, BadgeNFT>>>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [b"user_stats", recipient.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + WalletMintCount::INIT_SPACE,
+        seeds = [b"wallet_mints", promotion.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub wallet_mints: Box<Account<'info, WalletMintCount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<MintHookedCoupon>,
+    coupon_id: u64,
+    allow_list_proof: Option<AllowListProof>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let presale_access = has_presale_access(
+        &ctx.accounts.promotion,
+        ctx.accounts.recipient.key(),
+        ctx.accounts.presale_allowance.as_deref(),
+        ctx.accounts.badge_nft.as_deref(),
+    );
+    check_mintable(&ctx.accounts.promotion, current_time, presale_access)?;
+    check_allow_list(
+        &ctx.accounts.promotion,
+        ctx.accounts.recipient.key(),
+        ctx.accounts.wallet_mints.minted,
+        allow_list_proof.as_ref(),
+    )?;
+
+    // Collect the primary sale price from the payer
+    let currency = Currency::resolve(
+        ctx.accounts.promotion.payment_mint,
+        ctx.accounts.payment_mint.as_deref(),
+        ctx.accounts.payment_token_program.as_ref(),
+        &ctx.accounts.system_program,
+    )?;
+    let payer = ctx.accounts.payer.to_account_info();
+    let (price, marketplace_fee) = collect_mint_payment(
+        &ctx.accounts.promotion,
+        &ctx.accounts.marketplace,
+        &currency,
+        currency.account_for(&payer, ctx.accounts.payer_payment_account.as_deref())?,
+        currency.account_for(
+            &ctx.accounts.merchant_authority.to_account_info(),
+            ctx.accounts.merchant_payment_account.as_deref(),
+        )?,
+        currency.account_for(
+            &ctx.accounts.marketplace_authority.to_account_info(),
+            ctx.accounts.marketplace_payment_account.as_deref(),
+        )?,
+        payer,
+    )?;
+
+    record_wallet_mint(
+        &mut ctx.accounts.wallet_mints,
+        &ctx.accounts.promotion,
+        ctx.accounts.recipient.key(),
+        ctx.bumps.wallet_mints,
+    )?;
+
+    let user_stats = &mut ctx.accounts.user_stats;
+    record_purchase(
+        user_stats,
+        ctx.accounts.recipient.key(),
+        ctx.accounts.platform_config.settings.reputation_per_purchase,
+        current_time,
+    );
+
+    let promotion = &mut ctx.accounts.promotion;
+    let coupon = &mut ctx.accounts.coupon;
+    init_coupon(
+        coupon,
+        coupon_id,
+        promotion,
+        ctx.accounts.recipient.key(),
+        Some(ctx.accounts.nft_mint.key()),
+        current_time,
+    );
+    coupon.last_sale_price = price;
+
+    // The hook needs the coupon account on every transfer
+    let extra_account_metas = [ExtraAccountMeta::new_with_pubkey(&coupon.key(), false, true)?];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+        &extra_account_metas,
+    )?;
+
+    // Fund the mint for the metadata Token-2022 appends to it
+    let metadata = TokenMetadata {
+        // The merchant PDA, so redemptions can rewrite the uses left
+        update_authority: OptionalNonZeroPubkey::try_from(Some(ctx.accounts.merchant.key()))?,
+        mint: ctx.accounts.nft_mint.key(),
+        name: coupon.metadata_name(),
+        symbol: "DC".to_string(),
+        uri: coupon.metadata_uri.clone(),
+        additional_metadata: vec![],
+    };
+    let mint_info = ctx.accounts.nft_mint.to_account_info();
+    let required_lamports = Rent::get()?.minimum_balance(mint_info.data_len() + metadata.tlv_size_of()?);
+    let top_up = required_lamports.saturating_sub(mint_info.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: mint_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+
+    // The merchant PDA holds every authority on the mint, so a staff key that
+    // minted the coupon can't mint more of it or repoint the hook later
+    let merchant_seeds: &[&[u8]] = &[
+        b"merchant",
+        ctx.accounts.merchant.authority.as_ref(),
+        &[ctx.bumps.merchant],
+    ];
+    token_metadata_initialize(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TokenMetadataInitialize {
+                program_id: ctx.accounts.token_program.to_account_info(),
+                metadata: mint_info.clone(),
+                update_authority: ctx.accounts.merchant.to_account_info(),
+                mint_authority: ctx.accounts.merchant.to_account_info(),
+                mint: mint_info.clone(),
+            },
+            &[merchant_seeds],
+        ),
+        metadata.name,
+        metadata.symbol,
+        metadata.uri,
+    )?;
+
+    // Mint NFT to recipient
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: mint_info,
+                to: ctx.accounts.token_account.to_account_info(),
+                authority: ctx.accounts.merchant.to_account_info(),
+            },
+            &[merchant_seeds],
+        ),
+        1,
+    )?;
+
+    promotion.current_supply += 1;
+    ctx.accounts.merchant.total_coupons_created += 1;
+    ctx.accounts.marketplace.total_coupons += 1;
+
+    emit!(CouponMinted {
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        promotion: promotion.key(),
+        recipient: ctx.accounts.recipient.key(),
+        merchant: coupon.merchant,
+        discount_percentage: coupon.discount_percentage,
+        price,
+        marketplace_fee,
+    });
+
+    msg!("Hooked coupon minted! Purchases: {} | Reputation: {} | Tier: {:?}",
+        user_stats.total_purchases, user_stats.reputation_score, user_stats.tier);
+
+    Ok(())
+}
@@ -1,8 +1,19 @@
 // src/instructions/mod.rs
 pub mod initialize;
 pub mod register_merchant;
+pub mod set_merchant_royalty;
+pub mod merchant_staff;
+pub mod merchant_branches;
 pub mod create_promotion;
+pub mod promotion_lifecycle;
+pub mod geo_cells;
+pub mod presale;
 pub mod mint_coupon;
+pub mod mint_hooked_coupon;
+pub mod claim_with_voucher;
+pub mod promo_codes;
+pub mod loyalty;
+pub mod transfer_hook;
 pub mod transfer_coupon;
 pub mod redeem_coupon;
 pub mod list_for_sale;
@@ -19,14 +30,29 @@ pub mod auto_award_badge;
 pub mod redemption_tickets;
 pub mod group_deals;
 pub mod auctions;
+pub mod marketplace_admin;
+pub mod update_platform_config;
+pub mod payments;
+pub mod escrow;
 
 
 
 
 pub use initialize::*;
 pub use register_merchant::*;
+pub use set_merchant_royalty::*;
+pub use merchant_staff::*;
+pub use merchant_branches::*;
 pub use create_promotion::*;
+pub use promotion_lifecycle::*;
+pub use geo_cells::*;
+pub use presale::*;
 pub use mint_coupon::*;
+pub use mint_hooked_coupon::*;
+pub use claim_with_voucher::*;
+pub use promo_codes::*;
+pub use loyalty::*;
+pub use transfer_hook::*;
 pub use transfer_coupon::*;
 pub use redeem_coupon::*;
 pub use list_for_sale::*;
@@ -42,4 +68,6 @@ pub use claim_rewards::*;
 pub use auto_award_badge::*;
 pub use redemption_tickets::*;
 pub use group_deals::*;
-pub use auctions::*;
\ No newline at end of file
+pub use auctions::*;
+pub use marketplace_admin::*;
+pub use update_platform_config::*;
\ No newline at end of file
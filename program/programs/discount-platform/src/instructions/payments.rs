@@ -0,0 +1,95 @@
+// src/instructions/payments.rs
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked,
+};
+use crate::errors::CouponError;
+
+// What a sale settles in. Promotions, listings, auctions and group deals carry
+// an optional payment_mint; None keeps the original lamport transfers, Some(mint)
+// moves the same amounts between token accounts of that mint instead.
+#[derive(Clone)]
+pub enum Currency<'a, 'info> {
+    Sol {
+        system_program: AccountInfo<'info>,
+    },
+    Token {
+        mint: &'a InterfaceAccount<'info, Mint>,
+        token_program: &'a Interface<'info, TokenInterface>,
+    },
+}
+
+impl<'a, 'info> Currency<'a, 'info> {
+    pub fn resolve(
+        payment_mint: Option<Pubkey>,
+        mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        token_program: Option<&'a Interface<'info, TokenInterface>>,
+        system_program: &Program<'info, System>,
+    ) -> Result<Self> {
+        let Some(expected) = payment_mint else {
+            return Ok(Currency::Sol {
+                system_program: system_program.to_account_info(),
+            });
+        };
+        let mint = mint.ok_or(CouponError::MissingPaymentAccount)?;
+        let token_program = token_program.ok_or(CouponError::MissingPaymentAccount)?;
+        require_keys_eq!(mint.key(), expected, CouponError::PaymentMintMismatch);
+        require_keys_eq!(*mint.to_account_info().owner, token_program.key(), CouponError::PaymentMintMismatch);
+        Ok(Currency::Token { mint, token_program })
+    }
+
+    // The account that actually sends or receives funds for `wallet`: the wallet
+    // itself for SOL, otherwise its token account for the payment mint.
+    pub fn account_for(
+        &self,
+        wallet: &AccountInfo<'info>,
+        token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    ) -> Result<AccountInfo<'info>> {
+        match self {
+            Currency::Sol { .. } => Ok(wallet.clone()),
+            Currency::Token { mint, .. } => {
+                let token_account = token_account.ok_or(CouponError::MissingPaymentAccount)?;
+                require_keys_eq!(token_account.mint, mint.key(), CouponError::PaymentMintMismatch);
+                require_keys_eq!(token_account.owner, wallet.key(), CouponError::InvalidPaymentAccount);
+                Ok(token_account.to_account_info())
+            }
+        }
+    }
+
+    // Moves `amount` between two accounts returned by account_for. `authority`
+    // signs token transfers; SOL transfers are signed by `from` itself.
+    pub fn transfer(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        match self {
+            Currency::Sol { system_program } => transfer(
+                CpiContext::new_with_signer(system_program.clone(), Transfer { from, to }, signer_seeds),
+                amount,
+            ),
+            Currency::Token { mint, token_program } => transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from,
+                        mint: mint.to_account_info(),
+                        to,
+                        authority,
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                mint.decimals,
+            ),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// src/instructions/presale.rs
+use anchor_lang::prelude::*;
+use crate::state::{Promotion, Merchant, MerchantStaff, PresaleAllowance};
+use crate::errors::CouponError;
+use crate::events::{PresaleWalletAdded, PresaleWalletRemoved};
+
+// ============================================================================
+// Add Pre-sale Wallet (lets `wallet` mint before the promotion starts)
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddPresaleWallet<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PresaleAllowance::INIT_SPACE,
+        seeds = [b"presale_allowance", promotion.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub presale_allowance: Account<'info, PresaleAllowance>,
+
+    #[account(
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub promotion: Account<'info, Promotion>,
+
+    #[account(
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_CREATE_PROMOTIONS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_presale_wallet(ctx: Context<AddPresaleWallet>, wallet: Pubkey) -> Result<()> {
+    let presale_allowance = &mut ctx.accounts.presale_allowance;
+    presale_allowance.promotion = ctx.accounts.promotion.key();
+    presale_allowance.wallet = wallet;
+    presale_allowance.created_at = Clock::get()?.unix_timestamp;
+    presale_allowance.bump = ctx.bumps.presale_allowance;
+
+    emit!(PresaleWalletAdded {
+        promotion: presale_allowance.promotion,
+        wallet,
+        timestamp: presale_allowance.created_at,
+    });
+
+    msg!("✅ Pre-sale wallet added: {}", wallet);
+
+    Ok(())
+}
+
+// ============================================================================
+// Remove Pre-sale Wallet (closes the allowance, rent back to the signer)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct RemovePresaleWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale_allowance", promotion.key().as_ref(), presale_allowance.wallet.as_ref()],
+        bump = presale_allowance.bump,
+        close = authority
+    )]
+    pub presale_allowance: Account<'info, PresaleAllowance>,
+
+    #[account(
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub promotion: Account<'info, Promotion>,
+
+    #[account(
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_CREATE_PROMOTIONS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn remove_presale_wallet(ctx: Context<RemovePresaleWallet>) -> Result<()> {
+    let presale_allowance = &ctx.accounts.presale_allowance;
+
+    emit!(PresaleWalletRemoved {
+        promotion: presale_allowance.promotion,
+        wallet: presale_allowance.wallet,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Pre-sale wallet removed: {}", presale_allowance.wallet);
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_add_presale_wallet(ctx: Context<AddPresaleWallet>, wallet: Pubkey) -> Result<()> {
+    add_presale_wallet(ctx, wallet)
+}
+
+pub fn handler_remove_presale_wallet(ctx: Context<RemovePresaleWallet>) -> Result<()> {
+    remove_presale_wallet(ctx)
+}
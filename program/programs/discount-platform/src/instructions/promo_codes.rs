@@ -0,0 +1,283 @@
+// src/instructions/promo_codes.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::{
+    Coupon, Promotion, Merchant, Marketplace, PlatformConfig, UserStats, WalletMintCount,
+    PromoCodeCommit, PromoCodeClaim,
+};
+use crate::allow_list;
+use crate::promo_codes::{self, MAX_CODE_LEN};
+use crate::errors::CouponError;
+use crate::events::{CouponMinted, PromoCodeClaimed};
+use super::mint_coupon::{check_mintable, record_wallet_mint, record_purchase, init_coupon, MerchantSignedMint};
+
+// ============================================================================
+// Commit (hides the code until a later slot; see promo_codes.rs)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct CommitPromoCode<'info> {
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        space = 8 + PromoCodeCommit::INIT_SPACE,
+        seeds = [b"promo_code_commit", promotion.key().as_ref(), claimer.key().as_ref()],
+        bump
+    )]
+    pub promo_code_commit: Account<'info, PromoCodeCommit>,
+
+    #[account(
+        constraint = promotion.promo_code_root.is_some() @ CouponError::InvalidPromoCode
+    )]
+    pub promotion: Account<'info, Promotion>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn commit_promo_code(ctx: Context<CommitPromoCode>, commitment: [u8; 32]) -> Result<()> {
+    let promo_code_commit = &mut ctx.accounts.promo_code_commit;
+    promo_code_commit.promotion = ctx.accounts.promotion.key();
+    promo_code_commit.claimer = ctx.accounts.claimer.key();
+    promo_code_commit.commitment = commitment;
+    promo_code_commit.slot = Clock::get()?.slot;
+    promo_code_commit.bump = ctx.bumps.promo_code_commit;
+
+    msg!("✅ Promo code committed at slot {}", promo_code_commit.slot);
+
+    Ok(())
+}
+
+// ============================================================================
+// Claim (reveals the code, marks it spent and mints a free coupon)
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(coupon_id: u64, code: String)]
+pub struct ClaimWithCode<'info> {
+    #[account(
+        mut,
+        seeds = [b"promo_code_commit", promotion.key().as_ref(), claimer.key().as_ref()],
+        bump = promo_code_commit.bump,
+        close = claimer
+    )]
+    pub promo_code_commit: Box<Account<'info, PromoCodeCommit>>,
+
+    #[account(
+        init,
+        payer = claimer,
+        space = 8 + PromoCodeClaim::INIT_SPACE,
+        seeds = [b"promo_code", promotion.key().as_ref(), &promo_codes::hash_code(code.as_bytes())],
+        bump
+    )]
+    pub promo_code_claim: Box<Account<'info, PromoCodeClaim>>,
+
+    #[account(
+        init,
+        payer = claimer,
+        space = 8 + Coupon::INIT_SPACE,
+        seeds = [b"coupon", promotion.key().as_ref(), &promotion.current_supply.to_le_bytes()],
+        bump
+    )]
+    pub coupon: Box<Account<'info, Coupon>>,
+
+    #[account(
+        init,
+        payer = claimer,
+        mint::decimals = 0,
+        mint::authority = merchant,
+        mint::freeze_authority = merchant
+    )]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = claimer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = claimer
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metadata account
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Master Edition account
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub promotion: Box<Account<'info, Promotion>>,
+
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(
+        mut,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [b"user_stats", claimer.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        space = 8 + WalletMintCount::INIT_SPACE,
+        seeds = [b"wallet_mints", promotion.key().as_ref(), claimer.key().as_ref()],
+        bump
+    )]
+    pub wallet_mints: Box<Account<'info, WalletMintCount>>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Sysvar Instructions
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn claim_with_code(
+    ctx: Context<ClaimWithCode>,
+    coupon_id: u64,
+    code: String,
+    salt: [u8; 32],
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let current_time = clock.unix_timestamp;
+    let claimer = ctx.accounts.claimer.key();
+
+    require!(!code.is_empty() && code.len() <= MAX_CODE_LEN, CouponError::InvalidPromoCode);
+    let promo_code_commit = &ctx.accounts.promo_code_commit;
+    require!(promo_code_commit.slot < clock.slot, CouponError::RevealTooEarly);
+    require!(
+        promo_code_commit.commitment == promo_codes::hash_commitment(code.as_bytes(), &claimer, &salt),
+        CouponError::CommitmentMismatch
+    );
+
+    let code_hash = promo_codes::hash_code(code.as_bytes());
+    let Some(root) = &ctx.accounts.promotion.promo_code_root else {
+        return err!(CouponError::InvalidPromoCode);
+    };
+    require!(allow_list::verify(root, code_hash, &proof), CouponError::InvalidPromoCode);
+
+    // Like a voucher, a code is a grant from the merchant in itself, so the
+    // allow-list and pre-sale don't apply. Start time, supply and per-wallet
+    // limits do.
+    check_mintable(&ctx.accounts.promotion, current_time, false)?;
+    record_wallet_mint(&mut ctx.accounts.wallet_mints, &ctx.accounts.promotion, claimer, ctx.bumps.wallet_mints)?;
+
+    let promo_code_claim = &mut ctx.accounts.promo_code_claim;
+    promo_code_claim.promotion = ctx.accounts.promotion.key();
+    promo_code_claim.code_hash = code_hash;
+    promo_code_claim.claimer = claimer;
+    promo_code_claim.claimed_at = current_time;
+    promo_code_claim.bump = ctx.bumps.promo_code_claim;
+
+    let user_stats = &mut ctx.accounts.user_stats;
+    record_purchase(
+        user_stats,
+        claimer,
+        ctx.accounts.platform_config.settings.reputation_per_purchase,
+        current_time,
+    );
+
+    let promotion = &mut ctx.accounts.promotion;
+    let coupon = &mut ctx.accounts.coupon;
+    init_coupon(coupon, coupon_id, promotion, claimer, Some(ctx.accounts.nft_mint.key()), current_time);
+
+    MerchantSignedMint {
+        merchant: ctx.accounts.merchant.to_account_info(),
+        merchant_authority: ctx.accounts.merchant.authority,
+        merchant_bump: ctx.bumps.merchant,
+        nft_mint: ctx.accounts.nft_mint.to_account_info(),
+        token_account: ctx.accounts.token_account.to_account_info(),
+        metadata: ctx.accounts.metadata.to_account_info(),
+        master_edition: ctx.accounts.master_edition.to_account_info(),
+        payer: ctx.accounts.claimer.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+    }
+    .invoke(
+        coupon.metadata_name(),
+        coupon.metadata_uri.clone(),
+        ctx.accounts.merchant.royalty_basis_points
+            .min(ctx.accounts.platform_config.settings.max_royalty_basis_points),
+    )?;
+
+    promotion.current_supply += 1;
+    ctx.accounts.merchant.total_coupons_created += 1;
+    ctx.accounts.marketplace.total_coupons += 1;
+
+    emit!(CouponMinted {
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        promotion: promotion.key(),
+        recipient: claimer,
+        merchant: coupon.merchant,
+        discount_percentage: coupon.discount_percentage,
+        price: 0,
+        marketplace_fee: 0,
+    });
+
+    emit!(PromoCodeClaimed {
+        promotion: promotion.key(),
+        claimer,
+        code_hash,
+        timestamp: current_time,
+    });
+
+    msg!("✅ Promo code claimed by {}", claimer);
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_commit_promo_code(ctx: Context<CommitPromoCode>, commitment: [u8; 32]) -> Result<()> {
+    commit_promo_code(ctx, commitment)
+}
+
+pub fn handler_claim_with_code(
+    ctx: Context<ClaimWithCode>,
+    coupon_id: u64,
+    code: String,
+    salt: [u8; 32],
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    claim_with_code(ctx, coupon_id, code, salt, proof)
+}
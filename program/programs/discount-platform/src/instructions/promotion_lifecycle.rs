@@ -0,0 +1,456 @@
+// src/instructions/promotion_lifecycle.rs
+use anchor_lang::prelude::*;
+use crate::state::{Promotion, DiscountKind, PresaleWindow, RedemptionSchedule, Merchant, MerchantStaff, GeoCell, GeoCellEntry};
+use super::geo_cells::remove_from_geo_cell;
+use crate::errors::CouponError;
+use crate::events::{
+    PromotionUpdated,
+    PromotionStatusChanged,
+    PromotionExtended,
+    PromotionSupplyIncreased,
+    PromotionDiscountKindUpdated,
+    PromotionUsesUpdated,
+    PromotionWalletLimitUpdated,
+    PromotionAllowListUpdated,
+    PromotionPromoCodesUpdated,
+    PromotionLaunchUpdated,
+    PromotionScheduleUpdated,
+    PromotionBranchesUpdated,
+    PromotionClosed,
+};
+
+// ============================================================================
+// Merchant-gated updates (details, pause, expiry, supply, launch, rules)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct ManagePromotion<'info> {
+    #[account(
+        mut,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant
+    )]
+    pub promotion: Account<'info, Promotion>,
+
+    #[account(
+        constraint = merchant.is_authorized_for(
+            &authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_CREATE_PROMOTIONS,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn update_promotion_details(
+    ctx: Context<ManagePromotion>,
+    category: String,
+    description: String,
+) -> Result<()> {
+    require!(category.len() <= 30, CouponError::CategoryTooLong);
+    require!(description.len() <= 200, CouponError::DescriptionTooLong);
+
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.category = category;
+    promotion.description = description;
+
+    emit!(PromotionUpdated {
+        promotion: promotion.key(),
+        category: promotion.category.clone(),
+        description: promotion.description.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion details updated");
+
+    Ok(())
+}
+
+pub fn set_promotion_active(ctx: Context<ManagePromotion>, is_active: bool) -> Result<()> {
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.is_active = is_active;
+
+    emit!(PromotionStatusChanged {
+        promotion: promotion.key(),
+        is_active,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion active: {}", is_active);
+
+    Ok(())
+}
+
+pub fn extend_promotion(ctx: Context<ManagePromotion>, new_expiry_timestamp: i64) -> Result<()> {
+    let promotion = &mut ctx.accounts.promotion;
+
+    // Minted coupons keep the expiry they were minted with, so once any exist
+    // only allow moving the end forward; shortening would leave coupons
+    // outliving their promotion. Before the first mint it may be pulled in too.
+    require!(
+        new_expiry_timestamp > Clock::get()?.unix_timestamp,
+        CouponError::InvalidExpiry
+    );
+    require!(
+        new_expiry_timestamp > promotion.expiry_timestamp || promotion.current_supply == 0,
+        CouponError::InvalidExpiry
+    );
+
+    let old_expiry_timestamp = promotion.expiry_timestamp;
+    promotion.expiry_timestamp = new_expiry_timestamp;
+
+    emit!(PromotionExtended {
+        promotion: promotion.key(),
+        old_expiry_timestamp,
+        new_expiry_timestamp,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion expiry changed: {} -> {}", old_expiry_timestamp, new_expiry_timestamp);
+
+    Ok(())
+}
+
+pub fn increase_promotion_supply(ctx: Context<ManagePromotion>, new_max_supply: u32) -> Result<()> {
+    let promotion = &mut ctx.accounts.promotion;
+    require!(new_max_supply > promotion.max_supply, CouponError::InvalidSupply);
+
+    let old_max_supply = promotion.max_supply;
+    promotion.max_supply = new_max_supply;
+
+    emit!(PromotionSupplyIncreased {
+        promotion: promotion.key(),
+        old_max_supply,
+        new_max_supply,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion supply: {} -> {}", old_max_supply, new_max_supply);
+
+    Ok(())
+}
+
+// Turns a percentage promotion into a fixed-amount, BOGO, free-item or
+// minimum-spend offer. Only before the first mint, so every coupon of a
+// promotion carries the same kind.
+pub fn set_discount_kind(ctx: Context<ManagePromotion>, discount_kind: DiscountKind) -> Result<()> {
+    require!(discount_kind.is_valid(), CouponError::InvalidDiscount);
+
+    let promotion = &mut ctx.accounts.promotion;
+    require!(promotion.current_supply == 0, CouponError::DiscountLocked);
+
+    promotion.discount_kind = discount_kind;
+    promotion.discount_percentage = discount_kind.percent();
+
+    emit!(PromotionDiscountKindUpdated {
+        promotion: promotion.key(),
+        discount_kind,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion discount: {:?}", discount_kind);
+
+    Ok(())
+}
+
+// Turns coupons minted from now on into punch cards redeemable `uses_per_coupon`
+// times; 1 makes them single-use again. Coupons already minted keep their count.
+pub fn set_uses_per_coupon(ctx: Context<ManagePromotion>, uses_per_coupon: u16) -> Result<()> {
+    require!(uses_per_coupon > 0, CouponError::InvalidUses);
+
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.uses_per_coupon = uses_per_coupon;
+
+    emit!(PromotionUsesUpdated {
+        promotion: promotion.key(),
+        uses_per_coupon,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion uses per coupon: {}", uses_per_coupon);
+
+    Ok(())
+}
+
+pub fn set_max_per_wallet(ctx: Context<ManagePromotion>, max_per_wallet: Option<u32>) -> Result<()> {
+    require!(max_per_wallet != Some(0), CouponError::InvalidWalletLimit);
+
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.max_per_wallet = max_per_wallet;
+
+    emit!(PromotionWalletLimitUpdated {
+        promotion: promotion.key(),
+        max_per_wallet,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion per-wallet limit: {:?}", max_per_wallet);
+
+    Ok(())
+}
+
+// Restricts minting to the wallets in a merkle allow-list, or lifts the
+// restriction with None. Claims so far still count against each leaf's quantity.
+pub fn set_allow_list(ctx: Context<ManagePromotion>, allow_list_root: Option<[u8; 32]>) -> Result<()> {
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.allow_list_root = allow_list_root;
+
+    emit!(PromotionAllowListUpdated {
+        promotion: promotion.key(),
+        allow_list_root,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion allow-list: {}", allow_list_root.is_some());
+
+    Ok(())
+}
+
+// Attaches a batch of promo codes, or detaches them with None. A new root
+// replaces the old one, so include earlier codes to keep them claimable; codes
+// already claimed stay spent either way.
+pub fn set_promo_codes(ctx: Context<ManagePromotion>, promo_code_root: Option<[u8; 32]>) -> Result<()> {
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.promo_code_root = promo_code_root;
+
+    emit!(PromotionPromoCodesUpdated {
+        promotion: promotion.key(),
+        promo_code_root,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion promo codes: {}", promo_code_root.is_some());
+
+    Ok(())
+}
+
+// Announces a drop: minting opens at `start_timestamp`, and optionally earlier
+// for the pre-sale audience. Send it with create_promotion to keep the
+// promotion closed from the start.
+pub fn set_promotion_launch(
+    ctx: Context<ManagePromotion>,
+    start_timestamp: i64,
+    presale: Option<PresaleWindow>,
+) -> Result<()> {
+    let promotion = &mut ctx.accounts.promotion;
+    require!(start_timestamp < promotion.expiry_timestamp, CouponError::InvalidLaunchWindow);
+    if let Some(presale) = &presale {
+        require!(presale.start_timestamp < start_timestamp, CouponError::InvalidLaunchWindow);
+    }
+
+    promotion.start_timestamp = start_timestamp;
+    promotion.presale = presale;
+
+    emit!(PromotionLaunchUpdated {
+        promotion: promotion.key(),
+        start_timestamp,
+        presale,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion launch: {} (pre-sale: {})", start_timestamp, presale.is_some());
+
+    Ok(())
+}
+
+pub fn set_redemption_schedule(
+    ctx: Context<ManagePromotion>,
+    redemption_schedule: Option<RedemptionSchedule>,
+) -> Result<()> {
+    if let Some(schedule) = &redemption_schedule {
+        require!(schedule.is_valid(), CouponError::InvalidSchedule);
+    }
+
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.redemption_schedule = redemption_schedule;
+
+    emit!(PromotionScheduleUpdated {
+        promotion: promotion.key(),
+        redemption_schedule,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Redemption schedule: {:?}", redemption_schedule);
+
+    Ok(())
+}
+
+pub fn set_promotion_branches(ctx: Context<ManagePromotion>, allowed_branches: Vec<u32>) -> Result<()> {
+    require!(
+        allowed_branches.len() <= Promotion::MAX_ALLOWED_BRANCHES,
+        CouponError::TooManyBranches
+    );
+    let branch_count = ctx.accounts.merchant.branch_count;
+    require!(
+        allowed_branches.iter().all(|index| *index < branch_count),
+        CouponError::InvalidBranch
+    );
+
+    let promotion = &mut ctx.accounts.promotion;
+    promotion.allowed_branches = allowed_branches;
+
+    emit!(PromotionBranchesUpdated {
+        promotion: promotion.key(),
+        allowed_branches: promotion.allowed_branches.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion branches: {:?}", promotion.allowed_branches);
+
+    Ok(())
+}
+
+// ============================================================================
+// Close Promotion (after expiry, rent back to the merchant)
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct ClosePromotion<'info> {
+    #[account(
+        mut,
+        constraint = promotion.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = promotion.expiry_timestamp <= Clock::get()?.unix_timestamp @ CouponError::PromotionNotExpired,
+        close = authority
+    )]
+    pub promotion: Account<'info, Promotion>,
+
+    // Owner only: the rent goes to the signer, so a staff key could pocket it
+    #[account(
+        constraint = merchant.is_authorized(&authority.key()) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    // GeoCell index accounts, only needed when the promotion is geofenced
+    // (see ClearPromotionGeofence)
+    #[account(
+        mut,
+        seeds = [b"geo_cell", &promotion.geo_cell_id.to_le_bytes()],
+        bump = geo_cell.bump
+    )]
+    pub geo_cell: Option<Box<Account<'info, GeoCell>>>,
+    #[account(
+        mut,
+        seeds = [b"geo_cell_entry", promotion.geo_cell_id.to_le_bytes().as_ref(), &geo_cell_entry.index.to_le_bytes()],
+        bump = geo_cell_entry.bump
+    )]
+    pub geo_cell_entry: Option<Box<Account<'info, GeoCellEntry>>>,
+    #[account(
+        mut,
+        seeds = [
+            b"geo_cell_entry",
+            promotion.geo_cell_id.to_le_bytes().as_ref(),
+            &geo_cell.as_ref().map_or(0, |geo_cell| geo_cell.promotion_count).saturating_sub(1).to_le_bytes()
+        ],
+        bump = last_geo_cell_entry.bump
+    )]
+    pub last_geo_cell_entry: Option<Box<Account<'info, GeoCellEntry>>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn close_promotion(ctx: Context<ClosePromotion>) -> Result<()> {
+    let promotion = &ctx.accounts.promotion;
+
+    if promotion.is_location_based {
+        let (Some(geo_cell), Some(geo_cell_entry)) =
+            (ctx.accounts.geo_cell.as_deref_mut(), ctx.accounts.geo_cell_entry.as_deref_mut())
+        else {
+            return err!(CouponError::InvalidGeoCellEntry);
+        };
+        remove_from_geo_cell(
+            geo_cell,
+            geo_cell_entry,
+            ctx.accounts.last_geo_cell_entry.as_deref_mut(),
+            promotion.key(),
+            ctx.accounts.authority.to_account_info(),
+        )?;
+    }
+
+    emit!(PromotionClosed {
+        promotion: promotion.key(),
+        merchant: promotion.merchant,
+        coupons_minted: promotion.current_supply,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Promotion closed and rent reclaimed");
+
+    Ok(())
+}
+
+// ============================================================================
+// Handlers for lib.rs
+// ============================================================================
+
+pub fn handler_update_promotion_details(
+    ctx: Context<ManagePromotion>,
+    category: String,
+    description: String,
+) -> Result<()> {
+    update_promotion_details(ctx, category, description)
+}
+
+pub fn handler_set_promotion_active(ctx: Context<ManagePromotion>, is_active: bool) -> Result<()> {
+    set_promotion_active(ctx, is_active)
+}
+
+pub fn handler_extend_promotion(ctx: Context<ManagePromotion>, new_expiry_timestamp: i64) -> Result<()> {
+    extend_promotion(ctx, new_expiry_timestamp)
+}
+
+pub fn handler_increase_promotion_supply(ctx: Context<ManagePromotion>, new_max_supply: u32) -> Result<()> {
+    increase_promotion_supply(ctx, new_max_supply)
+}
+
+pub fn handler_set_discount_kind(ctx: Context<ManagePromotion>, discount_kind: DiscountKind) -> Result<()> {
+    set_discount_kind(ctx, discount_kind)
+}
+
+pub fn handler_set_uses_per_coupon(ctx: Context<ManagePromotion>, uses_per_coupon: u16) -> Result<()> {
+    set_uses_per_coupon(ctx, uses_per_coupon)
+}
+
+pub fn handler_set_max_per_wallet(ctx: Context<ManagePromotion>, max_per_wallet: Option<u32>) -> Result<()> {
+    set_max_per_wallet(ctx, max_per_wallet)
+}
+
+pub fn handler_set_allow_list(ctx: Context<ManagePromotion>, allow_list_root: Option<[u8; 32]>) -> Result<()> {
+    set_allow_list(ctx, allow_list_root)
+}
+
+pub fn handler_set_promo_codes(ctx: Context<ManagePromotion>, promo_code_root: Option<[u8; 32]>) -> Result<()> {
+    set_promo_codes(ctx, promo_code_root)
+}
+
+pub fn handler_set_promotion_launch(
+    ctx: Context<ManagePromotion>,
+    start_timestamp: i64,
+    presale: Option<PresaleWindow>,
+) -> Result<()> {
+    set_promotion_launch(ctx, start_timestamp, presale)
+}
+
+pub fn handler_set_redemption_schedule(
+    ctx: Context<ManagePromotion>,
+    redemption_schedule: Option<RedemptionSchedule>,
+) -> Result<()> {
+    set_redemption_schedule(ctx, redemption_schedule)
+}
+
+pub fn handler_set_promotion_branches(ctx: Context<ManagePromotion>, allowed_branches: Vec<u32>) -> Result<()> {
+    set_promotion_branches(ctx, allowed_branches)
+}
+
+pub fn handler_close_promotion(ctx: Context<ClosePromotion>) -> Result<()> {
+    close_promotion(ctx)
+}
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{Rating, Promotion, UserStats, BadgeType,ReputationTier};
+use crate::state::{Rating, Promotion, PlatformConfig, UserStats, BadgeType,ReputationTier};
 use crate::errors::CouponError;
 use crate::events::PromotionRated;
 
@@ -16,6 +16,12 @@ pub struct RatePromotion<'info> {
     
     pub promotion: Account<'info, Promotion>,
     
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(
         init_if_needed,
         payer = user,
@@ -62,7 +68,7 @@ pub fn handler(ctx: Context<RatePromotion>, stars: u8) -> Result<()> {
         
         // Update user stats
         user_stats.total_ratings_given += 1;
-        user_stats.add_reputation(2); // 2 points per rating
+        user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_rating);
         
         // Check for TopReviewer badge at 20 ratings
         if user_stats.total_ratings_given == 20 && !user_stats.has_badge(BadgeType::TopReviewer as u8) {
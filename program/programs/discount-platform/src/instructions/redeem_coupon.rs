@@ -1,15 +1,24 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint, burn, Burn};
-use crate::state::{Coupon, Merchant, UserStats, BadgeType,ReputationTier};
+use anchor_spl::token_2022;
+use anchor_spl::token_interface::{
+    TokenInterface, TokenAccount, Mint, burn, Burn, token_metadata_update_field, TokenMetadataUpdateField,
+};
+use anchor_spl::token_interface::spl_token_metadata_interface::state::Field;
+use mpl_token_metadata::accounts::Metadata;
+use mpl_token_metadata::instructions::UpdateMetadataAccountV2CpiBuilder;
+use mpl_token_metadata::types::DataV2;
+use crate::state::{Coupon, CouponLock, CouponUse, LoyaltyCard, LoyaltyProgram, RedemptionBill, Merchant, MerchantStaff, MerchantBranch, Promotion, PlatformConfig, UserStats, BadgeType,ReputationTier};
 use crate::errors::CouponError;
-use crate::events::CouponRedeemed;
+use crate::events::{CouponRedeemed, CouponUsed};
+use super::loyalty::stamp_loyalty_card;
 
-pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
+pub fn handler(ctx: Context<RedeemCoupon>, bill: Option<RedemptionBill>) -> Result<()> {
     let coupon = &mut ctx.accounts.coupon;
     require!(!coupon.is_redeemed, CouponError::CouponAlreadyRedeemed);
     require!(coupon.expiry_timestamp > Clock::get()?.unix_timestamp, CouponError::CouponExpired);
     require!(coupon.owner == ctx.accounts.user.key(), CouponError::NotCouponOwner);
     require!(coupon.merchant == ctx.accounts.merchant.key(), CouponError::WrongMerchant);
+    require!(coupon.lock == CouponLock::None, CouponError::CouponLocked);
 
     let current_time = Clock::get()?.unix_timestamp;
 
@@ -31,7 +40,7 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
 
     // Update user stats for redemption
     user_stats.total_redemptions += 1;
-    user_stats.add_reputation(10); // 10 points per redemption
+    user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_redemption);
     user_stats.last_activity = current_time;
 
     // Check for badge eligibility
@@ -43,9 +52,61 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
         msg!("🏆 User eligible for FiftyRedemptions badge! Total redemptions: {}", user_stats.total_redemptions);
     }
 
-    // Mark as redeemed
-    coupon.is_redeemed = true;
-    coupon.redeemed_at = current_time;
+    let branch = ctx.accounts.branch.as_ref().map(|branch| branch.key());
+    let use_number = coupon.use_once(current_time);
+    record_coupon_use(
+        &mut ctx.accounts.coupon_use,
+        coupon,
+        ctx.accounts.merchant_authority.key(),
+        branch,
+        use_number,
+        current_time,
+        ctx.bumps.coupon_use,
+    );
+
+    stamp_loyalty_card(
+        ctx.accounts.loyalty_card.as_deref_mut(),
+        ctx.accounts.loyalty_program.as_deref(),
+        coupon,
+        current_time,
+    )?;
+
+    let discount_amount = record_savings(
+        coupon,
+        bill.as_ref(),
+        user_stats,
+        &mut ctx.accounts.merchant,
+        &mut ctx.accounts.promotion,
+    )?;
+
+    emit!(CouponUsed {
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        user: coupon.owner,
+        merchant: coupon.merchant,
+        use_number,
+        uses_remaining: coupon.uses_remaining,
+        purchase_amount: bill.map(|bill| bill.amount),
+        discount_amount,
+        branch,
+        timestamp: current_time,
+    });
+
+    if !coupon.is_redeemed {
+        update_uses_left(
+            coupon,
+            &ctx.accounts.merchant,
+            ctx.bumps.merchant,
+            ctx.accounts.nft_mint.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.metadata.as_ref().map(|metadata| metadata.to_account_info()),
+            ctx.accounts.token_metadata_program.as_ref().map(|program| program.to_account_info()),
+        )?;
+
+        msg!("Use {} of {} recorded | Reputation: {}", use_number, coupon.uses_total, user_stats.reputation_score);
+
+        return Ok(());
+    }
 
     // Update merchant stats
     let merchant = &mut ctx.accounts.merchant;
@@ -70,7 +131,11 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
         user: coupon.owner,
         merchant: coupon.merchant,
         discount_percentage: coupon.discount_percentage,
+        discount_kind: coupon.discount_kind,
         redemption_code: format!("REDEEMED-{}", coupon.id),
+        purchase_amount: bill.map(|bill| bill.amount),
+        discount_amount,
+        branch,
         timestamp: coupon.redeemed_at,
     });
 
@@ -80,32 +145,172 @@ pub fn handler(ctx: Context<RedeemCoupon>) -> Result<()> {
     Ok(())
 }
 
+// ============================================================================
+// Shared with verify_and_redeem_ticket
+// ============================================================================
+
+pub fn record_coupon_use(
+    coupon_use: &mut Account<CouponUse>,
+    coupon: &Account<Coupon>,
+    redeemed_by: Pubkey,
+    branch: Option<Pubkey>,
+    use_number: u16,
+    current_time: i64,
+    bump: u8,
+) {
+    coupon_use.coupon = coupon.key();
+    coupon_use.user = coupon.owner;
+    coupon_use.merchant = coupon.merchant;
+    coupon_use.redeemed_by = redeemed_by;
+    coupon_use.branch = branch;
+    coupon_use.use_number = use_number;
+    coupon_use.used_at = current_time;
+    coupon_use.bump = bump;
+}
+
+// Discount the coupon gives on the merchant-reported bill, added to the
+// customer's savings and the merchant's and promotion's totals. 0 without a bill.
+pub fn record_savings(
+    coupon: &Coupon,
+    bill: Option<&RedemptionBill>,
+    user_stats: &mut UserStats,
+    merchant: &mut Merchant,
+    promotion: &mut Promotion,
+) -> Result<u64> {
+    let Some(bill) = bill else {
+        return Ok(0);
+    };
+    let discount_amount = coupon
+        .discount_kind
+        .discount_on(bill)
+        .ok_or(error!(CouponError::BelowMinimumSpend))?;
+
+    user_stats.total_savings = user_stats.total_savings.saturating_add(discount_amount);
+    merchant.total_discount_given = merchant.total_discount_given.saturating_add(discount_amount);
+    promotion.total_discount_given = promotion.total_discount_given.saturating_add(discount_amount);
+
+    Ok(discount_amount)
+}
+
+// Renames a multi-use coupon's NFT to show the uses left, signed by the merchant
+// PDA that holds its update authority. Token-2022 coupons keep their metadata
+// in the mint; Metaplex ones need the metadata account and program.
+pub fn update_uses_left<'info>(
+    coupon: &Coupon,
+    merchant: &Account<'info, Merchant>,
+    merchant_bump: u8,
+    nft_mint: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    metadata: Option<AccountInfo<'info>>,
+    token_metadata_program: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let merchant_seeds: &[&[u8]] = &[b"merchant", merchant.authority.as_ref(), &[merchant_bump]];
+
+    if *nft_mint.owner == token_2022::ID {
+        // Names only get shorter as uses run out, so the mint never needs a top-up
+        return token_metadata_update_field(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TokenMetadataUpdateField {
+                    program_id: token_program,
+                    metadata: nft_mint,
+                    update_authority: merchant.to_account_info(),
+                },
+                &[merchant_seeds],
+            ),
+            Field::Name,
+            coupon.metadata_name(),
+        );
+    }
+
+    let (Some(metadata), Some(token_metadata_program)) = (metadata, token_metadata_program) else {
+        return err!(CouponError::MetadataRequired);
+    };
+    let current = Metadata::try_from(&metadata).map_err(|_| error!(CouponError::MetadataRequired))?;
+    require_keys_eq!(current.mint, nft_mint.key(), CouponError::WrongCoupon);
+
+    UpdateMetadataAccountV2CpiBuilder::new(&token_metadata_program)
+        .metadata(&metadata)
+        .update_authority(&merchant.to_account_info())
+        .data(DataV2 {
+            name: coupon.metadata_name(),
+            symbol: current.symbol.trim_end_matches('\0').to_string(),
+            uri: current.uri.trim_end_matches('\0').to_string(),
+            seller_fee_basis_points: current.seller_fee_basis_points,
+            creators: current.creators,
+            collection: current.collection,
+            uses: current.uses,
+        })
+        .invoke_signed(&[merchant_seeds])?;
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct RedeemCoupon<'info> {
     #[account(
         mut,
         constraint = coupon.owner == user.key() @ CouponError::NotCouponOwner,
-        constraint = coupon.merchant == merchant.key() @ CouponError::WrongMerchant
+        constraint = coupon.merchant == merchant.key() @ CouponError::WrongMerchant,
+        has_one = promotion @ CouponError::WrongCoupon
     )]
     pub coupon: Account<'info, Coupon>,
+
+    #[account(
+        mut,
+        constraint = promotion.allows_branch(branch.as_ref().map(|branch| branch.index)) @ CouponError::BranchNotAllowed,
+        constraint = promotion.is_redeemable_at(Clock::get()?.unix_timestamp) @ CouponError::OutsideRedemptionWindow
+    )]
+    pub promotion: Box<Account<'info, Promotion>>,
+
+    // Store the coupon is redeemed at; required when the promotion limits branches
+    #[account(
+        constraint = branch.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = branch.is_active @ CouponError::BranchInactive
+    )]
+    pub branch: Option<Account<'info, MerchantBranch>>,
     
     /// CHECK: SPL Token Mint
-    #[account(mut)]
-    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = coupon.mint == Some(nft_mint.key()) @ CouponError::WrongCoupon
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
     
     /// CHECK: SPL Token Account
     #[account(
         mut,
         constraint = token_account.mint == nft_mint.key(),
-        constraint = token_account.owner == user.key()
+        constraint = token_account.owner == user.key(),
+        constraint = token_account.amount == 1 @ CouponError::NotCouponOwner
     )]
-    pub token_account: Account<'info, TokenAccount>,
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
-        constraint = merchant.authority == merchant_authority.key() @ CouponError::NotMerchantAuthority
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump,
+        constraint = merchant.is_authorized_for(
+            &merchant_authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_REDEEM,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
     )]
     pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `merchant_authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), merchant_authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
     
     #[account(
         init_if_needed,
@@ -116,9 +321,43 @@ pub struct RedeemCoupon<'info> {
     )]
     pub user_stats: Account<'info, UserStats>,
     
+    #[account(
+        init,
+        payer = user,
+        space = 8 + CouponUse::INIT_SPACE,
+        seeds = [b"coupon_use", coupon.key().as_ref(), &coupon.uses_remaining.to_le_bytes()],
+        bump
+    )]
+    pub coupon_use: Box<Account<'info, CouponUse>>,
+
+    // Customer's stamp card at this merchant; the coupon's last use adds a stamp
+    #[account(
+        mut,
+        seeds = [b"loyalty_card", merchant.key().as_ref(), user.key().as_ref()],
+        bump = loyalty_card.bump
+    )]
+    pub loyalty_card: Option<Box<Account<'info, LoyaltyCard>>>,
+
+    // The merchant's stamp program; required with loyalty_card
+    #[account(
+        seeds = [b"loyalty_program", merchant.key().as_ref()],
+        bump = loyalty_program.bump
+    )]
+    pub loyalty_program: Option<Box<Account<'info, LoyaltyProgram>>>,
+
+    // Metaplex metadata of the NFT; needed to rename multi-use coupons until
+    // their last use
+    /// CHECK: Checked against nft_mint in update_uses_left
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    
     #[account(mut)]
     pub user: Signer<'info>,
     pub merchant_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -1,17 +1,29 @@
 // src/instructions/redemption_tickets.rs
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint, burn, Burn, close_account, CloseAccount};
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint, burn, Burn, close_account, CloseAccount};
 use crate::state::{
     Coupon, 
+    CouponLock,
+    CouponUse,
+    LoyaltyCard,
+    LoyaltyProgram,
+    RedemptionBill,
     Merchant, 
+    MerchantStaff,
+    MerchantBranch,
+    Promotion,
+    PlatformConfig,
     UserStats, 
     RedemptionTicket, 
     RedemptionLocation,
+    Location,
     BadgeType,
     ReputationTier,
 };
 use crate::errors::CouponError;
-use crate::events::{TicketGenerated, TicketRedeemed, CouponRedeemed};
+use crate::events::{TicketGenerated, TicketRedeemed, CouponRedeemed, CouponUsed};
+use super::redeem_coupon::{record_coupon_use, record_savings, update_uses_left};
+use super::loyalty::stamp_loyalty_card;
 
 // ============================================================================
 // Generate Redemption Ticket (User Side)
@@ -37,10 +49,27 @@ pub struct GenerateRedemptionTicket<'info> {
     #[account(
         constraint = coupon.owner == user.key() @ CouponError::NotCouponOwner,
         constraint = !coupon.is_redeemed @ CouponError::CouponAlreadyRedeemed,
-        constraint = coupon.expiry_timestamp > Clock::get()?.unix_timestamp @ CouponError::CouponExpired
+        constraint = coupon.expiry_timestamp > Clock::get()?.unix_timestamp @ CouponError::CouponExpired,
+        has_one = promotion @ CouponError::WrongCoupon
     )]
     pub coupon: Account<'info, Coupon>,
     
+    pub promotion: Box<Account<'info, Promotion>>,
+    
+    // Store the user is at; the geofence is measured from it instead of the promotion
+    #[account(
+        constraint = branch.merchant == coupon.merchant @ CouponError::WrongMerchant,
+        constraint = branch.is_active @ CouponError::BranchInactive,
+        constraint = promotion.allows_branch(Some(branch.index)) @ CouponError::BranchNotAllowed
+    )]
+    pub branch: Option<Account<'info, MerchantBranch>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -68,20 +97,38 @@ pub fn generate_redemption_ticket(
     ticket.merchant = coupon.merchant;
     ticket.ticket_hash = ticket_hash;
     ticket.created_at = clock.unix_timestamp;
-    ticket.expires_at = clock.unix_timestamp + 300; // 5 minutes
+    ticket.expires_at = clock.unix_timestamp + ctx.accounts.platform_config.settings.ticket_expiry_seconds;
     ticket.is_consumed = false;
     ticket.nonce = nonce;
+    ticket.redeemed_at_branch = None;
     
     // Optional: Record location where ticket was generated
+    let mut reported_location = None;
     if let (Some(lat), Some(lon)) = (latitude, longitude) {
-        require!(lat >= -90.0 && lat <= 90.0, CouponError::InvalidCoordinates);
-        require!(lon >= -180.0 && lon <= 180.0, CouponError::InvalidCoordinates);
-        
+        let location = Location::checked_from_coords(lat, lon)
+            .ok_or(CouponError::InvalidCoordinates)?;
         ticket.redemption_location = Some(RedemptionLocation {
-            latitude: (lat * 1_000_000.0) as i32,
-            longitude: (lon * 1_000_000.0) as i32,
+            latitude: location.latitude,
+            longitude: location.longitude,
             timestamp: clock.unix_timestamp,
         });
+        reported_location = Some(location);
+    }
+    
+    // Geo-fenced promotions only issue tickets on site
+    let promotion = &ctx.accounts.promotion;
+    if promotion.is_location_based {
+        let reported_location = reported_location.ok_or(CouponError::LocationRequired)?;
+        let center = match &ctx.accounts.branch {
+            Some(branch) => &branch.location,
+            None => &promotion.location,
+        };
+        #[cfg(feature = "bench")]
+        anchor_lang::solana_program::log::sol_log_compute_units();
+        let within_geofence = promotion.is_within_geofence(center, &reported_location);
+        #[cfg(feature = "bench")]
+        anchor_lang::solana_program::log::sol_log_compute_units();
+        require!(within_geofence, CouponError::LocationNotSupported);
     }
     
     emit!(TicketGenerated {
@@ -118,30 +165,68 @@ pub struct VerifyAndRedeemTicket<'info> {
         mut,
         constraint = coupon.key() == ticket.coupon @ CouponError::WrongCoupon,
         constraint = !coupon.is_redeemed @ CouponError::CouponAlreadyRedeemed,
-        constraint = coupon.merchant == merchant.key() @ CouponError::WrongMerchant
+        constraint = coupon.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = coupon.lock == CouponLock::None @ CouponError::CouponLocked,
+        constraint = coupon.owner == ticket.user @ CouponError::NotCouponOwner,
+        has_one = promotion @ CouponError::WrongCoupon
     )]
     pub coupon: Account<'info, Coupon>,
+
+    #[account(
+        mut,
+        constraint = promotion.allows_branch(branch.as_ref().map(|branch| branch.index)) @ CouponError::BranchNotAllowed,
+        constraint = promotion.is_redeemable_at(Clock::get()?.unix_timestamp) @ CouponError::OutsideRedemptionWindow
+    )]
+    pub promotion: Box<Account<'info, Promotion>>,
+    
+    // Store the coupon is redeemed at; required when the promotion limits branches
+    #[account(
+        constraint = branch.merchant == merchant.key() @ CouponError::WrongMerchant,
+        constraint = branch.is_active @ CouponError::BranchInactive
+    )]
+    pub branch: Option<Account<'info, MerchantBranch>>,
     
     /// CHECK: NFT Mint
     #[account(
         mut,
-        constraint = nft_mint.key() == coupon.mint.unwrap() @ CouponError::WrongCoupon
+        constraint = coupon.mint == Some(nft_mint.key()) @ CouponError::WrongCoupon
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
     
     /// CHECK: Token account holding the NFT
     #[account(
         mut,
         constraint = token_account.mint == nft_mint.key(),
-        constraint = token_account.owner == ticket.user
+        constraint = token_account.owner == ticket.user,
+        constraint = token_account.amount == 1 @ CouponError::NotCouponOwner
     )]
-    pub token_account: Account<'info, TokenAccount>,
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
-        constraint = merchant.authority == merchant_authority.key() @ CouponError::NotMerchantAuthority
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump,
+        constraint = merchant.is_authorized_for(
+            &merchant_authority.key(),
+            merchant_staff.as_deref(),
+            MerchantStaff::ROLE_REDEEM,
+            Clock::get()?.unix_timestamp
+        ) @ CouponError::NotMerchantAuthority
     )]
     pub merchant: Account<'info, Merchant>,
+
+    // Staff grant for `merchant_authority`; omit when the merchant authority signs
+    #[account(
+        seeds = [b"merchant_staff", merchant.key().as_ref(), merchant_authority.key().as_ref()],
+        bump = merchant_staff.bump
+    )]
+    pub merchant_staff: Option<Account<'info, MerchantStaff>>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
     
     #[account(
         mut,
@@ -150,6 +235,40 @@ pub struct VerifyAndRedeemTicket<'info> {
     )]
     pub user_stats: Account<'info, UserStats>,
     
+    #[account(
+        init,
+        payer = user,
+        space = 8 + CouponUse::INIT_SPACE,
+        seeds = [b"coupon_use", coupon.key().as_ref(), &coupon.uses_remaining.to_le_bytes()],
+        bump
+    )]
+    pub coupon_use: Box<Account<'info, CouponUse>>,
+
+    // Customer's stamp card at this merchant; the coupon's last use adds a stamp
+    #[account(
+        mut,
+        seeds = [b"loyalty_card", merchant.key().as_ref(), ticket.user.as_ref()],
+        bump = loyalty_card.bump
+    )]
+    pub loyalty_card: Option<Box<Account<'info, LoyaltyCard>>>,
+
+    // The merchant's stamp program; required with loyalty_card
+    #[account(
+        seeds = [b"loyalty_program", merchant.key().as_ref()],
+        bump = loyalty_program.bump
+    )]
+    pub loyalty_program: Option<Box<Account<'info, LoyaltyProgram>>>,
+
+    // Metaplex metadata of the NFT; needed to rename multi-use coupons until
+    // their last use
+    /// CHECK: Checked against nft_mint in update_uses_left
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    
     // CHANGED: User must be a Signer to authorize NFT burning
     #[account(
         mut,
@@ -158,13 +277,14 @@ pub struct VerifyAndRedeemTicket<'info> {
     pub user: Signer<'info>,
     
     pub merchant_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn verify_and_redeem_ticket(
     ctx: Context<VerifyAndRedeemTicket>,
     expected_hash: [u8; 32],
+    bill: Option<RedemptionBill>,
 ) -> Result<()> {
     let ticket = &mut ctx.accounts.ticket;
     let coupon = &mut ctx.accounts.coupon;
@@ -195,19 +315,24 @@ pub fn verify_and_redeem_ticket(
     
     // Mark ticket as consumed (prevents double-redemption)
     ticket.is_consumed = true;
-    
-    // Mark coupon as redeemed
-    coupon.is_redeemed = true;
-    coupon.redeemed_at = clock.unix_timestamp;
-    
-    // Update merchant stats
-    let merchant = &mut ctx.accounts.merchant;
-    merchant.total_coupons_redeemed += 1;
+    ticket.redeemed_at_branch = ctx.accounts.branch.as_ref().map(|branch| branch.key());
+    
+    // Spend one use; the last one marks the coupon redeemed
+    let use_number = coupon.use_once(clock.unix_timestamp);
+    record_coupon_use(
+        &mut ctx.accounts.coupon_use,
+        coupon,
+        ctx.accounts.merchant_authority.key(),
+        ticket.redeemed_at_branch,
+        use_number,
+        clock.unix_timestamp,
+        ctx.bumps.coupon_use,
+    );
     
     // Update user stats
     let user_stats = &mut ctx.accounts.user_stats;
     user_stats.total_redemptions += 1;
-    user_stats.add_reputation(10);
+    user_stats.add_reputation(ctx.accounts.platform_config.settings.reputation_per_redemption);
     user_stats.last_activity = clock.unix_timestamp;
     
     // Check for badge eligibility
@@ -218,6 +343,67 @@ pub fn verify_and_redeem_ticket(
     if user_stats.total_redemptions == 50 && !user_stats.has_badge(BadgeType::FiftyRedemptions as u8) {
         msg!("🏆 User eligible for FiftyRedemptions badge!");
     }
+
+    let discount_amount = record_savings(
+        coupon,
+        bill.as_ref(),
+        user_stats,
+        &mut ctx.accounts.merchant,
+        &mut ctx.accounts.promotion,
+    )?;
+    
+    emit!(TicketRedeemed {
+        ticket: ticket.key(),
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        user: ticket.user,
+        merchant: ctx.accounts.merchant.key(),
+        discount_kind: coupon.discount_kind,
+        purchase_amount: bill.map(|bill| bill.amount),
+        discount_amount,
+        branch: ticket.redeemed_at_branch,
+        redeemed_at: clock.unix_timestamp,
+    });
+
+    stamp_loyalty_card(
+        ctx.accounts.loyalty_card.as_deref_mut(),
+        ctx.accounts.loyalty_program.as_deref(),
+        coupon,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(CouponUsed {
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        user: ticket.user,
+        merchant: ctx.accounts.merchant.key(),
+        use_number,
+        uses_remaining: coupon.uses_remaining,
+        purchase_amount: bill.map(|bill| bill.amount),
+        discount_amount,
+        branch: ticket.redeemed_at_branch,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if !coupon.is_redeemed {
+        update_uses_left(
+            coupon,
+            &ctx.accounts.merchant,
+            ctx.bumps.merchant,
+            ctx.accounts.nft_mint.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.metadata.as_ref().map(|metadata| metadata.to_account_info()),
+            ctx.accounts.token_metadata_program.as_ref().map(|program| program.to_account_info()),
+        )?;
+
+        msg!("✓ Use {} of {} redeemed via ticket", use_number, coupon.uses_total);
+
+        return Ok(());
+    }
+
+    // Update merchant stats
+    let merchant = &mut ctx.accounts.merchant;
+    merchant.total_coupons_redeemed += 1;
     
     // Burn the NFT (permanent on-chain proof)
     let cpi_accounts = Burn {
@@ -245,22 +431,17 @@ pub fn verify_and_redeem_ticket(
     
     close_account(close_ctx)?;
     
-    emit!(TicketRedeemed {
-        ticket: ticket.key(),
-        coupon: coupon.key(),
-        nft_mint: ctx.accounts.nft_mint.key(),
-        user: ticket.user,
-        merchant: merchant.key(),
-        redeemed_at: clock.unix_timestamp,
-    });
-    
     emit!(CouponRedeemed {
         coupon: coupon.key(),
         nft_mint: ctx.accounts.nft_mint.key(),
         user: ticket.user,
         merchant: merchant.key(),
         discount_percentage: coupon.discount_percentage,
+        discount_kind: coupon.discount_kind,
         redemption_code: format!("TICKET-{}", ticket.nonce),
+        purchase_amount: bill.map(|bill| bill.amount),
+        discount_amount,
+        branch: ticket.redeemed_at_branch,
         timestamp: clock.unix_timestamp,
     });
     
@@ -312,8 +493,9 @@ pub fn handler_generate_ticket(
 pub fn handler_verify_redeem_ticket(
     ctx: Context<VerifyAndRedeemTicket>,
     expected_hash: [u8; 32],
+    bill: Option<RedemptionBill>,
 ) -> Result<()> {
-    verify_and_redeem_ticket(ctx, expected_hash)
+    verify_and_redeem_ticket(ctx, expected_hash, bill)
 }
 
 pub fn handler_cancel_ticket(ctx: Context<CancelRedemptionTicket>) -> Result<()> {
@@ -19,15 +19,16 @@ pub fn handler(
     merchant.category = category.clone();
     merchant.total_coupons_created = 0;
     merchant.total_coupons_redeemed = 0;
+    merchant.total_discount_given = 0;
     merchant.is_active = true;
     merchant.created_at = Clock::get()?.unix_timestamp;
+    merchant.royalty_basis_points = 0;
+    merchant.branch_count = 0;
     
     // Set location
     if let (Some(lat), Some(lon)) = (latitude, longitude) {
-        require!(lat >= -90.0 && lat <= 90.0, CouponError::InvalidCoordinates);
-        require!(lon >= -180.0 && lon <= 180.0, CouponError::InvalidCoordinates);
-        
-        merchant.location = Location::from_coords(lat, lon);
+        merchant.location = Location::checked_from_coords(lat, lon)
+            .ok_or(CouponError::InvalidCoordinates)?;
         merchant.has_physical_location = true;
     } else {
         merchant.location = Location {
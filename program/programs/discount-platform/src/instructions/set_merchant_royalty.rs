@@ -0,0 +1,46 @@
+// src/instructions/set_merchant_royalty.rs
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, PlatformConfig};
+use crate::errors::CouponError;
+use crate::events::MerchantRoyaltyUpdated;
+
+#[derive(Accounts)]
+pub struct SetMerchantRoyalty<'info> {
+    // Owner only: the royalty sets the merchant's terms on every resale, so no
+    // staff role covers it
+    #[account(
+        mut,
+        constraint = merchant.is_authorized(&authority.key()) @ CouponError::NotMerchantAuthority
+    )]
+    pub merchant: Account<'info, Merchant>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetMerchantRoyalty>, royalty_basis_points: u16) -> Result<()> {
+    require!(
+        royalty_basis_points <= ctx.accounts.platform_config.settings.max_royalty_basis_points,
+        CouponError::RoyaltyTooHigh
+    );
+
+    let merchant = &mut ctx.accounts.merchant;
+    let old_royalty_basis_points = merchant.royalty_basis_points;
+    merchant.royalty_basis_points = royalty_basis_points;
+
+    emit!(MerchantRoyaltyUpdated {
+        merchant: merchant.key(),
+        old_royalty_basis_points,
+        new_royalty_basis_points: royalty_basis_points,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("✅ Merchant royalty: {} -> {} bps", old_royalty_basis_points, royalty_basis_points);
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint, transfer, Transfer};
-use crate::state::{Coupon, StakingPool, StakeAccount};
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+use crate::state::{Coupon, CouponLock, Marketplace, StakingPool, StakeAccount};
 use crate::errors::CouponError;
 use crate::events::RewardsStaked;
+use super::escrow::NftEscrow;
 
 #[derive(Accounts)]
 pub struct StakeCoupon<'info> {
@@ -22,18 +23,26 @@ pub struct StakeCoupon<'info> {
     )]
     pub staking_pool: Account<'info, StakingPool>,
 
+    #[account(
+        seeds = [b"marketplace"],
+        bump,
+        constraint = !marketplace.paused @ CouponError::MarketplacePaused
+    )]
+    pub marketplace: Account<'info, Marketplace>,
+
     #[account(
         mut,
         constraint = coupon.owner == user.key() @ CouponError::NotCouponOwner,
-        constraint = !coupon.is_redeemed @ CouponError::CouponAlreadyRedeemed
+        constraint = !coupon.is_redeemed @ CouponError::CouponAlreadyRedeemed,
+        constraint = coupon.lock == CouponLock::None @ CouponError::CouponLocked
     )]
     pub coupon: Account<'info, Coupon>,
 
     /// CHECK: NFT Mint
     #[account(
-        constraint = nft_mint.key() == coupon.mint.unwrap() @ CouponError::WrongCoupon
+        constraint = coupon.mint == Some(nft_mint.key()) @ CouponError::WrongCoupon
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: User's token account
     #[account(
@@ -41,7 +50,7 @@ pub struct StakeCoupon<'info> {
         constraint = user_token_account.mint == nft_mint.key(),
         constraint = user_token_account.owner == user.key()
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// CHECK: Staking vault token account (PDA)
     #[account(
@@ -49,11 +58,11 @@ pub struct StakeCoupon<'info> {
         seeds = [b"stake_vault", nft_mint.key().as_ref()],
         bump
     )]
-    pub stake_vault: Account<'info, TokenAccount>,
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub user: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -63,7 +72,7 @@ pub fn handler(
 ) -> Result<()> {
     let staking_pool = &mut ctx.accounts.staking_pool;
     let stake_account = &mut ctx.accounts.stake_account;
-    let coupon = &ctx.accounts.coupon;
+    let coupon = &mut ctx.accounts.coupon;
     let current_time = Clock::get()?.unix_timestamp;
 
     require!(staking_pool.is_active, CouponError::PromotionInactive);
@@ -75,15 +84,16 @@ pub fn handler(
         CouponError::InvalidExpiry
     );
 
-    // Transfer NFT to staking vault
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.stake_vault.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    transfer(cpi_ctx, 1)?;
+    // Transfer NFT to staking vault; hooked coupons are deposited by the user's own transfer
+    NftEscrow {
+        mint: &ctx.accounts.nft_mint,
+        token_program: &ctx.accounts.token_program,
+    }
+    .deposit(
+        ctx.accounts.user_token_account.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+    )?;
 
     // Initialize stake account
     stake_account.user = ctx.accounts.user.key();
@@ -96,6 +106,7 @@ pub fn handler(
     stake_account.rewards_earned = 0;
     stake_account.is_active = true;
     stake_account.claimed_at = None;
+    coupon.lock = CouponLock::Staked;
 
     // Update pool stats
     staking_pool.total_staked += stake_account.amount_staked;
@@ -1,13 +1,41 @@
 use anchor_lang::prelude::*;
-use crate::state::Coupon;
+use anchor_spl::token_2022;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint, transfer_checked, TransferChecked};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::{Coupon, CouponLock};
 use crate::errors::CouponError;
 use crate::events::CouponTransferred;
 
+// Group coupons (`mint == None`) have no token to move, so they are not
+// transferable and stay with the participant they were minted for. For hooked
+// coupons this only checks the coupon and creates the recipient's token
+// account; the client's Token-2022 transfer that follows moves the NFT and the
+// hook updates Coupon.owner (see escrow.rs for why this can't CPI).
 pub fn handler(ctx: Context<TransferCoupon>) -> Result<()> {
     let coupon = &mut ctx.accounts.coupon;
     require!(!coupon.is_redeemed, CouponError::CouponAlreadyRedeemed);
     require!(coupon.expiry_timestamp > Clock::get()?.unix_timestamp, CouponError::CouponExpired);
     require!(coupon.owner == ctx.accounts.from_authority.key(), CouponError::NotCouponOwner);
+    require!(coupon.lock == CouponLock::None, CouponError::CouponLocked);
+
+    if *ctx.accounts.nft_mint.to_account_info().owner == token_2022::ID {
+        return Ok(());
+    }
+
+    // Move the NFT itself so the token holder and Coupon.owner stay in sync
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.from_token_account.to_account_info(),
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                to: ctx.accounts.to_token_account.to_account_info(),
+                authority: ctx.accounts.from_authority.to_account_info(),
+            },
+        ),
+        1,
+        ctx.accounts.nft_mint.decimals,
+    )?;
 
     let old_owner = coupon.owner;
     
@@ -16,7 +44,7 @@ pub fn handler(ctx: Context<TransferCoupon>) -> Result<()> {
 
     emit!(CouponTransferred {
         coupon: coupon.key(),
-        nft_mint: coupon.mint.unwrap_or(Pubkey::default()),
+        nft_mint: ctx.accounts.nft_mint.key(),
         from: old_owner,
         to: coupon.owner,
         timestamp: Clock::get()?.unix_timestamp,
@@ -29,10 +57,40 @@ pub fn handler(ctx: Context<TransferCoupon>) -> Result<()> {
 pub struct TransferCoupon<'info> {
     #[account(
         mut,
-        constraint = coupon.owner == from_authority.key() @ CouponError::NotCouponOwner
+        constraint = coupon.owner == from_authority.key() @ CouponError::NotCouponOwner,
+        constraint = coupon.mint.is_some() @ CouponError::CouponNotTokenized
     )]
     pub coupon: Account<'info, Coupon>,
+
+    /// CHECK: NFT Mint
+    #[account(
+        constraint = coupon.mint == Some(nft_mint.key()) @ CouponError::WrongCoupon
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Sender's token account holding the NFT
+    #[account(
+        mut,
+        constraint = from_token_account.mint == nft_mint.key() @ CouponError::WrongCoupon,
+        constraint = from_token_account.owner == from_authority.key() @ CouponError::NotCouponOwner,
+        constraint = from_token_account.amount == 1 @ CouponError::NotCouponOwner
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = from_authority,
+        associated_token::mint = nft_mint,
+        associated_token::authority = new_owner,
+        associated_token::token_program = token_program
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+
     /// CHECK: This is the new owner
     pub new_owner: UncheckedAccount<'info>,
+    #[account(mut)]
     pub from_authority: Signer<'info>,
-}
\ No newline at end of file
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
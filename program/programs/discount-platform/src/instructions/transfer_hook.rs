@@ -0,0 +1,89 @@
+// src/instructions/transfer_hook.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Account as SplTokenAccount;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{Coupon, CouponLock};
+use crate::errors::CouponError;
+use crate::events::CouponTransferred;
+
+// Accounts follow the spl-transfer-hook-interface Execute layout; `coupon` is
+// resolved from the ExtraAccountMetaList written in mint_hooked_coupon.
+#[derive(Accounts)]
+pub struct CouponTransferHook<'info> {
+    #[account(token::mint = mint)]
+    pub source_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Source token account owner or delegate, checked by Token-2022
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: ExtraAccountMetaList for this mint
+    #[account(
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = coupon.mint == Some(mint.key()) @ CouponError::WrongCoupon
+    )]
+    pub coupon: Account<'info, Coupon>,
+}
+
+pub fn handler(ctx: Context<CouponTransferHook>, _amount: u64) -> Result<()> {
+    // Only Token-2022 sets the transferring flag, so this rejects direct calls
+    let source_info = ctx.accounts.source_token.to_account_info();
+    let source_data = source_info.try_borrow_data()?;
+    let source_account = StateWithExtensions::<SplTokenAccount>::unpack(&source_data)?;
+    let hook_account = source_account.get_extension::<TransferHookAccount>()?;
+    require!(bool::from(hook_account.transferring), CouponError::NotTransferring);
+
+    let coupon = &mut ctx.accounts.coupon;
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(!coupon.is_redeemed, CouponError::CouponAlreadyRedeemed);
+    require!(coupon.expiry_timestamp > current_time, CouponError::CouponExpired);
+
+    // A locked coupon may only go into the vault it was locked for (see
+    // escrow.rs). It still belongs to Coupon.owner while it sits there.
+    if coupon.lock != CouponLock::None {
+        let vault = escrow_vault(coupon.key(), coupon.lock, ctx.accounts.mint.key());
+        require!(
+            vault == Some(ctx.accounts.destination_token.key()),
+            CouponError::CouponLocked
+        );
+        return Ok(());
+    }
+
+    let old_owner = coupon.owner;
+    coupon.owner = ctx.accounts.destination_token.owner;
+
+    emit!(CouponTransferred {
+        coupon: coupon.key(),
+        nft_mint: ctx.accounts.mint.key(),
+        from: old_owner,
+        to: coupon.owner,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+// Vault a coupon under `lock` is escrowed in; auctions leave it in place
+fn escrow_vault(coupon: Pubkey, lock: CouponLock, mint: Pubkey) -> Option<Pubkey> {
+    match lock {
+        CouponLock::Listed => {
+            let (listing, _) = Pubkey::find_program_address(&[b"listing", coupon.as_ref()], &crate::ID);
+            Some(Pubkey::find_program_address(&[b"listing_vault", listing.as_ref()], &crate::ID).0)
+        }
+        CouponLock::Staked => Some(Pubkey::find_program_address(&[b"stake_vault", mint.as_ref()], &crate::ID).0),
+        CouponLock::Auctioned | CouponLock::None => None,
+    }
+}
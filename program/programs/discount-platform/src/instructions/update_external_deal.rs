@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{ExternalDeal, DealSource};
+use crate::state::{ExternalDeal, DealSource, PlatformConfig};
 use crate::errors::CouponError;
 use crate::events::ExternalDealUpdated;
 
@@ -15,6 +15,12 @@ pub struct UpdateExternalDeal<'info> {
     )]
     pub external_deal: Account<'info, ExternalDeal>,
     
+    #[account(
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -72,7 +78,7 @@ pub fn handler(
     } else {
         // For existing deals, check update interval (1 hour minimum)
         require!(
-            current_time >= deal.last_updated + 3600,
+            current_time >= deal.last_updated + ctx.accounts.platform_config.settings.external_deal_update_interval,
             CouponError::InvalidExpiry
         );
         deal.verification_count += 1;
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::{Marketplace, PlatformConfig, PlatformSettings};
+use crate::errors::CouponError;
+use crate::events::PlatformConfigUpdated;
+
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        seeds = [b"marketplace"],
+        bump,
+        has_one = authority @ CouponError::NotMarketplaceAuthority
+    )]
+    pub marketplace: Account<'info, Marketplace>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UpdatePlatformConfig>, settings: PlatformSettings) -> Result<()> {
+    require!(settings.is_valid(), CouponError::InvalidConfig);
+
+    let platform_config = &mut ctx.accounts.platform_config;
+    platform_config.settings = settings;
+    platform_config.version += 1;
+    platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(PlatformConfigUpdated {
+        platform_config: platform_config.key(),
+        version: platform_config.version,
+        settings,
+        timestamp: platform_config.updated_at,
+    });
+
+    msg!("✅ Platform config updated to version {}", platform_config.version);
+
+    Ok(())
+}
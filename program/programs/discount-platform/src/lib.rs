@@ -5,9 +5,16 @@ pub mod state;
 pub mod instructions;
 pub mod errors;
 pub mod events;
+pub mod allow_list;
+pub mod promo_codes;
+#[cfg(test)]
+mod test_fixtures;
 
 use instructions::*;
-use state::{BadgeType, DiscountTier, AuctionType};
+use allow_list::AllowListProof;
+use state::{BadgeType, DiscountTier, AuctionType, PlatformSettings, PresaleWindow, RedemptionSchedule, MintVoucher, DiscountKind, RedemptionBill};
+use spl_discriminator::SplDiscriminate;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
 declare_id!("9P3wW4XQH7DntMqfEiLqS6SNztihxfenNUSqECh3WTf3");
 
@@ -19,6 +26,32 @@ pub mod discount_platform {
         instructions::initialize::handler(ctx)
     }
 
+    pub fn set_marketplace_fee(ctx: Context<MarketplaceAdmin>, fee_basis_points: u16) -> Result<()> {
+        instructions::marketplace_admin::handler_set_marketplace_fee(ctx, fee_basis_points)
+    }
+
+    pub fn propose_marketplace_authority(
+        ctx: Context<MarketplaceAdmin>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::marketplace_admin::handler_propose_marketplace_authority(ctx, new_authority)
+    }
+
+    pub fn accept_marketplace_authority(ctx: Context<AcceptMarketplaceAuthority>) -> Result<()> {
+        instructions::marketplace_admin::handler_accept_marketplace_authority(ctx)
+    }
+
+    pub fn set_marketplace_paused(ctx: Context<MarketplaceAdmin>, paused: bool) -> Result<()> {
+        instructions::marketplace_admin::handler_set_marketplace_paused(ctx, paused)
+    }
+
+    pub fn update_platform_config(
+        ctx: Context<UpdatePlatformConfig>,
+        settings: PlatformSettings,
+    ) -> Result<()> {
+        instructions::update_platform_config::handler(ctx, settings)
+    }
+
     pub fn register_merchant(
         ctx: Context<RegisterMerchant>,
         name: String,
@@ -29,6 +62,42 @@ pub mod discount_platform {
         instructions::register_merchant::handler(ctx, name, category, latitude, longitude)
     }
 
+    pub fn set_merchant_royalty(ctx: Context<SetMerchantRoyalty>, royalty_basis_points: u16) -> Result<()> {
+        instructions::set_merchant_royalty::handler(ctx, royalty_basis_points)
+    }
+
+    pub fn add_merchant_staff(
+        ctx: Context<AddMerchantStaff>,
+        staff: Pubkey,
+        roles: u8,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        instructions::merchant_staff::handler_add_merchant_staff(ctx, staff, roles, expires_at)
+    }
+
+    pub fn revoke_merchant_staff(ctx: Context<RevokeMerchantStaff>) -> Result<()> {
+        instructions::merchant_staff::handler_revoke_merchant_staff(ctx)
+    }
+
+    pub fn add_merchant_branch(
+        ctx: Context<AddMerchantBranch>,
+        name: String,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<()> {
+        instructions::merchant_branches::handler_add_merchant_branch(ctx, name, latitude, longitude)
+    }
+
+    pub fn update_merchant_branch(
+        ctx: Context<UpdateMerchantBranch>,
+        name: String,
+        latitude: f64,
+        longitude: f64,
+        is_active: bool,
+    ) -> Result<()> {
+        instructions::merchant_branches::handler_update_merchant_branch(ctx, name, latitude, longitude, is_active)
+    }
+
     pub fn create_promotion(
         ctx: Context<CreateCouponPromotion>,
         discount_percentage: u8,
@@ -49,16 +118,152 @@ pub mod discount_platform {
         )
     }
 
-    pub fn mint_coupon(ctx: Context<MintCoupon>, coupon_id: u64) -> Result<()> {
-        instructions::mint_coupon::handler(ctx, coupon_id)
+    pub fn update_promotion_details(
+        ctx: Context<ManagePromotion>,
+        category: String,
+        description: String,
+    ) -> Result<()> {
+        instructions::promotion_lifecycle::handler_update_promotion_details(ctx, category, description)
+    }
+
+    pub fn set_promotion_active(ctx: Context<ManagePromotion>, is_active: bool) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_promotion_active(ctx, is_active)
+    }
+
+    pub fn extend_promotion(ctx: Context<ManagePromotion>, new_expiry_timestamp: i64) -> Result<()> {
+        instructions::promotion_lifecycle::handler_extend_promotion(ctx, new_expiry_timestamp)
+    }
+
+    pub fn increase_promotion_supply(ctx: Context<ManagePromotion>, new_max_supply: u32) -> Result<()> {
+        instructions::promotion_lifecycle::handler_increase_promotion_supply(ctx, new_max_supply)
+    }
+
+    pub fn set_promotion_geofence(
+        ctx: Context<SetPromotionGeofence>,
+        latitude: f64,
+        longitude: f64,
+        radius_meters: u32,
+    ) -> Result<()> {
+        instructions::geo_cells::handler_set_promotion_geofence(ctx, latitude, longitude, radius_meters)
+    }
+
+    pub fn clear_promotion_geofence(ctx: Context<ClearPromotionGeofence>) -> Result<()> {
+        instructions::geo_cells::handler_clear_promotion_geofence(ctx)
+    }
+
+    pub fn set_discount_kind(ctx: Context<ManagePromotion>, discount_kind: DiscountKind) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_discount_kind(ctx, discount_kind)
+    }
+
+    pub fn set_uses_per_coupon(ctx: Context<ManagePromotion>, uses_per_coupon: u16) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_uses_per_coupon(ctx, uses_per_coupon)
+    }
+
+    pub fn set_max_per_wallet(ctx: Context<ManagePromotion>, max_per_wallet: Option<u32>) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_max_per_wallet(ctx, max_per_wallet)
+    }
+
+    pub fn set_allow_list(ctx: Context<ManagePromotion>, allow_list_root: Option<[u8; 32]>) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_allow_list(ctx, allow_list_root)
+    }
+
+    pub fn set_promo_codes(ctx: Context<ManagePromotion>, promo_code_root: Option<[u8; 32]>) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_promo_codes(ctx, promo_code_root)
+    }
+
+    pub fn set_promotion_launch(
+        ctx: Context<ManagePromotion>,
+        start_timestamp: i64,
+        presale: Option<PresaleWindow>,
+    ) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_promotion_launch(ctx, start_timestamp, presale)
+    }
+
+    pub fn add_presale_wallet(ctx: Context<AddPresaleWallet>, wallet: Pubkey) -> Result<()> {
+        instructions::presale::handler_add_presale_wallet(ctx, wallet)
+    }
+
+    pub fn remove_presale_wallet(ctx: Context<RemovePresaleWallet>) -> Result<()> {
+        instructions::presale::handler_remove_presale_wallet(ctx)
+    }
+
+    pub fn set_redemption_schedule(
+        ctx: Context<ManagePromotion>,
+        redemption_schedule: Option<RedemptionSchedule>,
+    ) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_redemption_schedule(ctx, redemption_schedule)
+    }
+
+    pub fn set_promotion_branches(ctx: Context<ManagePromotion>, allowed_branches: Vec<u32>) -> Result<()> {
+        instructions::promotion_lifecycle::handler_set_promotion_branches(ctx, allowed_branches)
+    }
+
+    pub fn close_promotion(ctx: Context<ClosePromotion>) -> Result<()> {
+        instructions::promotion_lifecycle::handler_close_promotion(ctx)
+    }
+
+    pub fn mint_coupon(
+        ctx: Context<MintCoupon>,
+        coupon_id: u64,
+        allow_list_proof: Option<AllowListProof>,
+    ) -> Result<()> {
+        instructions::mint_coupon::handler(ctx, coupon_id, allow_list_proof)
+    }
+
+    pub fn mint_hooked_coupon(
+        ctx: Context<MintHookedCoupon>,
+        coupon_id: u64,
+        allow_list_proof: Option<AllowListProof>,
+    ) -> Result<()> {
+        instructions::mint_hooked_coupon::handler(ctx, coupon_id, allow_list_proof)
+    }
+
+    pub fn claim_with_voucher(
+        ctx: Context<ClaimWithVoucher>,
+        coupon_id: u64,
+        voucher: MintVoucher,
+    ) -> Result<()> {
+        instructions::claim_with_voucher::handler(ctx, coupon_id, voucher)
+    }
+
+    pub fn commit_promo_code(ctx: Context<CommitPromoCode>, commitment: [u8; 32]) -> Result<()> {
+        instructions::promo_codes::handler_commit_promo_code(ctx, commitment)
+    }
+
+    pub fn claim_with_code(
+        ctx: Context<ClaimWithCode>,
+        coupon_id: u64,
+        code: String,
+        salt: [u8; 32],
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::promo_codes::handler_claim_with_code(ctx, coupon_id, code, salt, proof)
+    }
+
+    pub fn set_loyalty_program(ctx: Context<SetLoyaltyProgram>, stamps_required: u32, is_active: bool) -> Result<()> {
+        instructions::loyalty::handler_set_loyalty_program(ctx, stamps_required, is_active)
+    }
+
+    pub fn open_loyalty_card(ctx: Context<OpenLoyaltyCard>) -> Result<()> {
+        instructions::loyalty::handler_open_loyalty_card(ctx)
+    }
+
+    pub fn claim_loyalty_reward(ctx: Context<ClaimLoyaltyReward>, coupon_id: u64) -> Result<()> {
+        instructions::loyalty::handler_claim_loyalty_reward(ctx, coupon_id)
+    }
+
+    // Invoked by Token-2022 on every transfer of a hooked coupon mint
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<CouponTransferHook>, amount: u64) -> Result<()> {
+        instructions::transfer_hook::handler(ctx, amount)
     }
 
     pub fn transfer_coupon(ctx: Context<TransferCoupon>) -> Result<()> {
         instructions::transfer_coupon::handler(ctx)
     }
 
-    pub fn redeem_coupon(ctx: Context<RedeemCoupon>) -> Result<()> {
-        instructions::redeem_coupon::handler(ctx)
+    pub fn redeem_coupon(ctx: Context<RedeemCoupon>, bill: Option<RedemptionBill>) -> Result<()> {
+        instructions::redeem_coupon::handler(ctx, bill)
     }
 
     pub fn list_for_sale(ctx: Context<ListCouponForSale>, price: u64) -> Result<()> {
@@ -150,8 +355,9 @@ pub mod discount_platform {
     pub fn verify_and_redeem_ticket(
         ctx: Context<VerifyAndRedeemTicket>,
         expected_hash: [u8; 32],
+        bill: Option<RedemptionBill>,
     ) -> Result<()> {
-        instructions::redemption_tickets::handler_verify_redeem_ticket(ctx, expected_hash)
+        instructions::redemption_tickets::handler_verify_redeem_ticket(ctx, expected_hash, bill)
     }
 
     pub fn cancel_redemption_ticket(
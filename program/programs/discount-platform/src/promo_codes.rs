@@ -0,0 +1,65 @@
+// src/promo_codes.rs
+// Secret promo codes, e.g. "SUMMER24" printed on receipts. The merchant commits
+// to a batch with Promotion.promo_code_root, a merkle root over hash_code leaves
+// built with allow_list::AllowListTree::from_leaves.
+//
+// Revealing a code in a transaction would let anyone watching copy it into their
+// own claim, so claims are two steps: commit_promo_code stores
+// hash_commitment(code, claimer, salt), and claim_with_code reveals the code in
+// a later slot. A copied code is useless without a commitment made beforehand.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+// Prefixes 0 and 1 are the allow-list leaf and node hashes
+const CODE_PREFIX: &[u8] = &[2];
+const COMMITMENT_PREFIX: &[u8] = &[3];
+
+pub const MAX_CODE_LEN: usize = 32;
+
+pub fn hash_code(code: &[u8]) -> [u8; 32] {
+    hashv(&[CODE_PREFIX, code]).to_bytes()
+}
+
+pub fn hash_commitment(code: &[u8], claimer: &Pubkey, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[COMMITMENT_PREFIX, claimer.as_ref(), salt, code]).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allow_list::{hash_leaf, verify, AllowListTree};
+
+    const CODES: [&str; 5] = ["SUMMER24", "WINTER24", "SPRING25", "FALL25", "VIP-0001"];
+
+    #[test]
+    fn every_code_proves_against_the_root() {
+        let tree = AllowListTree::from_leaves(CODES.iter().map(|code| hash_code(code.as_bytes())).collect());
+
+        for (index, code) in CODES.iter().enumerate() {
+            assert!(verify(&tree.root(), hash_code(code.as_bytes()), &tree.proof(index)));
+        }
+        assert!(!verify(&tree.root(), hash_code(b"summer24"), &tree.proof(0)));
+    }
+
+    #[test]
+    fn code_leaves_differ_from_allow_list_leaves() {
+        // A wallet leaf and a code leaf over the same bytes must not collide
+        let wallet = Pubkey::new_unique();
+        let mut bytes = wallet.to_bytes().to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+
+        assert_ne!(hash_code(&bytes), hash_leaf(&wallet, 1));
+    }
+
+    #[test]
+    fn commitment_binds_the_claimer_and_salt() {
+        let claimer = Pubkey::new_unique();
+        let salt = [9; 32];
+        let commitment = hash_commitment(b"SUMMER24", &claimer, &salt);
+
+        assert_eq!(commitment, hash_commitment(b"SUMMER24", &claimer, &salt));
+        assert_ne!(commitment, hash_commitment(b"SUMMER24", &Pubkey::new_unique(), &salt));
+        assert_ne!(commitment, hash_commitment(b"SUMMER24", &claimer, &[8; 32]));
+        assert_ne!(commitment, hash_commitment(b"WINTER24", &claimer, &salt));
+    }
+}
@@ -18,6 +18,7 @@ pub struct CouponAuction {
     pub auto_extend: bool,            // Extend if bid in last 5 mins
     pub extension_seconds: i64,       // How much to extend
     pub min_bid_increment: u64,       // Minimum increase per bid
+    pub payment_mint: Option<Pubkey>, // SPL mint bids are paid in; None = SOL
 }
 
 // Added Debug derive to fix the error
@@ -54,7 +55,7 @@ impl CouponAuction {
     
     pub fn should_extend(&self, current_time: i64) -> bool {
         self.auto_extend 
-            && (self.end_time - current_time) < 300 // Less than 5 mins left
+            && (self.end_time - current_time) < self.extension_seconds // Inside the extension window
             && self.bid_count > 0
     }
     
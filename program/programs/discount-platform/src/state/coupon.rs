@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use super::DiscountKind;
 
 #[account]
 #[derive(InitSpace)]
@@ -8,11 +9,96 @@ pub struct Coupon {
     pub owner: Pubkey,
     pub merchant: Pubkey,
     pub discount_percentage: u8,
+    pub discount_kind: DiscountKind,   // Copied from the promotion at mint
     pub expiry_timestamp: i64,
-    pub is_redeemed: bool,
+    pub is_redeemed: bool,     // Set on the last use
     pub redeemed_at: i64,
+    pub uses_total: u16,       // Copied from promotion.uses_per_coupon at mint
+    pub uses_remaining: u16,
     pub created_at: i64,
       #[max_len(200)]
     pub metadata_uri: String,  // IPFS or Arweave link
     pub mint: Option<Pubkey>,  // SPL Token mint address
+    pub lock: CouponLock,      // Set while the coupon is escrowed by a listing, auction or stake
+    pub last_sale_price: u64,  // Paid at mint or the last resale; royalties apply above it
+}
+
+impl Coupon {
+    // Spends one use at `timestamp` and returns its number, 1 for the first.
+    // The last use marks the coupon redeemed.
+    pub fn use_once(&mut self, timestamp: i64) -> u16 {
+        self.uses_remaining -= 1;
+        if self.uses_remaining == 0 {
+            self.is_redeemed = true;
+            self.redeemed_at = timestamp;
+        }
+        self.uses_total - self.uses_remaining
+    }
+
+    // NFT name, which for multi-use coupons shows the uses left. Fits the 32
+    // bytes Metaplex allows for any u16 counts.
+    pub fn metadata_name(&self) -> String {
+        if self.uses_total > 1 {
+            format!("Punch Card {}/{} uses left", self.uses_remaining, self.uses_total)
+        } else {
+            "Discount Coupon".to_string()
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CouponLock {
+    None,
+    Listed,
+    Auctioned,
+    Staked,
+}
+
+impl anchor_lang::Space for CouponLock {
+    const INIT_SPACE: usize = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::coupon;
+
+    #[test]
+    fn single_use_coupons_are_redeemed_on_first_use() {
+        let mut coupon = coupon(1);
+
+        assert_eq!(coupon.use_once(100), 1);
+        assert!(coupon.is_redeemed);
+        assert_eq!(coupon.redeemed_at, 100);
+        assert_eq!(coupon.uses_remaining, 0);
+    }
+
+    #[test]
+    fn punch_cards_are_redeemed_on_the_last_use() {
+        let mut coupon = coupon(3);
+
+        assert_eq!(coupon.use_once(100), 1);
+        assert_eq!(coupon.use_once(200), 2);
+        assert!(!coupon.is_redeemed);
+        assert_eq!(coupon.redeemed_at, 0);
+
+        assert_eq!(coupon.use_once(300), 3);
+        assert!(coupon.is_redeemed);
+        assert_eq!(coupon.redeemed_at, 300);
+    }
+
+    #[test]
+    fn names_show_the_uses_left() {
+        let mut punch_card = coupon(10);
+        assert_eq!(punch_card.metadata_name(), "Punch Card 10/10 uses left");
+
+        punch_card.use_once(0);
+        assert_eq!(punch_card.metadata_name(), "Punch Card 9/10 uses left");
+
+        assert_eq!(coupon(1).metadata_name(), "Discount Coupon");
+    }
+
+    #[test]
+    fn names_fit_metaplex_limit() {
+        assert!(coupon(u16::MAX).metadata_name().len() <= 32);
+    }
 }
@@ -0,0 +1,18 @@
+// src/state/coupon_use.rs
+use anchor_lang::prelude::*;
+
+// One redemption of a coupon, written by redeem_coupon and
+// verify_and_redeem_ticket. Keyed by the coupon's uses_remaining before the
+// use, so the next record's address follows from the coupon alone.
+#[account]
+#[derive(InitSpace)]
+pub struct CouponUse {
+    pub coupon: Pubkey,
+    pub user: Pubkey,
+    pub merchant: Pubkey,
+    pub redeemed_by: Pubkey,       // Merchant authority or staff key that signed
+    pub branch: Option<Pubkey>,
+    pub use_number: u16,           // 1 for the first use
+    pub used_at: i64,
+    pub bump: u8,
+}
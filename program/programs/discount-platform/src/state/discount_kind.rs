@@ -0,0 +1,179 @@
+// src/state/discount_kind.rs
+use anchor_lang::prelude::*;
+
+// What a coupon is worth at the till. Set on the Promotion, copied into every
+// Coupon at mint and carried in the redemption events for POS integrations.
+// Amounts are in base units of `currency_mint`, e.g. 5_000_000 = $5 in USDC.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DiscountKind {
+    Percentage { percent: u8 },
+    FixedAmount { amount: u64, currency_mint: Pubkey },
+    // Buy `buy_quantity`, get `free_quantity` more free; BOGO is 1 + 1
+    BuyXGetY { buy_quantity: u8, free_quantity: u8 },
+    // `sku_hash` is sha256 of the merchant's item SKU; min_spend 0 = no minimum
+    FreeItem { sku_hash: [u8; 32], min_spend: u64, currency_mint: Pubkey },
+    // `amount_off` once the purchase reaches `min_spend`, e.g. $10 off $50
+    MinimumSpend { min_spend: u64, amount_off: u64, currency_mint: Pubkey },
+}
+
+// What the customer was billed at redemption, submitted by the merchant side.
+// Same base units as the kind's currency_mint, e.g. USDC for a $5-off coupon.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RedemptionBill {
+    pub amount: u64,         // Total before the discount
+    pub item_price: u64,     // Price of one free item for BuyXGetY and FreeItem; else 0
+    pub quantity: u16,       // Paid items for BuyXGetY, not counting the free ones; else 0
+}
+
+impl DiscountKind {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            DiscountKind::Percentage { percent } => percent > 0 && percent <= 100,
+            DiscountKind::FixedAmount { amount, .. } => amount > 0,
+            DiscountKind::BuyXGetY { buy_quantity, free_quantity } => buy_quantity > 0 && free_quantity > 0,
+            DiscountKind::FreeItem { sku_hash, .. } => sku_hash != [0; 32],
+            DiscountKind::MinimumSpend { min_spend, amount_off, .. } => min_spend > 0 && amount_off > 0,
+        }
+    }
+
+    // Discount given on `bill`, capped at the bill amount. None when the bill
+    // misses the kind's minimum spend or BuyXGetY's buy quantity.
+    pub fn discount_on(&self, bill: &RedemptionBill) -> Option<u64> {
+        let discount = match *self {
+            DiscountKind::Percentage { percent } => (bill.amount as u128 * percent as u128 / 100) as u64,
+            DiscountKind::FixedAmount { amount, .. } => amount,
+            DiscountKind::BuyXGetY { buy_quantity, free_quantity } => {
+                if bill.quantity < buy_quantity as u16 {
+                    return None;
+                }
+                bill.item_price.saturating_mul(free_quantity as u64)
+            }
+            DiscountKind::FreeItem { min_spend, .. } => {
+                if bill.amount < min_spend {
+                    return None;
+                }
+                bill.item_price
+            }
+            DiscountKind::MinimumSpend { min_spend, amount_off, .. } => {
+                if bill.amount < min_spend {
+                    return None;
+                }
+                amount_off
+            }
+        };
+        Some(discount.min(bill.amount))
+    }
+
+    // Percentage off for the legacy `discount_percentage` fields; 0 for other kinds
+    pub fn percent(&self) -> u8 {
+        match *self {
+            DiscountKind::Percentage { percent } => percent,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentages_must_be_between_one_and_a_hundred() {
+        assert!(DiscountKind::Percentage { percent: 1 }.is_valid());
+        assert!(DiscountKind::Percentage { percent: 100 }.is_valid());
+        assert!(!DiscountKind::Percentage { percent: 0 }.is_valid());
+        assert!(!DiscountKind::Percentage { percent: 101 }.is_valid());
+    }
+
+    #[test]
+    fn amount_kinds_need_non_zero_amounts() {
+        let usdc = Pubkey::new_unique();
+
+        assert!(DiscountKind::FixedAmount { amount: 5_000_000, currency_mint: usdc }.is_valid());
+        assert!(!DiscountKind::FixedAmount { amount: 0, currency_mint: usdc }.is_valid());
+
+        assert!(DiscountKind::MinimumSpend { min_spend: 50_000_000, amount_off: 10_000_000, currency_mint: usdc }.is_valid());
+        assert!(!DiscountKind::MinimumSpend { min_spend: 0, amount_off: 10_000_000, currency_mint: usdc }.is_valid());
+        assert!(!DiscountKind::MinimumSpend { min_spend: 50_000_000, amount_off: 0, currency_mint: usdc }.is_valid());
+    }
+
+    #[test]
+    fn item_kinds_need_quantities_and_a_sku() {
+        assert!(DiscountKind::BuyXGetY { buy_quantity: 1, free_quantity: 1 }.is_valid());
+        assert!(!DiscountKind::BuyXGetY { buy_quantity: 0, free_quantity: 1 }.is_valid());
+        assert!(!DiscountKind::BuyXGetY { buy_quantity: 2, free_quantity: 0 }.is_valid());
+
+        let currency_mint = Pubkey::new_unique();
+        assert!(DiscountKind::FreeItem { sku_hash: [7; 32], min_spend: 0, currency_mint }.is_valid());
+        assert!(!DiscountKind::FreeItem { sku_hash: [0; 32], min_spend: 0, currency_mint }.is_valid());
+    }
+
+    #[test]
+    fn only_percentages_have_a_percent() {
+        assert_eq!(DiscountKind::Percentage { percent: 25 }.percent(), 25);
+        assert_eq!(DiscountKind::BuyXGetY { buy_quantity: 1, free_quantity: 1 }.percent(), 0);
+        assert_eq!(
+            DiscountKind::FixedAmount { amount: 1, currency_mint: Pubkey::new_unique() }.percent(),
+            0
+        );
+    }
+
+    fn bill(amount: u64, item_price: u64) -> RedemptionBill {
+        RedemptionBill { amount, item_price, quantity: 0 }
+    }
+
+    #[test]
+    fn discounts_follow_the_kind() {
+        let usdc = Pubkey::new_unique();
+
+        assert_eq!(DiscountKind::Percentage { percent: 20 }.discount_on(&bill(50_000_000, 0)), Some(10_000_000));
+        assert_eq!(
+            DiscountKind::FixedAmount { amount: 5_000_000, currency_mint: usdc }.discount_on(&bill(30_000_000, 0)),
+            Some(5_000_000)
+        );
+        assert_eq!(
+            DiscountKind::BuyXGetY { buy_quantity: 2, free_quantity: 1 }
+                .discount_on(&RedemptionBill { quantity: 2, ..bill(9_000_000, 3_000_000) }),
+            Some(3_000_000)
+        );
+        assert_eq!(
+            DiscountKind::FreeItem { sku_hash: [7; 32], min_spend: 0, currency_mint: usdc }.discount_on(&bill(8_000_000, 4_000_000)),
+            Some(4_000_000)
+        );
+    }
+
+    #[test]
+    fn discounts_never_exceed_the_bill() {
+        let usdc = Pubkey::new_unique();
+
+        assert_eq!(
+            DiscountKind::FixedAmount { amount: 5_000_000, currency_mint: usdc }.discount_on(&bill(3_000_000, 0)),
+            Some(3_000_000)
+        );
+        assert_eq!(DiscountKind::Percentage { percent: 100 }.discount_on(&bill(u64::MAX, 0)), Some(u64::MAX));
+    }
+
+    #[test]
+    fn minimum_spends_must_be_met() {
+        let usdc = Pubkey::new_unique();
+        let ten_off_fifty = DiscountKind::MinimumSpend { min_spend: 50_000_000, amount_off: 10_000_000, currency_mint: usdc };
+
+        assert_eq!(ten_off_fifty.discount_on(&bill(49_999_999, 0)), None);
+        assert_eq!(ten_off_fifty.discount_on(&bill(50_000_000, 0)), Some(10_000_000));
+
+        let free_item = DiscountKind::FreeItem { sku_hash: [7; 32], min_spend: 20_000_000, currency_mint: usdc };
+        assert_eq!(free_item.discount_on(&bill(10_000_000, 4_000_000)), None);
+    }
+
+    #[test]
+    fn buy_quantities_must_be_met() {
+        let buy_two_get_one = DiscountKind::BuyXGetY { buy_quantity: 2, free_quantity: 1 };
+
+        assert_eq!(buy_two_get_one.discount_on(&RedemptionBill { quantity: 1, ..bill(6_000_000, 3_000_000) }), None);
+        assert_eq!(buy_two_get_one.discount_on(&bill(6_000_000, 3_000_000)), None);
+        assert_eq!(
+            buy_two_get_one.discount_on(&RedemptionBill { quantity: 3, ..bill(12_000_000, 3_000_000) }),
+            Some(3_000_000)
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub struct Listing {
     pub coupon: Pubkey,
     pub seller: Pubkey,
     pub price: u64,
+    pub payment_mint: Option<Pubkey>,  // None = SOL
     pub is_active: bool,
     pub created_at: i64,
 }
@@ -13,10 +13,42 @@ pub struct Location {
 impl Location {
     pub const PRECISION: i32 = 1_000_000;
     
+    // Mean earth radius (6,371 km) expressed as millimetres per degree of arc
+    pub const MILLIMETERS_PER_DEGREE: u128 = 111_194_927;
+    
+    // cos(0°..=90°) in Q2.30, linearly interpolated by cos_q30
+    const COS_TABLE_Q30: [u64; 91] = [
+        1073741824, 1073578288, 1073087729, 1072270298, 1071126243, 1069655912,
+    1067859754, 1065738315, 1063292242, 1060522280, 1057429273, 1054014162,
+    1050277989, 1046221891, 1041847103, 1037154959, 1032146887, 1026824413,
+    1021189159, 1015242840, 1008987269, 1002424350, 995556083, 988384560,
+    980911966, 973140576, 965072759, 956710970, 948057759, 939115760,
+    929887697, 920376381, 910584710, 900515665, 890172315, 879557810,
+    868675383, 857528349, 846120104, 834454122, 822533958, 810363241,
+    797945680, 785285058, 772385229, 759250125, 745883746, 732290163,
+    718473518, 704438018, 690187940, 675727625, 661061475, 646193961,
+    631129609, 615873009, 600428808, 584801711, 568996477, 553017922,
+    536870912, 520560366, 504091252, 487468587, 470697435, 453782903,
+    436730145, 419544355, 402230767, 384794656, 367241333, 349576144,
+    331804471, 313931728, 295963357, 277904834, 259761657, 241539355,
+    223243478, 204879599, 186453311, 167970228, 149435979, 130856211,
+    112236583, 93582766, 74900443, 56195305, 37473049, 18739379,
+    0,
+    ];
+    
+    // Degrees to micro-degrees, or None when either is out of range, NaN or
+    // infinite. Instructions taking f64 coordinates go through this.
+    pub fn checked_from_coords(lat: f64, lon: f64) -> Option<Self> {
+        ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon))
+            .then(|| Self::from_coords(lat, lon))
+    }
+    
+    // Unchecked: the casts saturate, so NaN lands on 0 and anything out of
+    // range on the i32 bounds
     pub fn from_coords(lat: f64, lon: f64) -> Self {
         Self {
-            latitude: (lat * Self::PRECISION as f64) as i32,
-            longitude: (lon * Self::PRECISION as f64) as i32,
+            latitude: (lat * Self::PRECISION as f64).round() as i32,
+            longitude: (lon * Self::PRECISION as f64).round() as i32,
             region_code: 0,
             country_code: 0,
             city_hash: 0,
@@ -30,45 +62,262 @@ impl Location {
         )
     }
     
-    pub fn distance_to(&self, other: &Location) -> f64 {
-        let (lat1, lon1) = self.to_coords();
-        let (lat2, lon2) = other.to_coords();
-        
-        let r = 6371000.0;
-        let phi1 = lat1.to_radians();
-        let phi2 = lat2.to_radians();
-        let delta_phi = (lat2 - lat1).to_radians();
-        let delta_lambda = (lon2 - lon1).to_radians();
+    // Distance in whole metres, using integer maths only so it costs few compute
+    // units and matches off-chain implementations bit for bit.
+    //
+    // Equirectangular projection around the mean latitude, on the same sphere as
+    // the haversine formula. Against haversine the result is within 1 m up to
+    // 10 km apart, and within 1 m + 0.05% up to 100 km, for |latitude| <= 80°.
+    // It keeps drifting beyond that (~0.5% at 1,000 km), so it is only meant for
+    // geofence-sized distances.
+    pub fn distance_to(&self, other: &Location) -> u64 {
+        let delta_lat = (other.latitude as i64 - self.latitude as i64).unsigned_abs();
+        let mut delta_lon = other.longitude as i64 - self.longitude as i64;
+        if delta_lon > 180 * Self::PRECISION as i64 {
+            delta_lon -= 360 * Self::PRECISION as i64;
+        } else if delta_lon < -180 * Self::PRECISION as i64 {
+            delta_lon += 360 * Self::PRECISION as i64;
+        }
+        let mean_lat = ((self.latitude as i64 + other.latitude as i64) / 2).unsigned_abs();
         
-        let a = (delta_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (delta_lambda / 2.0).sin().powi(2);
-        let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+        // Both legs in micro-degrees scaled by 2^16, so the rounding below is
+        // the only precision lost
+        let x = (delta_lon.unsigned_abs() as u128 * Self::cos_q30(mean_lat) as u128) >> 14;
+        let y = (delta_lat as u128) << 16;
+        let arc = isqrt(x * x + y * y);
         
-        r * c
+        let denominator = (Self::PRECISION as u128 * 1000) << 16;
+        ((arc * Self::MILLIMETERS_PER_DEGREE + denominator / 2) / denominator) as u64
+    }
+    
+    // cos of an absolute latitude in micro-degrees, in Q2.30
+    fn cos_q30(micro_degrees: u64) -> u64 {
+        let degree = (micro_degrees / Self::PRECISION as u64) as usize;
+        if degree >= 90 {
+            return 0;
+        }
+        let fraction = micro_degrees % Self::PRECISION as u64;
+        let (lower, upper) = (Self::COS_TABLE_Q30[degree], Self::COS_TABLE_Q30[degree + 1]);
+        lower - (lower - upper) * fraction / Self::PRECISION as u64
     }
 }
 
+// Integer square root (floor) by Newton's method
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = 1u128 << ((128 - n.leading_zeros()) / 2 + 1);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+// Index of geofenced promotions on a 0.1° grid, seeded by cell_id. The cell's
+// promotions are the GeoCellEntry PDAs at indices 0..promotion_count, so a client
+// lists promotions near a point by deriving that cell and its 8 neighbours.
 #[account]
 #[derive(InitSpace)]
 pub struct GeoCell {
     pub cell_id: u64,
-    pub min_latitude: i32,
-    pub max_latitude: i32,
-    pub min_longitude: i32,
-    pub max_longitude: i32,
+    pub min_latitude: i32,    // Inclusive
+    pub max_latitude: i32,    // Exclusive
+    pub min_longitude: i32,   // Inclusive
+    pub max_longitude: i32,   // Exclusive
     pub promotion_count: u32,
+    pub bump: u8,
 }
 
 impl GeoCell {
     pub const GRID_SIZE: i32 = 100_000;
     
     pub fn from_coords(lat: f64, lon: f64) -> (i32, i32) {
-        let lat_int = (lat * Location::PRECISION as f64) as i32;
-        let lon_int = (lon * Location::PRECISION as f64) as i32;
-        
-        (lat_int / Self::GRID_SIZE, lon_int / Self::GRID_SIZE)
+        Self::from_location(&Location::from_coords(lat, lon))
+    }
+    
+    // Floor division, so cells just south/west of 0° don't share a cell with
+    // the ones just north/east of it
+    pub fn from_location(location: &Location) -> (i32, i32) {
+        (
+            location.latitude.div_euclid(Self::GRID_SIZE),
+            location.longitude.div_euclid(Self::GRID_SIZE),
+        )
     }
     
     pub fn to_cell_id(cell_lat: i32, cell_lon: i32) -> u64 {
-        ((cell_lat as u64) << 32) | ((cell_lon as u64) & 0xFFFFFFFF)
+        ((cell_lat as u32 as u64) << 32) | (cell_lon as u32 as u64)
+    }
+    
+    pub fn from_cell_id(cell_id: u64) -> (i32, i32) {
+        ((cell_id >> 32) as u32 as i32, cell_id as u32 as i32)
+    }
+    
+    pub fn cell_id_for(lat: f64, lon: f64) -> u64 {
+        let (cell_lat, cell_lon) = Self::from_coords(lat, lon);
+        Self::to_cell_id(cell_lat, cell_lon)
+    }
+    
+    pub fn init_bounds(&mut self, cell_id: u64) {
+        let (cell_lat, cell_lon) = Self::from_cell_id(cell_id);
+        self.cell_id = cell_id;
+        self.min_latitude = cell_lat * Self::GRID_SIZE;
+        self.max_latitude = self.min_latitude + Self::GRID_SIZE;
+        self.min_longitude = cell_lon * Self::GRID_SIZE;
+        self.max_longitude = self.min_longitude + Self::GRID_SIZE;
+    }
+}
+
+// Slot `index` of a GeoCell's promotion list. Removal swaps the last slot in,
+// keeping indices 0..promotion_count contiguous.
+#[account]
+#[derive(InitSpace)]
+pub struct GeoCellEntry {
+    pub cell_id: u64,
+    pub index: u32,
+    pub promotion: Pubkey,
+    pub merchant: Pubkey,
+    pub bump: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // distance_to is checked against an f64 haversine reference on the same
+    // 6,371 km sphere
+    const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+    const METERS_PER_MICRO_DEGREE: f64 = 0.111_194_927;
+
+    fn haversine(a: &Location, b: &Location) -> f64 {
+        let (lat1, lon1) = a.to_coords();
+        let (lat2, lon2) = b.to_coords();
+        let delta_phi = (lat2 - lat1).to_radians();
+        let delta_lambda = (lon2 - lon1).to_radians();
+        let h = (delta_phi / 2.0).sin().powi(2)
+            + lat1.to_radians().cos() * lat2.to_radians().cos() * (delta_lambda / 2.0).sin().powi(2);
+        EARTH_RADIUS_METERS * 2.0 * h.sqrt().atan2((1.0 - h).sqrt())
+    }
+
+    fn location(latitude: i32, longitude: i32) -> Location {
+        Location {
+            latitude,
+            longitude,
+            region_code: 0,
+            country_code: 0,
+            city_hash: 0,
+        }
+    }
+
+    // xorshift64*, so failures reproduce without a proptest dependency
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+        }
+
+        fn unit(&mut self) -> f64 {
+            (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        fn range(&mut self, min: i64, max: i64) -> i64 {
+            min + (self.next_u64() % (max - min + 1) as u64) as i64
+        }
+    }
+
+    // Random pairs at most `max_distance` metres apart with both ends inside
+    // |latitude| <= `max_latitude` degrees
+    fn pairs(seed: u64, count: usize, max_distance: f64, max_latitude: i64) -> Vec<(Location, Location)> {
+        let mut rng = Rng(seed);
+        let lat_limit = max_latitude * 1_000_000;
+        let mut pairs = Vec::with_capacity(count);
+        while pairs.len() < count {
+            let latitude = rng.range(-lat_limit, lat_limit);
+            let longitude = rng.range(-180_000_000, 179_999_999);
+            let bearing = rng.unit() * std::f64::consts::TAU;
+            let distance = rng.unit() * max_distance;
+
+            let cos_lat = (latitude as f64 / 1e6).to_radians().cos();
+            let delta_lat = (distance * bearing.cos() / METERS_PER_MICRO_DEGREE) as i64;
+            let delta_lon = (distance * bearing.sin() / METERS_PER_MICRO_DEGREE / cos_lat) as i64;
+            let other_latitude = latitude + delta_lat;
+            if other_latitude.abs() > lat_limit {
+                continue;
+            }
+            let other_longitude = (longitude + delta_lon + 180_000_000).rem_euclid(360_000_000) - 180_000_000;
+
+            pairs.push((
+                location(latitude as i32, longitude as i32),
+                location(other_latitude as i32, other_longitude as i32),
+            ));
+        }
+        pairs
+    }
+
+    fn assert_within(pairs: &[(Location, Location)], tolerance: impl Fn(f64) -> f64) {
+        for (a, b) in pairs {
+            let expected = haversine(a, b);
+            let actual = a.distance_to(b) as f64;
+            assert!(
+                (actual - expected).abs() <= tolerance(expected),
+                "({}, {}) -> ({}, {}): got {} m, haversine {:.3} m",
+                a.latitude, a.longitude, b.latitude, b.longitude, actual, expected
+            );
+        }
+    }
+
+    #[test]
+    fn within_one_meter_up_to_10_km() {
+        assert_within(&pairs(0x9E37_79B9_7F4A_7C15, 50_000, 10_000.0, 80), |_| 1.0);
+    }
+
+    #[test]
+    fn within_one_meter_plus_five_bps_up_to_100_km() {
+        assert_within(&pairs(0xD1B5_4A32_D192_ED03, 50_000, 100_000.0, 80), |expected| 1.0 + expected * 0.0005);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn symmetric_and_zero_on_identical_points() {
+        for (a, b) in pairs(0x94D0_49BB_1331_11EB, 10_000, 100_000.0, 80) {
+            assert_eq!(a.distance_to(&b), b.distance_to(&a));
+            assert_eq!(a.distance_to(&a), 0);
+        }
+    }
+
+    #[test]
+    fn wraps_across_the_antimeridian() {
+        let west = location(10_000_000, 179_999_000);
+        let east = location(10_000_000, -179_999_000);
+        let expected = haversine(&west, &east);
+        assert!((west.distance_to(&east) as f64 - expected).abs() <= 1.0);
+    }
+
+    #[test]
+    fn from_coords_rounds_to_the_nearest_micro_degree() {
+        let location = Location::from_coords(40.712_799_9, -74.005_999_9);
+        assert_eq!((location.latitude, location.longitude), (40_712_800, -74_006_000));
+    }
+
+    #[test]
+    fn checked_from_coords_rejects_out_of_range_and_non_finite() {
+        for (lat, lon) in [
+            (f64::NAN, 0.0),
+            (0.0, f64::NAN),
+            (f64::INFINITY, 0.0),
+            (0.0, f64::NEG_INFINITY),
+            (90.000_001, 0.0),
+            (0.0, -180.000_001),
+        ] {
+            assert!(Location::checked_from_coords(lat, lon).is_none(), "({lat}, {lon})");
+        }
+        let corner = Location::checked_from_coords(-90.0, 180.0).unwrap();
+        assert_eq!((corner.latitude, corner.longitude), (-90_000_000, 180_000_000));
+    }
+}
@@ -0,0 +1,147 @@
+// src/state/loyalty.rs
+use anchor_lang::prelude::*;
+use super::Coupon;
+
+// A merchant's stamp program, e.g. "buy 9, get the 10th free". Each coupon
+// used up at the merchant stamps the customer's LoyaltyCard once, and
+// `stamps_required` stamps buy a free coupon from `reward_promotion` (whose
+// coupons earn no stamps). One PDA per merchant.
+#[account]
+#[derive(InitSpace)]
+pub struct LoyaltyProgram {
+    pub merchant: Pubkey,
+    pub reward_promotion: Pubkey,
+    pub stamps_required: u32,
+    pub is_active: bool,               // Stamps still count while paused; rewards wait
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl LoyaltyProgram {
+    // Whether using `coupon` just now earns a stamp: only the use that finishes
+    // it, so a punch card counts once, and never for the program's own rewards
+    pub fn earns_stamp(&self, coupon: &Coupon) -> bool {
+        coupon.is_redeemed && coupon.promotion != self.reward_promotion
+    }
+}
+
+// A customer's stamps at one merchant. One PDA per (merchant, user), opened by
+// the user; redemptions only stamp cards that exist.
+#[account]
+#[derive(InitSpace)]
+pub struct LoyaltyCard {
+    pub merchant: Pubkey,
+    pub user: Pubkey,
+    pub stamps: u32,                   // Unspent stamps
+    pub lifetime_stamps: u32,
+    pub rewards_claimed: u32,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl LoyaltyCard {
+    pub fn stamp(&mut self) {
+        self.stamps = self.stamps.saturating_add(1);
+        self.lifetime_stamps = self.lifetime_stamps.saturating_add(1);
+    }
+
+    pub fn can_claim(&self, program: &LoyaltyProgram) -> bool {
+        program.is_active && self.stamps >= program.stamps_required
+    }
+
+    // Spends one reward's worth of stamps; the caller checks can_claim first
+    pub fn redeem_reward(&mut self, program: &LoyaltyProgram) {
+        self.stamps -= program.stamps_required;
+        self.rewards_claimed += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::coupon;
+
+    fn program(stamps_required: u32, is_active: bool) -> LoyaltyProgram {
+        LoyaltyProgram {
+            merchant: Pubkey::new_unique(),
+            reward_promotion: Pubkey::new_unique(),
+            stamps_required,
+            is_active,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    fn card() -> LoyaltyCard {
+        LoyaltyCard {
+            merchant: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            stamps: 0,
+            lifetime_stamps: 0,
+            rewards_claimed: 0,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn rewards_need_the_threshold() {
+        let program = program(3, true);
+        let mut card = card();
+
+        card.stamp();
+        card.stamp();
+        assert!(!card.can_claim(&program));
+
+        card.stamp();
+        assert!(card.can_claim(&program));
+    }
+
+    #[test]
+    fn rewards_spend_only_the_threshold() {
+        let program = program(3, true);
+        let mut card = card();
+        for _ in 0..4 {
+            card.stamp();
+        }
+
+        card.redeem_reward(&program);
+        assert_eq!(card.stamps, 1);
+        assert_eq!(card.lifetime_stamps, 4);
+        assert_eq!(card.rewards_claimed, 1);
+        assert!(!card.can_claim(&program));
+    }
+
+    #[test]
+    fn paused_programs_keep_stamps_but_hold_rewards() {
+        let paused = program(1, false);
+        let mut card = card();
+        card.stamp();
+
+        assert_eq!(card.stamps, 1);
+        assert!(!card.can_claim(&paused));
+    }
+
+    #[test]
+    fn punch_cards_earn_one_stamp_on_their_last_use() {
+        let program = program(3, true);
+        let mut punch_card = coupon(3);
+
+        punch_card.use_once(0);
+        assert!(!program.earns_stamp(&punch_card));
+        punch_card.use_once(0);
+        assert!(!program.earns_stamp(&punch_card));
+        punch_card.use_once(0);
+        assert!(program.earns_stamp(&punch_card));
+    }
+
+    #[test]
+    fn reward_coupons_earn_no_stamp() {
+        let program = program(3, true);
+        let mut reward = coupon(1);
+        reward.promotion = program.reward_promotion;
+
+        reward.use_once(0);
+        assert!(!program.earns_stamp(&reward));
+    }
+}
@@ -7,4 +7,10 @@ pub struct Marketplace {
     pub total_coupons: u64,
     pub total_merchants: u64,
     pub fee_basis_points: u16,
+    pub pending_authority: Option<Pubkey>,  // Set by propose, cleared on accept
+    pub paused: bool,
+}
+
+impl Marketplace {
+    pub const MAX_FEE_BASIS_POINTS: u16 = 1000; // 10%
 }
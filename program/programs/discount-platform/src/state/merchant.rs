@@ -1,6 +1,6 @@
 // src/state/merchant.rs
 use anchor_lang::prelude::*;
-use super::Location;
+use super::{Location, MerchantStaff};
 
 #[account]
 #[derive(InitSpace)]
@@ -12,10 +12,70 @@ pub struct Merchant {
     pub category: String,
     pub total_coupons_created: u64,
     pub total_coupons_redeemed: u64,
+    pub total_discount_given: u64,  // Across redemptions that reported a bill
     pub is_active: bool,
     pub created_at: i64,
+    pub royalty_basis_points: u16,  // Merchant's cut of secondary sales
+    pub branch_count: u32,          // Next MerchantBranch index
 
     // Geographic data
     pub location: Location,
     pub has_physical_location: bool,
-}
\ No newline at end of file
+}
+
+impl Merchant {
+    // Whether `signer` is the merchant authority itself. Owner-only instructions
+    // check this rather than comparing against `authority` directly.
+    pub fn is_authorized(&self, signer: &Pubkey) -> bool {
+        self.authority == *signer
+    }
+
+    // Same as is_authorized, but also accepts a staff key whose grant carries
+    // `role` and has not expired. The caller derives `staff` from this merchant.
+    pub fn is_authorized_for(
+        &self,
+        signer: &Pubkey,
+        staff: Option<&MerchantStaff>,
+        role: u8,
+        current_time: i64,
+    ) -> bool {
+        self.is_authorized(signer)
+            || staff.is_some_and(|staff| {
+                staff.staff == *signer && staff.has_role(role) && staff.is_active(current_time)
+            })
+    }
+
+    // Royalty owed on a resale at `price`, clamped to the current platform cap.
+    // Only the premium over `last_sale_price` is charged, so selling at or
+    // below cost pays nothing.
+    pub fn royalty_for(&self, price: u64, last_sale_price: u64, max_royalty_basis_points: u16) -> u64 {
+        let basis_points = self.royalty_basis_points.min(max_royalty_basis_points);
+        let premium = price.saturating_sub(last_sale_price);
+        (premium as u128 * basis_points as u128 / 10000) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::merchant;
+
+    #[test]
+    fn charges_only_the_resale_premium() {
+        let merchant = merchant(500);
+        assert_eq!(merchant.royalty_for(1_500, 1_000, 1000), 25);
+        assert_eq!(merchant.royalty_for(1_000, 1_000, 1000), 0);
+        assert_eq!(merchant.royalty_for(800, 1_000, 1000), 0);
+    }
+
+    #[test]
+    fn clamps_to_platform_cap() {
+        let merchant = merchant(2_000);
+        assert_eq!(merchant.royalty_for(10_000, 0, 1000), 1_000);
+    }
+
+    #[test]
+    fn large_prices_do_not_overflow() {
+        let merchant = merchant(1_000);
+        assert_eq!(merchant.royalty_for(u64::MAX, 0, 1000), u64::MAX / 10);
+    }
+}
@@ -0,0 +1,18 @@
+// src/state/merchant_branch.rs
+use anchor_lang::prelude::*;
+use super::Location;
+
+// One physical store of a merchant. Indexed by Merchant.branch_count at creation,
+// so a chain's branches can be enumerated as [b"branch", merchant, 0..branch_count].
+#[account]
+#[derive(InitSpace)]
+pub struct MerchantBranch {
+    pub merchant: Pubkey,
+    pub index: u32,
+    #[max_len(50)]
+    pub name: String,
+    pub location: Location,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
@@ -0,0 +1,39 @@
+// src/state/merchant_staff.rs
+use anchor_lang::prelude::*;
+
+// A key the merchant authority has delegated some of its rights to, e.g. a
+// cashier's POS wallet. One PDA per (merchant, staff key), closed on revoke.
+#[account]
+#[derive(InitSpace)]
+pub struct MerchantStaff {
+    pub merchant: Pubkey,
+    pub staff: Pubkey,
+    pub roles: u8,                     // Bitmask of ROLE_* flags
+    pub expires_at: Option<i64>,       // None = valid until revoked
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl MerchantStaff {
+    pub const ROLE_REDEEM: u8 = 1 << 0;
+    // Create promotions and manage them afterwards: details, expiry, supply,
+    // presale wallets, geofence and the loyalty program
+    pub const ROLE_CREATE_PROMOTIONS: u8 = 1 << 1;
+    pub const ROLE_MANAGE_LISTINGS: u8 = 1 << 2;  // Open group deals on the merchant's promotions
+    pub const ROLE_ISSUE_VOUCHERS: u8 = 1 << 3;   // Mint coupons directly or sign claim vouchers
+    pub const ALL_ROLES: u8 = Self::ROLE_REDEEM
+        | Self::ROLE_CREATE_PROMOTIONS
+        | Self::ROLE_MANAGE_LISTINGS
+        | Self::ROLE_ISSUE_VOUCHERS;
+
+    pub fn has_role(&self, role: u8) -> bool {
+        self.roles & role == role
+    }
+
+    pub fn is_active(&self, current_time: i64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => current_time < expires_at,
+            None => true,
+        }
+    }
+}
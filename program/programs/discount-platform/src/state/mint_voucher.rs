@@ -0,0 +1,162 @@
+// src/state/mint_voucher.rs
+use anchor_lang::prelude::*;
+
+// Off-chain grant of one free coupon, signed by the merchant's backend and
+// claimed with claim_with_voucher. The signature is checked by an ed25519
+// program instruction placed just before the claim in the same transaction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct MintVoucher {
+    pub promotion: Pubkey,
+    pub recipient: Pubkey,
+    pub nonce: u64,                    // Unique per merchant; see VoucherNonce
+    pub expires_at: i64,
+}
+
+impl MintVoucher {
+    pub const DOMAIN: &'static [u8] = b"discount_platform:mint_voucher:v1";
+
+    // Bytes the merchant signs
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 80);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(self.promotion.as_ref());
+        message.extend_from_slice(self.recipient.as_ref());
+        message.extend_from_slice(&self.nonce.to_le_bytes());
+        message.extend_from_slice(&self.expires_at.to_le_bytes());
+        message
+    }
+
+    // Public key that signed this voucher in an ed25519 program instruction's
+    // data. Only a single signature whose key, signature and message all live in
+    // that same instruction is accepted, so the runtime has verified exactly
+    // the bytes read here.
+    pub fn signer_in(&self, ed25519_data: &[u8]) -> Option<Pubkey> {
+        const OFFSETS_START: usize = 2;
+        const SIGNATURE_LEN: usize = 64;
+        const THIS_INSTRUCTION: u16 = u16::MAX;
+
+        let read_u16 = |at: usize| -> Option<u16> {
+            let bytes = ed25519_data.get(OFFSETS_START + at..OFFSETS_START + at + 2)?;
+            Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+        };
+        if *ed25519_data.first()? != 1 {
+            return None;
+        }
+
+        let signature_offset = read_u16(0)? as usize;
+        let public_key_offset = read_u16(4)? as usize;
+        let message_offset = read_u16(8)? as usize;
+        let message_size = read_u16(10)? as usize;
+        if read_u16(2)? != THIS_INSTRUCTION
+            || read_u16(6)? != THIS_INSTRUCTION
+            || read_u16(12)? != THIS_INSTRUCTION
+        {
+            return None;
+        }
+
+        ed25519_data.get(signature_offset..signature_offset + SIGNATURE_LEN)?;
+        let public_key = ed25519_data.get(public_key_offset..public_key_offset + 32)?;
+        let message = ed25519_data.get(message_offset..message_offset + message_size)?;
+        if message != self.message().as_slice() {
+            return None;
+        }
+        Pubkey::try_from(public_key).ok()
+    }
+}
+
+// Marks a voucher nonce as claimed. One PDA per (merchant, nonce), so a
+// voucher can be claimed once even if it was signed for several promotions.
+#[account]
+#[derive(InitSpace)]
+pub struct VoucherNonce {
+    pub merchant: Pubkey,
+    pub nonce: u64,
+    pub promotion: Pubkey,
+    pub recipient: Pubkey,
+    pub signer: Pubkey,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // signer_in is fed hand-built ed25519 program data, laid out the way
+    // solana_sdk's new_ed25519_instruction does it
+
+    const THIS_INSTRUCTION: u16 = u16::MAX;
+
+    fn voucher() -> MintVoucher {
+        MintVoucher {
+            promotion: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            nonce: 42,
+            expires_at: 1_800_000_000,
+        }
+    }
+
+    // [count, padding, offsets (7 x u16), public key, signature, message]
+    fn ed25519_data(signer: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            instruction_index,
+            public_key_offset,
+            instruction_index,
+            message_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn reads_the_signer_of_the_voucher_message() {
+        let voucher = voucher();
+        let signer = Pubkey::new_unique();
+
+        let data = ed25519_data(&signer, &voucher.message(), THIS_INSTRUCTION);
+        assert_eq!(voucher.signer_in(&data), Some(signer));
+    }
+
+    #[test]
+    fn rejects_a_signature_over_another_voucher() {
+        let voucher = voucher();
+        let other = MintVoucher { nonce: 43, ..voucher };
+
+        let data = ed25519_data(&Pubkey::new_unique(), &other.message(), THIS_INSTRUCTION);
+        assert_eq!(voucher.signer_in(&data), None);
+    }
+
+    #[test]
+    fn rejects_data_held_in_another_instruction() {
+        let voucher = voucher();
+
+        let data = ed25519_data(&Pubkey::new_unique(), &voucher.message(), 0);
+        assert_eq!(voucher.signer_in(&data), None);
+    }
+
+    #[test]
+    fn rejects_several_signatures_and_truncated_data() {
+        let voucher = voucher();
+        let mut data = ed25519_data(&Pubkey::new_unique(), &voucher.message(), THIS_INSTRUCTION);
+
+        data[0] = 2;
+        assert_eq!(voucher.signer_in(&data), None);
+
+        data[0] = 1;
+        data.truncate(data.len() - 1);
+        assert_eq!(voucher.signer_in(&data), None);
+        assert_eq!(voucher.signer_in(&[]), None);
+    }
+}
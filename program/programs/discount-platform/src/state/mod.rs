@@ -1,8 +1,17 @@
 // src/state/mod.rs
 pub mod marketplace;
 pub mod merchant;
+pub mod merchant_staff;
+pub mod merchant_branch;
 pub mod promotion;
+pub mod discount_kind;
+pub mod presale_allowance;
+pub mod wallet_mint_count;
+pub mod mint_voucher;
+pub mod promo_code;
 pub mod coupon;
+pub mod coupon_use;
+pub mod loyalty;
 pub mod listing;
 pub mod badge;
 pub mod comment;
@@ -14,11 +23,21 @@ pub mod user_stats;
 pub mod redemption_ticket;
 pub mod group_deal;
 pub mod auctions;
+pub mod platform_config;
 
 pub use marketplace::*;
 pub use merchant::*;
+pub use merchant_staff::*;
+pub use merchant_branch::*;
 pub use promotion::*;
+pub use discount_kind::*;
+pub use presale_allowance::*;
+pub use wallet_mint_count::*;
+pub use mint_voucher::*;
+pub use promo_code::*;
 pub use coupon::*;
+pub use coupon_use::*;
+pub use loyalty::*;
 pub use listing::*;
 pub use comment::*;
 pub use external_deal::*;
@@ -28,6 +47,7 @@ pub use staking::*;
 pub use redemption_ticket::*;
 pub use group_deal::*;
 pub use auctions::*;
+pub use platform_config::*;
 
 // Export badge types explicitly (not ReputationTier from badge)
 pub use badge::{BadgeType, BadgeNFT, UserReputation};
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use super::Marketplace;
+
+#[account]
+#[derive(InitSpace)]
+pub struct PlatformConfig {
+    pub version: u32,               // Bumped on every update
+    pub updated_at: i64,
+    pub settings: PlatformSettings,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct PlatformSettings {
+    // Reputation points per action
+    pub reputation_per_purchase: u64,
+    pub reputation_per_redemption: u64,
+    pub reputation_per_listing: u64,
+    pub reputation_per_auction: u64,
+    pub reputation_per_bid: u64,
+    pub reputation_per_group_join: u64,
+    pub reputation_per_rating: u64,
+    pub reputation_per_comment: u64,
+
+    pub ticket_expiry_seconds: i64,
+
+    pub auction_min_duration: i64,
+    pub auction_max_duration: i64,
+    pub auction_extension_seconds: i64,
+
+    pub group_deal_min_duration: i64,
+    pub group_deal_max_tier_discount: u8,  // Max additional % per tier
+
+    pub external_deal_update_interval: i64,
+
+    pub max_royalty_basis_points: u16,     // Cap on merchant resale royalties; fee + royalty must fit in the price
+}
+
+impl Default for PlatformSettings {
+    fn default() -> Self {
+        Self {
+            reputation_per_purchase: 5,
+            reputation_per_redemption: 10,
+            reputation_per_listing: 3,
+            reputation_per_auction: 5,
+            reputation_per_bid: 2,
+            reputation_per_group_join: 3,
+            reputation_per_rating: 2,
+            reputation_per_comment: 1,
+            ticket_expiry_seconds: 300,      // 5 minutes
+            auction_min_duration: 300,       // 5 minutes
+            auction_max_duration: 604800,    // 7 days
+            auction_extension_seconds: 300,  // 5 minutes
+            group_deal_min_duration: 3600,   // 1 hour
+            group_deal_max_tier_discount: 50,
+            external_deal_update_interval: 3600,
+            max_royalty_basis_points: 1000,  // 10%
+        }
+    }
+}
+
+impl PlatformSettings {
+    pub fn is_valid(&self) -> bool {
+        self.ticket_expiry_seconds > 0
+            && self.auction_min_duration > 0
+            && self.auction_min_duration <= self.auction_max_duration
+            && self.auction_extension_seconds >= 0
+            && self.group_deal_min_duration > 0
+            && self.group_deal_max_tier_discount <= 100
+            && self.external_deal_update_interval >= 0
+            && self.max_royalty_basis_points <= 10000 - Marketplace::MAX_FEE_BASIS_POINTS
+    }
+}
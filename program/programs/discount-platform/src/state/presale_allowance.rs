@@ -0,0 +1,13 @@
+// src/state/presale_allowance.rs
+use anchor_lang::prelude::*;
+
+// A wallet the merchant let into a promotion's pre-sale. One PDA per
+// (promotion, wallet), closed when the wallet is removed.
+#[account]
+#[derive(InitSpace)]
+pub struct PresaleAllowance {
+    pub promotion: Pubkey,
+    pub wallet: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
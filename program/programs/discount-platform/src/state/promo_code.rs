@@ -0,0 +1,26 @@
+// src/state/promo_code.rs
+use anchor_lang::prelude::*;
+
+// A claimer's pending promo-code claim: hash_commitment(code, claimer, salt).
+// One PDA per (promotion, claimer); committing again replaces it, and
+// claim_with_code closes it.
+#[account]
+#[derive(InitSpace)]
+pub struct PromoCodeCommit {
+    pub promotion: Pubkey,
+    pub claimer: Pubkey,
+    pub commitment: [u8; 32],
+    pub slot: u64,                     // The code may be revealed in any later slot
+    pub bump: u8,
+}
+
+// Marks a promo code as spent. One PDA per (promotion, hash_code(code)).
+#[account]
+#[derive(InitSpace)]
+pub struct PromoCodeClaim {
+    pub promotion: Pubkey,
+    pub code_hash: [u8; 32],
+    pub claimer: Pubkey,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
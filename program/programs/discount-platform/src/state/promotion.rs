@@ -1,26 +1,262 @@
 // src/state/promotion.rs
 use anchor_lang::prelude::*;
-use super::Location;
+use super::{Location, BadgeType, DiscountKind};
 
 #[account]
 #[derive(InitSpace)]
 pub struct Promotion {
     pub merchant: Pubkey,
-    pub discount_percentage: u8,
+    pub discount_percentage: u8,      // DiscountKind::percent() of discount_kind
+    pub discount_kind: DiscountKind,
     pub max_supply: u32,
     pub current_supply: u32,
+    pub total_discount_given: u64,    // Across redemptions that reported a bill
+    pub max_per_wallet: Option<u32>,  // Coupons one recipient may mint; None = no limit
+    pub uses_per_coupon: u16,         // Redemptions each new coupon allows; 1 = single use
     pub expiry_timestamp: i64,
     #[max_len(30)]
     pub category: String,
     #[max_len(200)]
     pub description: String,
     pub price: u64,
+    pub payment_mint: Option<Pubkey>,  // SPL mint the price is paid in; None = SOL
     pub is_active: bool,
     pub created_at: i64,
 
+    // Coupons can be minted from start_timestamp on, or earlier during the
+    // pre-sale by allow-listed wallets and badge holders
+    pub start_timestamp: i64,
+    pub presale: Option<PresaleWindow>,
+
+    // Merkle root of (wallet, quantity) leaves allowed to mint; None = anyone
+    // (see allow_list.rs)
+    pub allow_list_root: Option<[u8; 32]>,
+
+    // Merkle root of hashed promo codes claimable with claim_with_code; None =
+    // no codes (see promo_codes.rs)
+    pub promo_code_root: Option<[u8; 32]>,
+
     // Geographic data
     pub location: Location,
     pub geo_cell_id: u64,
     pub radius_meters: u32,
     pub is_location_based: bool,
-}
\ No newline at end of file
+
+    // MerchantBranch indices the coupons can be redeemed at; empty = any
+    #[max_len(16)]
+    pub allowed_branches: Vec<u32>,
+
+    // When coupons may be redeemed; None = any time before expiry
+    pub redemption_schedule: Option<RedemptionSchedule>,
+}
+
+// Early minting before Promotion.start_timestamp. Open to wallets with a
+// PresaleAllowance for the promotion and, when `badge` is set, to its holders.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct PresaleWindow {
+    pub start_timestamp: i64,
+    pub badge: Option<BadgeType>,
+}
+
+impl PresaleWindow {
+    pub fn admits(&self, allow_listed: bool, badge: Option<BadgeType>) -> bool {
+        allow_listed || (self.badge.is_some() && self.badge == badge)
+    }
+}
+
+// Recurring redemption window, e.g. weekdays 15:00-18:00 at UTC-5. Days and
+// minutes are in the merchant's local time, i.e. UTC shifted by utc_offset_minutes.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct RedemptionSchedule {
+    pub start_timestamp: i64,      // Closed before this, whatever the day or time
+    pub days_of_week: u8,          // Bit 0 = Monday ... bit 6 = Sunday
+    pub open_minute: u16,          // Minutes after local midnight
+    pub close_minute: u16,         // Exclusive; below open_minute = closes after midnight
+    pub utc_offset_minutes: i16,
+}
+
+impl RedemptionSchedule {
+    pub const MINUTES_PER_DAY: u16 = 1440;
+    pub const ALL_DAYS: u8 = 0b111_1111;
+
+    pub fn is_valid(&self) -> bool {
+        self.days_of_week != 0
+            && self.days_of_week & !Self::ALL_DAYS == 0
+            && self.open_minute < Self::MINUTES_PER_DAY
+            && self.close_minute <= Self::MINUTES_PER_DAY
+            && self.open_minute != self.close_minute
+            && (-720..=840).contains(&self.utc_offset_minutes)
+    }
+
+    // Whether the window is open at `timestamp`. A window that runs past midnight
+    // belongs to the day it opened, so Friday 22:00-02:00 is open early Saturday.
+    pub fn is_open(&self, timestamp: i64) -> bool {
+        if timestamp < self.start_timestamp {
+            return false;
+        }
+
+        let local = timestamp + self.utc_offset_minutes as i64 * 60;
+        let day = local.div_euclid(86_400);
+        let minute = (local.rem_euclid(86_400) / 60) as u16;
+        // 1970-01-01 was a Thursday
+        let weekday = |day: i64| (day + 3).rem_euclid(7) as u8;
+        let open_on = |day: i64| self.days_of_week & (1 << weekday(day)) != 0;
+
+        if self.open_minute < self.close_minute {
+            open_on(day) && minute >= self.open_minute && minute < self.close_minute
+        } else {
+            (open_on(day) && minute >= self.open_minute) || (open_on(day - 1) && minute < self.close_minute)
+        }
+    }
+}
+
+impl Promotion {
+    pub const MAX_ALLOWED_BRANCHES: usize = 16;
+
+    // Whether `point` lies within radius_meters of `center`, which is the
+    // promotion's own location or the branch the coupon is redeemed at
+    pub fn is_within_geofence(&self, center: &Location, point: &Location) -> bool {
+        !self.is_location_based || center.distance_to(point) <= self.radius_meters as u64
+    }
+
+    pub fn is_mintable_at(&self, timestamp: i64, presale_access: bool) -> bool {
+        timestamp >= self.start_timestamp
+            || match &self.presale {
+                Some(presale) => presale_access && timestamp >= presale.start_timestamp,
+                None => false,
+            }
+    }
+
+    // Whether a wallet that has minted `minted` coupons may mint another
+    pub fn allows_wallet_mint(&self, minted: u32) -> bool {
+        match self.max_per_wallet {
+            Some(max_per_wallet) => minted < max_per_wallet,
+            None => true,
+        }
+    }
+
+    pub fn is_redeemable_at(&self, timestamp: i64) -> bool {
+        match &self.redemption_schedule {
+            Some(schedule) => schedule.is_open(timestamp),
+            None => true,
+        }
+    }
+
+    pub fn allows_branch(&self, branch_index: Option<u32>) -> bool {
+        self.allowed_branches.is_empty()
+            || branch_index.is_some_and(|index| self.allowed_branches.contains(&index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allow_listed_wallets_are_admitted() {
+        let presale = PresaleWindow { start_timestamp: 0, badge: None };
+
+        assert!(presale.admits(true, None));
+        assert!(!presale.admits(false, None));
+        assert!(!presale.admits(false, Some(BadgeType::EarlyAdopter)));
+    }
+
+    #[test]
+    fn only_the_configured_badge_is_admitted() {
+        let presale = PresaleWindow { start_timestamp: 0, badge: Some(BadgeType::TenRedemptions) };
+
+        assert!(presale.admits(false, Some(BadgeType::TenRedemptions)));
+        assert!(!presale.admits(false, Some(BadgeType::FirstPurchase)));
+        assert!(!presale.admits(false, None));
+        assert!(presale.admits(true, None));
+    }
+
+    // 2024-01-01 00:00:00 UTC, a Monday
+    const MONDAY: i64 = 1_704_067_200;
+    const HOUR: i64 = 3_600;
+    const DAY: i64 = 86_400;
+
+    const WEEKDAYS: u8 = 0b001_1111;
+    const FRIDAY: u8 = 1 << 4;
+
+    fn schedule(days_of_week: u8, open: u16, close: u16, utc_offset_minutes: i16) -> RedemptionSchedule {
+        RedemptionSchedule {
+            start_timestamp: 0,
+            days_of_week,
+            open_minute: open,
+            close_minute: close,
+            utc_offset_minutes,
+        }
+    }
+
+    #[test]
+    fn weekday_happy_hour_in_local_time() {
+        // Weekdays 15:00-18:00 at UTC-5, i.e. 20:00-23:00 UTC
+        let happy_hour = schedule(WEEKDAYS, 15 * 60, 18 * 60, -300);
+
+        assert!(happy_hour.is_open(MONDAY + 20 * HOUR));
+        assert!(happy_hour.is_open(MONDAY + 22 * HOUR + 59 * 60));
+        assert!(!happy_hour.is_open(MONDAY + 19 * HOUR + 59 * 60));
+        assert!(!happy_hour.is_open(MONDAY + 23 * HOUR)); // close_minute is exclusive
+        assert!(happy_hour.is_open(MONDAY + 4 * DAY + 21 * HOUR)); // Friday
+        assert!(!happy_hour.is_open(MONDAY + 5 * DAY + 21 * HOUR)); // Saturday
+        assert!(!happy_hour.is_open(MONDAY + 6 * DAY + 21 * HOUR)); // Sunday
+    }
+
+    #[test]
+    fn offset_moves_the_local_day() {
+        // Mondays 08:00-10:00 at UTC+10: Monday 09:00 local is still Sunday in UTC
+        let breakfast = schedule(1, 8 * 60, 10 * 60, 600);
+
+        assert!(breakfast.is_open(MONDAY - HOUR));
+        assert!(!breakfast.is_open(MONDAY + 23 * HOUR)); // Monday 23:00 UTC = Tuesday 09:00 local
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_the_day_it_opens() {
+        // Fridays 22:00-02:00 UTC
+        let late_night = schedule(FRIDAY, 22 * 60, 2 * 60, 0);
+        let friday = MONDAY + 4 * DAY;
+
+        assert!(late_night.is_open(friday + 23 * HOUR));
+        assert!(late_night.is_open(friday + DAY + HOUR)); // Saturday 01:00
+        assert!(!late_night.is_open(friday + DAY + 2 * HOUR)); // Saturday 02:00
+        assert!(!late_night.is_open(friday + DAY + 23 * HOUR)); // Saturday 23:00
+        assert!(!late_night.is_open(friday + HOUR)); // Friday 01:00 belongs to Thursday
+    }
+
+    #[test]
+    fn closed_before_start_timestamp() {
+        let mut all_day = schedule(RedemptionSchedule::ALL_DAYS, 0, RedemptionSchedule::MINUTES_PER_DAY, 0);
+        all_day.start_timestamp = MONDAY + DAY;
+
+        assert!(!all_day.is_open(MONDAY + 12 * HOUR));
+        assert!(all_day.is_open(MONDAY + DAY));
+        assert!(all_day.is_open(MONDAY + 3 * DAY + 23 * HOUR + 59 * 60));
+    }
+
+    #[test]
+    fn weekday_of_timestamps_before_the_epoch() {
+        // 1969-12-31 was a Wednesday
+        let mut wednesdays = schedule(1 << 2, 0, RedemptionSchedule::MINUTES_PER_DAY, 0);
+        wednesdays.start_timestamp = i64::MIN;
+
+        assert!(wednesdays.is_open(-HOUR));
+        assert!(!wednesdays.is_open(-DAY - HOUR));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(schedule(WEEKDAYS, 15 * 60, 18 * 60, -300).is_valid());
+        assert!(schedule(FRIDAY, 22 * 60, 2 * 60, 0).is_valid());
+        assert!(schedule(RedemptionSchedule::ALL_DAYS, 0, RedemptionSchedule::MINUTES_PER_DAY, 840).is_valid());
+
+        assert!(!schedule(0, 15 * 60, 18 * 60, 0).is_valid()); // no days
+        assert!(!schedule(0b1000_0000, 15 * 60, 18 * 60, 0).is_valid()); // unknown day bit
+        assert!(!schedule(WEEKDAYS, 15 * 60, 15 * 60, 0).is_valid()); // empty window
+        assert!(!schedule(WEEKDAYS, RedemptionSchedule::MINUTES_PER_DAY, 60, 0).is_valid());
+        assert!(!schedule(WEEKDAYS, 60, RedemptionSchedule::MINUTES_PER_DAY + 1, 0).is_valid());
+        assert!(!schedule(WEEKDAYS, 15 * 60, 18 * 60, -721).is_valid());
+        assert!(!schedule(WEEKDAYS, 15 * 60, 18 * 60, 841).is_valid());
+    }
+}
@@ -12,6 +12,7 @@ pub struct RedemptionTicket {
     pub is_consumed: bool,
     pub nonce: u64,                    // Prevents replay attacks
     pub redemption_location: Option<RedemptionLocation>,
+    pub redeemed_at_branch: Option<Pubkey>,  // MerchantBranch that consumed the ticket
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
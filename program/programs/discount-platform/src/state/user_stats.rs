@@ -9,6 +9,7 @@ pub struct UserStats {
     pub total_ratings_given: u32,
     pub total_comments: u32,
     pub total_listings: u32,
+    pub total_savings: u64,     // Discounts on redemptions that reported a bill
     pub reputation_score: u64,
     pub tier: ReputationTier,
     #[max_len(10)]
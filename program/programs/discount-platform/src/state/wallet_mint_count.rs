@@ -0,0 +1,14 @@
+// src/state/wallet_mint_count.rs
+use anchor_lang::prelude::*;
+
+// How many coupons of a promotion were minted to a wallet, checked against
+// Promotion.max_per_wallet. Keyed by the recipient at mint time, so moving
+// coupons to another wallet does not reset it.
+#[account]
+#[derive(InitSpace)]
+pub struct WalletMintCount {
+    pub promotion: Pubkey,
+    pub wallet: Pubkey,
+    pub minted: u32,
+    pub bump: u8,
+}
@@ -0,0 +1,43 @@
+// src/test_fixtures.rs
+// Accounts for the unit tests next to each module, filled with neutral values
+// so each test only spells out the fields it cares about
+use anchor_lang::prelude::Pubkey;
+use crate::state::{Coupon, CouponLock, DiscountKind, Location, Merchant};
+
+pub fn merchant(royalty_basis_points: u16) -> Merchant {
+    Merchant {
+        authority: Pubkey::new_unique(),
+        name: "Cafe".to_string(),
+        category: "food".to_string(),
+        total_coupons_created: 0,
+        total_coupons_redeemed: 0,
+        total_discount_given: 0,
+        is_active: true,
+        created_at: 0,
+        royalty_basis_points,
+        branch_count: 0,
+        location: Location { latitude: 0, longitude: 0, region_code: 0, country_code: 0, city_hash: 0 },
+        has_physical_location: false,
+    }
+}
+
+pub fn coupon(uses: u16) -> Coupon {
+    Coupon {
+        id: 1,
+        promotion: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        merchant: Pubkey::new_unique(),
+        discount_percentage: 10,
+        discount_kind: DiscountKind::Percentage { percent: 10 },
+        expiry_timestamp: i64::MAX,
+        is_redeemed: false,
+        redeemed_at: 0,
+        uses_total: uses,
+        uses_remaining: uses,
+        created_at: 0,
+        metadata_uri: String::new(),
+        mint: None,
+        lock: CouponLock::None,
+        last_sale_price: 0,
+    }
+}
@@ -0,0 +1,230 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  getCurrentTimestamp,
+  geoCellId,
+  deriveGeoCellPDA,
+  deriveGeoCellEntryPDA,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  LAMPORTS_PER_SOL,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+describe("Geofence Compute Benchmarks", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let marketplaceAuthority: PublicKey;
+  let promotionPDA: PublicKey;
+  let coupon: PublicKey;
+
+  // Downtown Manhattan
+  const center = { latitude: 40.7128, longitude: -74.006 };
+
+  const mintCoupon = async (
+    promotion: PublicKey,
+    merchantPDA: PublicKey,
+    merchantSigner: Keypair,
+    recipient: Keypair
+  ): Promise<[PublicKey, Keypair]> => {
+    const promotionAccount = await program.account.promotion.fetch(promotion);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotion.toBuffer(), u32ToLeBytes(promotionAccount.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+    const [metadata] = deriveMetadataPDA(nftMint.publicKey);
+    const [masterEdition] = deriveMasterEditionPDA(nftMint.publicKey);
+    const merchant = await program.account.merchant.fetch(merchantPDA);
+
+    await program.methods
+      .mintCoupon(new BN(promotionAccount.currentSupply + 1), null)
+      .accounts({
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, recipient.publicKey),
+        metadata,
+        masterEdition,
+        promotion,
+        merchant: merchantPDA,
+        marketplace: accounts.marketplacePDA,
+        recipient: recipient.publicKey,
+        payer: recipient.publicKey,
+        authority: merchantSigner.publicKey,
+        merchantAuthority: merchant.authority,
+        marketplaceAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([recipient, nftMint, merchantSigner])
+      .rpc();
+
+    return [coupon, nftMint];
+  };
+
+  // Compute units the program itself reported for a simulated ticket generation
+  const ticketComputeUnits = async (latitude: number | null, longitude: number | null): Promise<number> => {
+    const nonce = new BN(getCurrentTimestamp() + Math.floor(Math.random() * 1_000_000));
+    const [ticket] = derivePDA(
+      [Buffer.from("ticket"), coupon.toBuffer(), accounts.user1.publicKey.toBuffer(), u64ToLeBytes(nonce)],
+      program.programId
+    );
+    const { raw } = await program.methods
+      .generateRedemptionTicket(nonce, latitude, longitude)
+      .accounts({
+        ticket,
+        coupon,
+        promotion: promotionPDA,
+        user: accounts.user1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1])
+      .simulate();
+
+    const pattern = new RegExp(`Program ${program.programId.toBase58()} consumed (\\d+) of`);
+    const line = raw.find((log) => pattern.test(log));
+    assert.isDefined(line, "compute units not found in logs");
+    return Number(line.match(pattern)[1]);
+  };
+
+  // Compute units spent inside the geofence check, from the two
+  // sol_log_compute_units lines around it. Only logged by a `bench` build:
+  //   anchor test -- --features bench
+  const geofenceCheckComputeUnits = async (latitude: number, longitude: number): Promise<number | null> => {
+    const nonce = new BN(getCurrentTimestamp() + Math.floor(Math.random() * 1_000_000));
+    const [ticket] = derivePDA(
+      [Buffer.from("ticket"), coupon.toBuffer(), accounts.user1.publicKey.toBuffer(), u64ToLeBytes(nonce)],
+      program.programId
+    );
+    const { raw } = await program.methods
+      .generateRedemptionTicket(nonce, latitude, longitude)
+      .accounts({
+        ticket,
+        coupon,
+        promotion: promotionPDA,
+        user: accounts.user1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1])
+      .simulate();
+
+    const remaining = raw
+      .map((log) => log.match(/Program consumption: (\d+) units remaining/))
+      .filter((match) => match !== null)
+      .map((match) => Number(match[1]));
+    if (remaining.length < 2) {
+      return null;
+    }
+    // sol_log_compute_units itself costs 100 CU
+    return remaining[0] - remaining[1] - 100;
+  };
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+    marketplaceAuthority = (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority;
+
+    await program.methods
+      .registerMerchant("Bench Deli", "restaurant", null, null)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    [promotionPDA] = derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    );
+    await program.methods
+      .createPromotion(20, 10, getExpiryTimestamp(30), "food", "Bench promo", new BN(0.1 * LAMPORTS_PER_SOL))
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    [coupon] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+  });
+
+  it("Keeps the geofence check cheap", async () => {
+    const baseline = await ticketComputeUnits(center.latitude + 0.001, center.longitude + 0.001);
+
+    const cellId = geoCellId(center.latitude, center.longitude);
+    await program.methods
+      .setPromotionGeofence(center.latitude, center.longitude, 1_000)
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        geoCell: deriveGeoCellPDA(cellId, program.programId)[0],
+        geoCellEntry: deriveGeoCellEntryPDA(cellId, 0, program.programId)[0],
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const samples: number[] = [];
+    for (const [dLat, dLon] of [[0.001, 0.001], [-0.004, 0.006], [0.0001, -0.008], [0.0089, 0]]) {
+      samples.push(await ticketComputeUnits(center.latitude + dLat, center.longitude + dLon));
+    }
+    const overhead = Math.max(...samples) - baseline;
+
+    console.log(`  generate_redemption_ticket: ${baseline} CU without geofence, ${samples.join(" / ")} CU with`);
+    console.log(`  geofence check overhead: ${overhead} CU`);
+    assert.isBelow(overhead, 10_000);
+  });
+
+  it("Keeps the distance maths itself under 2,000 CU", async function () {
+    const samples: number[] = [];
+    for (const [dLat, dLon] of [[0.001, 0.001], [-0.004, 0.006], [0.0001, -0.008], [0.0089, 0]]) {
+      const units = await geofenceCheckComputeUnits(center.latitude + dLat, center.longitude + dLon);
+      if (units === null) {
+        this.skip();
+      }
+      samples.push(units);
+    }
+
+    console.log(`  is_within_geofence: ${samples.join(" / ")} CU`);
+    assert.isBelow(Math.max(...samples), 2_000);
+  });
+});
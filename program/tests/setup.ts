@@ -6,6 +6,7 @@ import {
   Connection,
   LAMPORTS_PER_SOL 
 } from "@solana/web3.js";
+import { createHash } from "crypto";
 import {
   TOKEN_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -49,7 +50,7 @@ export interface TestAccounts {
 export async function airdrop(
   connection: Connection,
   publicKey: PublicKey,
-  amount: number = 10
+  amount: number = 100
 ): Promise<void> {
   const signature = await connection.requestAirdrop(
     publicKey,
@@ -105,6 +106,120 @@ export function deriveMasterEditionPDA(mint: PublicKey): [PublicKey, number] {
   );
 }
 
+// Helper function: GeoCell id of the 0.1° grid cell containing a point
+// (mirrors GeoCell::cell_id_for)
+export function geoCellId(latitude: number, longitude: number): BN {
+  return geoCellIdFromCell(geoCellIndex(latitude), geoCellIndex(longitude));
+}
+
+// Micro-degrees rounded half away from zero, like Location::from_coords
+export function toMicroDegrees(degrees: number): number {
+  return Math.sign(degrees) * Math.round(Math.abs(degrees) * 1_000_000);
+}
+
+function geoCellIndex(degrees: number): number {
+  return Math.floor(toMicroDegrees(degrees) / 100_000);
+}
+
+export function geoCellIdFromCell(cellLat: number, cellLon: number): BN {
+  return new BN(cellLat >>> 0).shln(32).or(new BN(cellLon >>> 0));
+}
+
+// Helper function: The cell containing a point plus its 8 neighbours, i.e.
+// every cell a promotion near that point can be indexed under
+export function nearbyGeoCellIds(latitude: number, longitude: number): BN[] {
+  const cellLat = geoCellIndex(latitude);
+  const cellLon = geoCellIndex(longitude);
+  const ids: BN[] = [];
+  for (const dLat of [-1, 0, 1]) {
+    for (const dLon of [-1, 0, 1]) {
+      ids.push(geoCellIdFromCell(cellLat + dLat, cellLon + dLon));
+    }
+  }
+  return ids;
+}
+
+// Helper function: Derive GeoCell PDA
+export function deriveGeoCellPDA(cellId: BN, programId: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("geo_cell"), u64ToLeBytes(cellId)],
+    programId
+  );
+}
+
+// Helper function: Derive the PDA of a GeoCell's index-th promotion entry
+export function deriveGeoCellEntryPDA(
+  cellId: BN,
+  index: number,
+  programId: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("geo_cell_entry"), u64ToLeBytes(cellId), u32ToLeBytes(index)],
+    programId
+  );
+}
+
+// Helper function: Allow-list leaf hash (mirrors allow_list::hash_leaf)
+export function allowListLeaf(wallet: PublicKey, quantity: number): Buffer {
+  return sha256(Buffer.from([0]), wallet.toBuffer(), u32ToLeBytes(quantity));
+}
+
+function allowListPair(a: Buffer, b: Buffer): Buffer {
+  return Buffer.compare(a, b) <= 0 ? sha256(Buffer.from([1]), a, b) : sha256(Buffer.from([1]), b, a);
+}
+
+function sha256(...parts: Buffer[]): Buffer {
+  const hash = createHash("sha256");
+  parts.forEach((part) => hash.update(part));
+  return hash.digest();
+}
+
+// Helper function: Merkle root and per-leaf proofs over hashed leaves
+// (mirrors allow_list::AllowListTree::from_leaves)
+export function buildMerkleTree(leaves: Buffer[]): { root: number[]; proofs: number[][][] } {
+  const levels = [leaves];
+  while (levels[levels.length - 1].length > 1) {
+    const level = levels[levels.length - 1];
+    const next: Buffer[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(i + 1 < level.length ? allowListPair(level[i], level[i + 1]) : level[i]);
+    }
+    levels.push(next);
+  }
+
+  const proofs = leaves.map((_, leafIndex) => {
+    const proof: number[][] = [];
+    let index = leafIndex;
+    for (const level of levels.slice(0, -1)) {
+      const sibling = level[index ^ 1];
+      if (sibling) proof.push([...sibling]);
+      index >>= 1;
+    }
+    return proof;
+  });
+
+  return { root: [...levels[levels.length - 1][0]], proofs };
+}
+
+// Helper function: Merkle root and per-entry proofs for set_allow_list and
+// mint_coupon (mirrors allow_list::AllowListTree::new)
+export function buildAllowList(entries: { wallet: PublicKey; quantity: number }[]): {
+  root: number[];
+  proofs: number[][][];
+} {
+  return buildMerkleTree(entries.map(({ wallet, quantity }) => allowListLeaf(wallet, quantity)));
+}
+
+// Helper function: Promo-code leaf hash (mirrors promo_codes::hash_code)
+export function promoCodeLeaf(code: string): Buffer {
+  return sha256(Buffer.from([2]), Buffer.from(code));
+}
+
+// Helper function: commit_promo_code commitment (mirrors promo_codes::hash_commitment)
+export function promoCodeCommitment(code: string, claimer: PublicKey, salt: Buffer): number[] {
+  return [...sha256(Buffer.from([3]), claimer.toBuffer(), salt, Buffer.from(code))];
+}
+
 // Helper function: Check if account exists
 export async function accountExists(
   connection: Connection,
@@ -172,7 +172,7 @@ describe("Advanced Integration Tests", () => {
     );
 
     await program.methods
-      .verifyAndRedeemTicket(secretHash)
+      .verifyAndRedeemTicket(secretHash, null)
       .accounts({
         ticket,
         coupon,
@@ -300,7 +300,7 @@ describe("Advanced Integration Tests", () => {
     );
 
     await program.methods
-      .verifyAndRedeemTicket(secretHash)
+      .verifyAndRedeemTicket(secretHash, null)
       .accounts({
         ticket,
         coupon,
@@ -32,6 +32,7 @@ describe("Auctions", () => {
   let promotionPDA: PublicKey;
   let couponPDA: PublicKey;
   let couponMint: Keypair;
+  let marketplaceWasAlreadyInitialized = false;
 
   before(async () => {
     console.log("\n=== AUCTION TESTS SETUP ===");
@@ -39,6 +40,7 @@ describe("Auctions", () => {
     
     // Initialize marketplace if needed
     const marketplaceExists = await accountExists(connection, accounts.marketplacePDA);
+    marketplaceWasAlreadyInitialized = marketplaceExists;
     if (!marketplaceExists) {
       console.log("Initializing marketplace...");
       await program.methods
@@ -135,7 +137,7 @@ describe("Auctions", () => {
 
       // Mint coupon
       await program.methods
-        .mintCoupon(new BN(1))
+        .mintCoupon(new BN(1), null)
         .accounts({
           coupon: couponPDA,
           nftMint: couponMint.publicKey,
@@ -149,6 +151,8 @@ describe("Auctions", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -214,6 +218,10 @@ describe("Auctions", () => {
       assert.equal(auction.autoExtend, true);
       assert.equal(auction.minBidIncrement.toString(), minIncrement.toString());
 
+      // Coupon is held until the auction settles
+      const lockedCoupon = await program.account.coupon.fetch(couponPDA);
+      assert.deepEqual(lockedCoupon.lock, { auctioned: {} });
+
       // Verify user stats updated
       const userStats = await program.account.userStats.fetch(sellerStatsPDA);
       assert.equal(userStats.totalListings, 1);
@@ -527,7 +535,7 @@ describe("Auctions", () => {
 
       // Mint coupon
       await program.methods
-        .mintCoupon(new BN(2))
+        .mintCoupon(new BN(2), null)
         .accounts({
           coupon: newCouponPDA,
           nftMint: newCouponMint.publicKey,
@@ -541,6 +549,8 @@ describe("Auctions", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -671,7 +681,7 @@ describe("Auctions", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(3))
+        .mintCoupon(new BN(3), null)
         .accounts({
           coupon: dutchCouponPDA,
           nftMint: dutchCouponMint.publicKey,
@@ -685,6 +695,8 @@ describe("Auctions", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -794,6 +806,46 @@ describe("Auctions", () => {
       }
     });
 
+    it("Test 50b: Fails to buy a Dutch auction while the marketplace is paused", async function () {
+      // Pausing needs the authority keypair, which we only hold if this file initialized the marketplace
+      if (marketplaceWasAlreadyInitialized) {
+        this.skip();
+        return;
+      }
+
+      await program.methods
+        .setMarketplacePaused(true)
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+
+      try {
+        await program.methods
+          .buyDutchAuction()
+          .accounts({
+            auction: dutchAuctionPDA,
+            coupon: dutchCouponPDA,
+            seller: accounts.user1.publicKey,
+            marketplaceAuthority: accounts.marketplaceAuthority.publicKey,
+            merchant: accounts.merchant1PDA,
+            merchantAuthority: accounts.merchant1.publicKey,
+            buyer: accounts.user2.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([accounts.user2])
+          .rpc();
+        assert.fail("Should have failed with MarketplacePaused");
+      } catch (error) {
+        assert.include(error.message, "MarketplacePaused");
+      } finally {
+        await program.methods
+          .setMarketplacePaused(false)
+          .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+          .signers([accounts.marketplaceAuthority])
+          .rpc();
+      }
+    });
+
     it("Test 51: Calculates Dutch auction price correctly", async () => {
       console.log("\n=== TEST 51: Dutch price calculation ===");
 
@@ -886,7 +938,7 @@ describe("Auctions", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(4))
+        .mintCoupon(new BN(4), null)
         .accounts({
           coupon: sealedCouponPDA,
           nftMint: sealedCouponMint.publicKey,
@@ -900,6 +952,8 @@ describe("Auctions", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -1067,7 +1121,7 @@ describe("Auctions", () => {
       });
 
       await program.methods
-        .mintCoupon(new BN(5))
+        .mintCoupon(new BN(5), null)
         .accounts({
           coupon: cancelCouponPDA,
           nftMint: cancelCouponMint.publicKey,
@@ -1081,6 +1135,8 @@ describe("Auctions", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -1127,6 +1183,7 @@ describe("Auctions", () => {
         .cancelAuction()
         .accounts({
           auction: cancelAuctionPDA,
+          coupon: cancelCouponPDA,
           seller: accounts.user1.publicKey,
         })
         .signers([accounts.user1])
@@ -1135,6 +1192,9 @@ describe("Auctions", () => {
       // Verify auction account is closed
       const auctionExists = await accountExists(connection, cancelAuctionPDA);
       assert.isFalse(auctionExists, "Auction account should be closed");
+
+      const releasedCoupon = await program.account.coupon.fetch(cancelCouponPDA);
+      assert.deepEqual(releasedCoupon.lock, { none: {} });
       
       console.log("✓ Auction cancelled successfully");
     });
@@ -1158,6 +1218,7 @@ describe("Auctions", () => {
           .cancelAuction()
           .accounts({
             auction: auctionPDA,
+            coupon: couponPDA,
             seller: accounts.user1.publicKey,
           })
           .signers([accounts.user1])
@@ -128,7 +128,7 @@ describe("Auto Badge Awarding System", () => {
         );
 
         await program.methods
-          .mintCoupon(new BN(1))
+          .mintCoupon(new BN(1), null)
           .accounts({
             coupon: couponPDA,
             nftMint: mintKeypair.publicKey,
@@ -142,6 +142,8 @@ describe("Auto Badge Awarding System", () => {
             userStats: userStatsPDA,
             payer: accounts.user1.publicKey,
             authority: accounts.merchant1.publicKey,
+            merchantAuthority: accounts.merchant1.publicKey,
+            marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -472,7 +474,7 @@ describe("Auto Badge Awarding System", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(100))
+        .mintCoupon(new BN(100), null)
         .accounts({
           coupon: couponPDA,
           nftMint: mintKeypair.publicKey,
@@ -486,6 +488,8 @@ describe("Auto Badge Awarding System", () => {
           userStats: user2StatsPDA,
           payer: accounts.user2.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -176,8 +176,12 @@ describe("Coupon Operations", () => {
       );
       console.log("UserStats PDA:", userStatsPDA.toBase58());
 
+      const marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      const merchantBalanceBefore = await connection.getBalance(accounts.merchant1.publicKey);
+      const feeCollectorBalanceBefore = await connection.getBalance(marketplace.authority);
+
       await program.methods
-        .mintCoupon(new BN(1))
+        .mintCoupon(new BN(1), null)
         .accounts({
           coupon: couponPDA,
           nftMint: couponMint.publicKey,
@@ -191,6 +195,8 @@ describe("Coupon Operations", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: marketplace.authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -220,6 +226,14 @@ describe("Coupon Operations", () => {
       assert.equal(userStats.totalPurchases, 1);
       assert.isAbove(userStats.reputationScore.toNumber(), 0);
       console.log("UserStats - Purchases:", userStats.totalPurchases, "Reputation:", userStats.reputationScore.toString());
+
+      // Verify the promotion price was split between merchant and marketplace
+      const price = promotion.price.toNumber();
+      const expectedFee = Math.floor((price * marketplace.feeBasisPoints) / 10000);
+      const merchantBalanceAfter = await connection.getBalance(accounts.merchant1.publicKey);
+      const feeCollectorBalanceAfter = await connection.getBalance(marketplace.authority);
+      assert.equal(merchantBalanceAfter - merchantBalanceBefore, price - expectedFee);
+      assert.equal(feeCollectorBalanceAfter - feeCollectorBalanceBefore, expectedFee);
       
       console.log("✓ Coupon minted successfully!");
     });
@@ -255,7 +269,7 @@ describe("Coupon Operations", () => {
         );
 
         await program.methods
-          .mintCoupon(new BN(i + 2))
+          .mintCoupon(new BN(i + 2), null)
           .accounts({
             coupon: newCouponPDA,
             nftMint: newMint.publicKey,
@@ -269,6 +283,8 @@ describe("Coupon Operations", () => {
             userStats: user2StatsPDA,
             payer: accounts.user2.publicKey,
             authority: accounts.merchant1.publicKey,
+            merchantAuthority: accounts.merchant1.publicKey,
+            marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -330,7 +346,7 @@ describe("Coupon Operations", () => {
       const firstTokenAccount = getAssociatedTokenAddressSync(firstMint.publicKey, accounts.user1.publicKey);
 
       await program.methods
-        .mintCoupon(new BN(100))
+        .mintCoupon(new BN(100), null)
         .accounts({
           coupon: firstCouponPDA,
           nftMint: firstMint.publicKey,
@@ -344,6 +360,8 @@ describe("Coupon Operations", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -372,7 +390,7 @@ describe("Coupon Operations", () => {
 
       try {
         await program.methods
-          .mintCoupon(new BN(101))
+          .mintCoupon(new BN(101), null)
           .accounts({
             coupon: secondCouponPDA,
             nftMint: secondMint.publicKey,
@@ -386,6 +404,8 @@ describe("Coupon Operations", () => {
             userStats: userStatsPDA,
             payer: accounts.user1.publicKey,
             authority: accounts.merchant1.publicKey,
+            merchantAuthority: accounts.merchant1.publicKey,
+            marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -452,7 +472,7 @@ describe("Coupon Operations", () => {
 
       try {
         await program.methods
-          .mintCoupon(new BN(200))
+          .mintCoupon(new BN(200), null)
           .accounts({
             coupon: expiredCouponPDA,
             nftMint: expiredMint.publicKey,
@@ -466,6 +486,8 @@ describe("Coupon Operations", () => {
             userStats: userStatsPDA,
             payer: accounts.user1.publicKey,
             authority: accounts.merchant1.publicKey,
+            merchantAuthority: accounts.merchant1.publicKey,
+            marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -493,14 +515,26 @@ describe("Coupon Operations", () => {
         .transferCoupon()
         .accounts({
           coupon: couponPDA,
+          nftMint: couponMint.publicKey,
+          fromTokenAccount: tokenAccount,
+          toTokenAccount: getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user2.publicKey),
           newOwner: accounts.user2.publicKey,
           fromAuthority: accounts.user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
 
       const couponAfter = await program.account.coupon.fetch(couponPDA);
       assert.equal(couponAfter.owner.toString(), accounts.user2.publicKey.toString());
+
+      // The NFT moved with the coupon
+      const recipientBalance = await connection.getTokenAccountBalance(
+        getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user2.publicKey)
+      );
+      assert.equal(recipientBalance.value.amount, "1");
+      const senderBalance = await connection.getTokenAccountBalance(tokenAccount);
+      assert.equal(senderBalance.value.amount, "0");
     });
 
     it("Fails when non-owner tries to transfer", async () => {
@@ -509,8 +543,12 @@ describe("Coupon Operations", () => {
           .transferCoupon()
           .accounts({
             coupon: couponPDA,
+            nftMint: couponMint.publicKey,
+            fromTokenAccount: tokenAccount,
+            toTokenAccount: getAssociatedTokenAddressSync(couponMint.publicKey, accounts.merchant1.publicKey),
             newOwner: accounts.merchant1.publicKey,
             fromAuthority: accounts.user1.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([accounts.user1])
           .rpc();
@@ -528,8 +566,12 @@ describe("Coupon Operations", () => {
         .transferCoupon()
         .accounts({
           coupon: couponPDA,
+          nftMint: couponMint.publicKey,
+          fromTokenAccount: getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user2.publicKey),
+          toTokenAccount: tokenAccount,
           newOwner: accounts.user1.publicKey,
           fromAuthority: accounts.user2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user2])
         .rpc();
@@ -548,7 +590,7 @@ describe("Coupon Operations", () => {
       const redeemedCountBefore = merchantBefore.totalCouponsRedeemed.toNumber();
 
       await program.methods
-        .redeemCoupon()
+        .redeemCoupon(null)
         .accounts({
           coupon: couponPDA,
           nftMint: couponMint.publicKey,
@@ -579,7 +621,7 @@ describe("Coupon Operations", () => {
     it("Fails to redeem already redeemed coupon", async () => {
       try {
         await program.methods
-          .redeemCoupon()
+          .redeemCoupon(null)
           .accounts({
             coupon: couponPDA,
             nftMint: couponMint.publicKey,
@@ -0,0 +1,198 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  derivePDA,
+  accountExists,
+  u64ToLeBytes,
+  geoCellId,
+  nearbyGeoCellIds,
+  deriveGeoCellPDA,
+  deriveGeoCellEntryPDA,
+  LAMPORTS_PER_SOL,
+} from "./setup";
+
+describe("GeoCell Index", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let promotionA: PublicKey;
+  let promotionB: PublicKey;
+
+  // Both points fall in cell (100, 200)
+  const pointA = { latitude: 10.01, longitude: 20.01 };
+  const pointB = { latitude: 10.02, longitude: 20.03 };
+  const cellId = geoCellId(pointA.latitude, pointA.longitude);
+
+  const setGeofence = (
+    promotion: PublicKey,
+    merchantPDA: PublicKey,
+    signer: Keypair,
+    point: { latitude: number; longitude: number },
+    entryIndex: number
+  ) => {
+    const id = geoCellId(point.latitude, point.longitude);
+    return program.methods
+      .setPromotionGeofence(point.latitude, point.longitude, 250)
+      .accounts({
+        promotion,
+        merchant: merchantPDA,
+        geoCell: deriveGeoCellPDA(id, program.programId)[0],
+        geoCellEntry: deriveGeoCellEntryPDA(id, entryIndex, program.programId)[0],
+        authority: signer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+  };
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+
+    const promotions: PublicKey[] = [];
+    for (const [merchant, merchantPDA] of [
+      [accounts.merchant1, accounts.merchant1PDA],
+      [accounts.merchant2, accounts.merchant2PDA],
+    ] as const) {
+      await program.methods
+        .registerMerchant("Geo Cafe", "cafe", null, null)
+        .accounts({
+          merchant: merchantPDA,
+          marketplace: accounts.marketplacePDA,
+          authority: merchant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([merchant])
+        .rpc();
+
+      const [promotion] = derivePDA(
+        [Buffer.from("promotion"), merchantPDA.toBuffer(), u64ToLeBytes(0)],
+        program.programId
+      );
+      await program.methods
+        .createPromotion(10, 50, getExpiryTimestamp(30), "food", "Geo promo", new BN(0.1 * LAMPORTS_PER_SOL))
+        .accounts({
+          promotion,
+          merchant: merchantPDA,
+          authority: merchant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([merchant])
+        .rpc();
+      promotions.push(promotion);
+    }
+    [promotionA, promotionB] = promotions;
+  });
+
+  it("Rejects a geofence set by a foreign signer", async () => {
+    try {
+      await setGeofence(promotionA, accounts.merchant1PDA, accounts.merchant2, pointA, 0);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("NotMerchantAuthority");
+    }
+  });
+
+  it("Indexes two promotions in the same cell", async () => {
+    await setGeofence(promotionA, accounts.merchant1PDA, accounts.merchant1, pointA, 0);
+    await setGeofence(promotionB, accounts.merchant2PDA, accounts.merchant2, pointB, 1);
+
+    const geoCell = await program.account.geoCell.fetch(deriveGeoCellPDA(cellId, program.programId)[0]);
+    assert.equal(geoCell.cellId.toString(), cellId.toString());
+    assert.equal(geoCell.promotionCount, 2);
+    assert.equal(geoCell.minLatitude, 10_000_000);
+    assert.equal(geoCell.maxLatitude, 10_100_000);
+    assert.equal(geoCell.minLongitude, 20_000_000);
+    assert.equal(geoCell.maxLongitude, 20_100_000);
+
+    const entry1 = await program.account.geoCellEntry.fetch(deriveGeoCellEntryPDA(cellId, 1, program.programId)[0]);
+    assert.ok(entry1.promotion.equals(promotionB));
+    assert.ok(entry1.merchant.equals(accounts.merchant2PDA));
+  });
+
+  it("Finds the promotions by deriving the surrounding cells", async () => {
+    // A point in the neighbouring cell to the north still lists both
+    const found: string[] = [];
+    for (const id of nearbyGeoCellIds(10.11, 20.05)) {
+      const [geoCellPDA] = deriveGeoCellPDA(id, program.programId);
+      const geoCell = await program.account.geoCell.fetchNullable(geoCellPDA);
+      for (let index = 0; index < (geoCell?.promotionCount ?? 0); index++) {
+        const entry = await program.account.geoCellEntry.fetch(deriveGeoCellEntryPDA(id, index, program.programId)[0]);
+        found.push(entry.promotion.toBase58());
+      }
+    }
+
+    assert.includeMembers(found, [promotionA.toBase58(), promotionB.toBase58()]);
+  });
+
+  it("Rejects a second geofence on the same promotion", async () => {
+    try {
+      await setGeofence(promotionA, accounts.merchant1PDA, accounts.merchant1, pointA, 2);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("GeofenceAlreadySet");
+    }
+  });
+
+  it("Clears a geofence and moves the last entry into its slot", async () => {
+    await program.methods
+      .clearPromotionGeofence()
+      .accounts({
+        promotion: promotionA,
+        merchant: accounts.merchant1PDA,
+        geoCell: deriveGeoCellPDA(cellId, program.programId)[0],
+        geoCellEntry: deriveGeoCellEntryPDA(cellId, 0, program.programId)[0],
+        lastGeoCellEntry: deriveGeoCellEntryPDA(cellId, 1, program.programId)[0],
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const geoCell = await program.account.geoCell.fetch(deriveGeoCellPDA(cellId, program.programId)[0]);
+    assert.equal(geoCell.promotionCount, 1);
+
+    const entry0 = await program.account.geoCellEntry.fetch(deriveGeoCellEntryPDA(cellId, 0, program.programId)[0]);
+    assert.ok(entry0.promotion.equals(promotionB));
+    assert.equal(entry0.index, 0);
+    assert.isFalse(await accountExists(connection, deriveGeoCellEntryPDA(cellId, 1, program.programId)[0]));
+
+    const promotion = await program.account.promotion.fetch(promotionA);
+    assert.isFalse(promotion.isLocationBased);
+    assert.equal(promotion.geoCellId.toString(), "0");
+  });
+
+  it("Puts points just south-west of 0,0 in their own cell", async () => {
+    const point = { latitude: -0.05, longitude: -0.05 };
+    await setGeofence(promotionA, accounts.merchant1PDA, accounts.merchant1, point, 0);
+
+    const id = geoCellId(point.latitude, point.longitude);
+    assert.notEqual(id.toString(), geoCellId(0.05, 0.05).toString());
+
+    const geoCell = await program.account.geoCell.fetch(deriveGeoCellPDA(id, program.programId)[0]);
+    assert.equal(geoCell.minLatitude, -100_000);
+    assert.equal(geoCell.maxLatitude, 0);
+    assert.equal(geoCell.minLongitude, -100_000);
+    assert.equal(geoCell.maxLongitude, 0);
+  });
+});
@@ -1760,6 +1760,11 @@ describe("Group Deals", () => {
         program.programId
       );
 
+      const [walletMintsPDA] = derivePDA(
+        [Buffer.from("wallet_mints"), promotionPDA.toBuffer(), mintUser.publicKey.toBuffer()],
+        program.programId
+      );
+
       const promotion = await program.account.promotion.fetch(promotionPDA);
       const groupDeal = await program.account.groupDeal.fetch(mintGroupDealPDA);
 
@@ -1770,6 +1775,7 @@ describe("Group Deals", () => {
           groupDeal: mintGroupDealPDA,
           participant: mintParticipantPDA,
           promotion: promotionPDA,
+          walletMints: walletMintsPDA,
           payer: mintUser.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -1786,6 +1792,10 @@ describe("Group Deals", () => {
       assert.equal(coupon.discountPercentage, expectedDiscount);
       assert.equal(coupon.isRedeemed, false);
 
+      // Group coupons count towards the participant's per-wallet limit
+      const walletMints = await program.account.walletMintCount.fetch(walletMintsPDA);
+      assert.equal(walletMints.minted, 1);
+
       // Verify participant updated
       const participant = await program.account.groupParticipant.fetch(mintParticipantPDA);
       assert.equal(participant.couponMinted?.toString(), groupCouponPDA.toString());
@@ -136,7 +136,7 @@ describe("Integration Tests", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(1000))
+        .mintCoupon(new BN(1000), null)
         .accounts({
           coupon: newCouponPDA,
           nftMint: newMint.publicKey,
@@ -150,6 +150,8 @@ describe("Integration Tests", () => {
           userStats: newUserStatsPDA,
           payer: newUser.publicKey,
           authority: newMerchant.publicKey,
+          merchantAuthority: newMerchant.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -166,7 +168,7 @@ describe("Integration Tests", () => {
 
       // Step 5: User redeems coupon
       await program.methods
-        .redeemCoupon()
+        .redeemCoupon(null)
         .accounts({
           coupon: newCouponPDA,
           nftMint: newMint.publicKey,
@@ -287,7 +289,7 @@ describe("Integration Tests", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(2000))
+        .mintCoupon(new BN(2000), null)
         .accounts({
           coupon: testCouponPDA,
           nftMint: testMint.publicKey,
@@ -301,6 +303,8 @@ describe("Integration Tests", () => {
           userStats: sellerStatsPDA,
           payer: seller.publicKey,
           authority: testMerchant.publicKey,
+          merchantAuthority: testMerchant.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -328,8 +332,11 @@ describe("Integration Tests", () => {
         .accounts({
           listing: testListingPDA,
           coupon: testCouponPDA,
+          nftMint: testMint.publicKey,
+          sellerTokenAccount: testTokenAccount,
           seller: seller.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([seller])
         .rpc();
@@ -348,11 +355,15 @@ describe("Integration Tests", () => {
         .accounts({
           listing: testListingPDA,
           coupon: testCouponPDA,
+          nftMint: testMint.publicKey,
           marketplace: accounts.marketplacePDA,
           seller: seller.publicKey,
           buyer: buyer.publicKey,
           marketplaceAuthority: actualMarketplaceAuthority, // FIX: Use actual authority
+          merchant: testMerchantPDA,
+          merchantAuthority: testMerchant.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([buyer])
         .rpc();
@@ -361,9 +372,13 @@ describe("Integration Tests", () => {
       coupon = await program.account.coupon.fetch(testCouponPDA);
       assert.equal(coupon.owner.toString(), buyer.publicKey.toString());
 
-      // Verify listing deactivated
-      const listingFinal = await program.account.listing.fetch(testListingPDA);
-      assert.equal(listingFinal.isActive, false);
+      // Verify listing closed and NFT delivered
+      const listingFinal = await connection.getAccountInfo(testListingPDA);
+      assert.isNull(listingFinal);
+      const buyerTokenBalance = await connection.getTokenAccountBalance(
+        getAssociatedTokenAddressSync(testMint.publicKey, buyer.publicKey)
+      );
+      assert.equal(buyerTokenBalance.value.amount, "1");
 
       // Verify payments
       const sellerBalanceAfter = await connection.getBalance(seller.publicKey);
@@ -624,7 +639,7 @@ describe("Integration Tests", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(5000))
+        .mintCoupon(new BN(5000), null)
         .accounts({
           coupon: couponPDA,
           nftMint: mintKeypair.publicKey,
@@ -638,6 +653,8 @@ describe("Integration Tests", () => {
           userStats: dedicatedUserStatsPDA,
           payer: dedicatedUser.publicKey,
           authority: merchant.publicKey,
+          merchantAuthority: merchant.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -880,7 +897,7 @@ describe("Integration Tests", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(7000))
+        .mintCoupon(new BN(7000), null)
         .accounts({
           coupon: stakingCouponPDA,
           nftMint: stakingMint.publicKey,
@@ -894,6 +911,8 @@ describe("Integration Tests", () => {
           userStats: stakingUserStatsPDA,
           payer: stakingUser.publicKey,
           authority: stakingMerchant.publicKey,
+          merchantAuthority: stakingMerchant.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
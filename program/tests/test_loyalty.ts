@@ -0,0 +1,320 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+describe("Loyalty Stamps", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let promotionPDA: PublicKey;
+  let rewardPromotionPDA: PublicKey;
+  let loyaltyProgramPDA: PublicKey;
+  let loyaltyCardPDA: PublicKey;
+
+  const promotionAt = (index: number) =>
+    derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(index)],
+      program.programId
+    )[0];
+
+  const nextCoupon = async (promotion: PublicKey): Promise<[PublicKey, BN]> => {
+    const promotionAccount = await program.account.promotion.fetch(promotion);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotion.toBuffer(), u32ToLeBytes(promotionAccount.currentSupply)],
+      program.programId
+    );
+    return [coupon, new BN(promotionAccount.currentSupply + 1)];
+  };
+
+  const mintCoupon = async (): Promise<[PublicKey, Keypair]> => {
+    const [coupon, couponId] = await nextCoupon(promotionPDA);
+    const nftMint = Keypair.generate();
+    const marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+
+    await program.methods
+      .mintCoupon(couponId, null)
+      .accounts({
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+        metadata: deriveMetadataPDA(nftMint.publicKey)[0],
+        masterEdition: deriveMasterEditionPDA(nftMint.publicKey)[0],
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        recipient: accounts.user1.publicKey,
+        payer: accounts.user1.publicKey,
+        authority: accounts.merchant1.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        marketplaceAuthority: marketplace.authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([accounts.user1, nftMint, accounts.merchant1])
+      .rpc();
+
+    return [coupon, nftMint];
+  };
+
+  const redeem = (
+    coupon: PublicKey,
+    nftMint: Keypair,
+    promotion: PublicKey = promotionPDA,
+    loyaltyProgram: PublicKey | null = loyaltyProgramPDA
+  ) =>
+    program.methods
+      .redeemCoupon(null)
+      .accounts({
+        coupon,
+        promotion,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+        merchant: accounts.merchant1PDA,
+        loyaltyCard: loyaltyCardPDA,
+        loyaltyProgram,
+        metadata: deriveMetadataPDA(nftMint.publicKey)[0],
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        user: accounts.user1.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1, accounts.merchant1])
+      .rpc();
+
+  const claimReward = async (): Promise<[PublicKey, Keypair]> => {
+    const [coupon, couponId] = await nextCoupon(rewardPromotionPDA);
+    const nftMint = Keypair.generate();
+
+    await program.methods
+      .claimLoyaltyReward(couponId)
+      .accounts({
+        loyaltyCard: loyaltyCardPDA,
+        loyaltyProgram: loyaltyProgramPDA,
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+        metadata: deriveMetadataPDA(nftMint.publicKey)[0],
+        masterEdition: deriveMasterEditionPDA(nftMint.publicKey)[0],
+        promotion: rewardPromotionPDA,
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        user: accounts.user1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([accounts.user1, nftMint])
+      .rpc();
+
+    return [coupon, nftMint];
+  };
+
+  const setLoyaltyProgram = (stampsRequired: number, isActive: boolean) =>
+    program.methods
+      .setLoyaltyProgram(stampsRequired, isActive)
+      .accounts({
+        loyaltyProgram: loyaltyProgramPDA,
+        rewardPromotion: rewardPromotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+
+    await program.methods
+      .registerMerchant("Stamp Cafe", "cafe", null, null)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    promotionPDA = promotionAt(0);
+    rewardPromotionPDA = promotionAt(1);
+    for (const [promotion, description] of [
+      [promotionPDA, "Coffee"],
+      [rewardPromotionPDA, "Free coffee"],
+    ] as const) {
+      await program.methods
+        .createPromotion(promotion === promotionPDA ? 10 : 100, 20, getExpiryTimestamp(30), "cafe", description, new BN(0))
+        .accounts({
+          promotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+    }
+
+    [loyaltyProgramPDA] = derivePDA(
+      [Buffer.from("loyalty_program"), accounts.merchant1PDA.toBuffer()],
+      program.programId
+    );
+    [loyaltyCardPDA] = derivePDA(
+      [Buffer.from("loyalty_card"), accounts.merchant1PDA.toBuffer(), accounts.user1.publicKey.toBuffer()],
+      program.programId
+    );
+  });
+
+  it("Rejects a zero stamp threshold", async () => {
+    try {
+      await setLoyaltyProgram(0, true);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("InvalidStampThreshold");
+    }
+  });
+
+  it("Sets up the stamp program and a card", async () => {
+    await setLoyaltyProgram(2, true);
+    await program.methods
+      .openLoyaltyCard()
+      .accounts({
+        loyaltyCard: loyaltyCardPDA,
+        loyaltyProgram: loyaltyProgramPDA,
+        user: accounts.user1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1])
+      .rpc();
+
+    const loyaltyProgram = await program.account.loyaltyProgram.fetch(loyaltyProgramPDA);
+    assert.equal(loyaltyProgram.stampsRequired, 2);
+    assert.ok(loyaltyProgram.rewardPromotion.equals(rewardPromotionPDA));
+  });
+
+  it("Rejects a stamp without the loyalty program account", async () => {
+    const [coupon, nftMint] = await mintCoupon();
+
+    try {
+      await redeem(coupon, nftMint, promotionPDA, null);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("LoyaltyProgramRequired");
+    }
+    await redeem(coupon, nftMint);
+  });
+
+  it("Stamps the card on each redemption", async () => {
+    const [coupon, nftMint] = await mintCoupon();
+    await redeem(coupon, nftMint);
+
+    const card = await program.account.loyaltyCard.fetch(loyaltyCardPDA);
+    assert.equal(card.stamps, 2);
+    assert.equal(card.lifetimeStamps, 2);
+  });
+
+  it("Holds rewards while the program is paused", async () => {
+    await setLoyaltyProgram(2, false);
+    try {
+      await claimReward();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("LoyaltyProgramInactive");
+    }
+    await setLoyaltyProgram(2, true);
+  });
+
+  it("Claims a free reward coupon without the merchant", async () => {
+    const [coupon] = await claimReward();
+
+    const couponAccount = await program.account.coupon.fetch(coupon);
+    assert.ok(couponAccount.owner.equals(accounts.user1.publicKey));
+    assert.ok(couponAccount.promotion.equals(rewardPromotionPDA));
+
+    const card = await program.account.loyaltyCard.fetch(loyaltyCardPDA);
+    assert.equal(card.stamps, 0);
+    assert.equal(card.rewardsClaimed, 1);
+  });
+
+  it("Rejects a claim without enough stamps", async () => {
+    try {
+      await claimReward();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("NotEnoughStamps");
+    }
+  });
+
+  it("Earns no stamp for redeeming a reward coupon", async () => {
+    await setLoyaltyProgram(1, true);
+    const [coupon, nftMint] = await mintCoupon();
+    await redeem(coupon, nftMint);
+    const [reward, rewardMint] = await claimReward();
+
+    await redeem(reward, rewardMint, rewardPromotionPDA);
+
+    const card = await program.account.loyaltyCard.fetch(loyaltyCardPDA);
+    assert.equal(card.stamps, 0);
+    assert.equal(card.lifetimeStamps, 3);
+    await setLoyaltyProgram(2, true);
+  });
+
+  it("Stamps a punch card once, on its last use", async () => {
+    await program.methods
+      .setUsesPerCoupon(3)
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+    const [coupon, nftMint] = await mintCoupon();
+
+    for (let use = 1; use <= 3; use++) {
+      await redeem(coupon, nftMint);
+      const card = await program.account.loyaltyCard.fetch(loyaltyCardPDA);
+      assert.equal(card.stamps, use < 3 ? 0 : 1);
+    }
+  });
+});
@@ -1,9 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
 import { DiscountPlatform } from "../target/types/discount_platform";
-import { SystemProgram ,ComputeBudgetProgram } from "@solana/web3.js";
+import { SystemProgram ,ComputeBudgetProgram, Keypair } from "@solana/web3.js";
 import { assert, expect } from "chai";
-import { setupTestAccounts, TestAccounts } from "./setup";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  getExpiryTimestamp,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 
 describe("Marketplace Initialization", () => {
   const provider = anchor.AnchorProvider.env();
@@ -97,4 +108,266 @@ describe("Marketplace Initialization", () => {
       expect(error.message).to.include("already in use");
     }
   });
+
+  describe("Marketplace Admin", () => {
+    // Positive paths need the authority keypair, which we only hold if this file initialized the marketplace
+    it("Rejects admin calls from a non-authority", async () => {
+      try {
+        await program.methods
+          .setMarketplaceFee(100)
+          .accounts({ authority: accounts.user1.publicKey })
+          .signers([accounts.user1])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("NotMarketplaceAuthority");
+      }
+
+      try {
+        await program.methods
+          .setMarketplacePaused(true)
+          .accounts({ authority: accounts.user1.publicKey })
+          .signers([accounts.user1])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("NotMarketplaceAuthority");
+      }
+    });
+
+    it("Rejects accepting authority without a matching proposal", async () => {
+      try {
+        await program.methods
+          .acceptMarketplaceAuthority()
+          .accounts({ newAuthority: accounts.user2.publicKey })
+          .signers([accounts.user2])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("NotPendingAuthority");
+      }
+    });
+
+    it("Updates the fee within the cap", async function() {
+      if (marketplaceWasAlreadyInitialized) {
+        this.skip();
+        return;
+      }
+
+      await program.methods
+        .setMarketplaceFee(500)
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      let marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      assert.equal(marketplace.feeBasisPoints, 500);
+
+      try {
+        await program.methods
+          .setMarketplaceFee(1001)
+          .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+          .signers([accounts.marketplaceAuthority])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("FeeTooHigh");
+      }
+
+      // Restore the default for the other suites
+      await program.methods
+        .setMarketplaceFee(250)
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      assert.equal(marketplace.feeBasisPoints, 250);
+    });
+
+    it("Pauses and unpauses trading", async function() {
+      if (marketplaceWasAlreadyInitialized) {
+        this.skip();
+        return;
+      }
+
+      await program.methods
+        .setMarketplacePaused(true)
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      let marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      assert.isTrue(marketplace.paused);
+
+      // Minting is one of the paused entry points
+      await program.methods
+        .registerMerchant("Paused Shop", "retail", null, null)
+        .accounts({
+          merchant: accounts.merchant2PDA,
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.merchant2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant2])
+        .rpc();
+      const [promotionPDA] = derivePDA(
+        [Buffer.from("promotion"), accounts.merchant2PDA.toBuffer(), u64ToLeBytes(0)],
+        program.programId
+      );
+      await program.methods
+        .createPromotion(10, 10, getExpiryTimestamp(30), "retail", "Paused promotion", new BN(0))
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant2PDA,
+          authority: accounts.merchant2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant2])
+        .rpc();
+
+      const [couponPDA] = derivePDA(
+        [Buffer.from("coupon"), promotionPDA.toBuffer(), u32ToLeBytes(0)],
+        program.programId
+      );
+      const couponMint = Keypair.generate();
+      try {
+        await program.methods
+          .mintCoupon(new BN(1), null)
+          .accounts({
+            coupon: couponPDA,
+            nftMint: couponMint.publicKey,
+            tokenAccount: getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user1.publicKey),
+            metadata: deriveMetadataPDA(couponMint.publicKey)[0],
+            masterEdition: deriveMasterEditionPDA(couponMint.publicKey)[0],
+            promotion: promotionPDA,
+            merchant: accounts.merchant2PDA,
+            marketplace: accounts.marketplacePDA,
+            recipient: accounts.user1.publicKey,
+            payer: accounts.user1.publicKey,
+            authority: accounts.merchant2.publicKey,
+            merchantAuthority: accounts.merchant2.publicKey,
+            marketplaceAuthority: accounts.marketplaceAuthority.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([accounts.user1, couponMint, accounts.merchant2])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("MarketplacePaused");
+      }
+
+      await program.methods
+        .setMarketplacePaused(false)
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      assert.isFalse(marketplace.paused);
+    });
+
+    it("Hands authority over in two steps", async function() {
+      if (marketplaceWasAlreadyInitialized) {
+        this.skip();
+        return;
+      }
+
+      await program.methods
+        .proposeMarketplaceAuthority(accounts.user1.publicKey)
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      let marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      assert.equal(marketplace.pendingAuthority.toString(), accounts.user1.publicKey.toString());
+      assert.equal(marketplace.authority.toString(), accounts.marketplaceAuthority.publicKey.toString());
+
+      await program.methods
+        .acceptMarketplaceAuthority()
+        .accounts({ newAuthority: accounts.user1.publicKey })
+        .signers([accounts.user1])
+        .rpc();
+      marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      assert.equal(marketplace.authority.toString(), accounts.user1.publicKey.toString());
+      assert.isNull(marketplace.pendingAuthority);
+
+      // Hand it back so later suites see the original authority
+      await program.methods
+        .proposeMarketplaceAuthority(accounts.marketplaceAuthority.publicKey)
+        .accounts({ authority: accounts.user1.publicKey })
+        .signers([accounts.user1])
+        .rpc();
+      await program.methods
+        .acceptMarketplaceAuthority()
+        .accounts({ newAuthority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+      assert.equal(marketplace.authority.toString(), accounts.marketplaceAuthority.publicKey.toString());
+    });
+  });
+
+  describe("Platform Config", () => {
+    const [platformConfigPDA] = derivePDA([Buffer.from("platform_config")], program.programId);
+
+    it("Is created by initialize with the default settings", async () => {
+      const config = await program.account.platformConfig.fetch(platformConfigPDA);
+      assert.isAtLeast(config.version, 1);
+      assert.isAbove(config.settings.ticketExpirySeconds.toNumber(), 0);
+      assert.isAtMost(
+        config.settings.auctionMinDuration.toNumber(),
+        config.settings.auctionMaxDuration.toNumber()
+      );
+    });
+
+    it("Rejects updates from a non-authority", async () => {
+      const config = await program.account.platformConfig.fetch(platformConfigPDA);
+      try {
+        await program.methods
+          .updatePlatformConfig(config.settings)
+          .accounts({ authority: accounts.user1.publicKey })
+          .signers([accounts.user1])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("NotMarketplaceAuthority");
+      }
+    });
+
+    it("Updates settings and bumps the version", async function() {
+      if (marketplaceWasAlreadyInitialized) {
+        this.skip();
+        return;
+      }
+
+      const before = await program.account.platformConfig.fetch(platformConfigPDA);
+      const defaults = before.settings;
+
+      await program.methods
+        .updatePlatformConfig({ ...defaults, ticketExpirySeconds: new BN(600) })
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      let config = await program.account.platformConfig.fetch(platformConfigPDA);
+      assert.equal(config.version, before.version + 1);
+      assert.equal(config.settings.ticketExpirySeconds.toNumber(), 600);
+
+      try {
+        await program.methods
+          .updatePlatformConfig({ ...defaults, auctionMinDuration: new BN(700000) })
+          .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+          .signers([accounts.marketplaceAuthority])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidConfig");
+      }
+
+      // Restore the defaults for the other suites
+      await program.methods
+        .updatePlatformConfig(defaults)
+        .accounts({ authority: accounts.marketplaceAuthority.publicKey })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+      config = await program.account.platformConfig.fetch(platformConfigPDA);
+      assert.equal(config.settings.ticketExpirySeconds.toNumber(), defaults.ticketExpirySeconds.toNumber());
+    });
+  });
 });
\ No newline at end of file
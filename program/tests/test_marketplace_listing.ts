@@ -30,7 +30,7 @@ describe("Marketplace Listing & Trading", () => {
   let promotionPDA: PublicKey;
   let couponPDA: PublicKey;
   let listingPDA: PublicKey;
-  let couponMint: Keypair;
+  let couponMintKey: PublicKey;
   const listingPrice = new BN(3 * LAMPORTS_PER_SOL);
 
   // Helper function to mint coupons with correct PDA derivation
@@ -61,7 +61,7 @@ describe("Marketplace Listing & Trading", () => {
     );
 
     await program.methods
-      .mintCoupon(couponIdForDisplay)
+      .mintCoupon(couponIdForDisplay, null)
       .accounts({
         coupon: couponPDA,
         nftMint: newMint.publicKey,
@@ -74,6 +74,8 @@ describe("Marketplace Listing & Trading", () => {
         recipient: recipient.publicKey,
         payer: recipient.publicKey,
         authority: accounts.merchant1.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -185,6 +187,7 @@ describe("Marketplace Listing & Trading", () => {
             !testCoupon.isRedeemed) {
           console.log(`✓ Found existing coupon #${i} for user1`);
           couponPDA = testPDA;
+          couponMintKey = testCoupon.mint;
           foundCoupon = true;
           break;
         }
@@ -198,7 +201,7 @@ describe("Marketplace Listing & Trading", () => {
       console.log("Minting new coupon for user1...");
       const result = await mintCouponForTest(accounts.user1, new BN(0));
       couponPDA = result.couponPDA;
-      couponMint = result.mint;
+      couponMintKey = result.mint.publicKey;
       console.log("✓ New coupon minted");
     }
 
@@ -215,8 +218,11 @@ describe("Marketplace Listing & Trading", () => {
         .accounts({
           listing: listingPDA,
           coupon: couponPDA,
+          nftMint: couponMintKey,
+          sellerTokenAccount: getAssociatedTokenAddressSync(couponMintKey, accounts.user1.publicKey),
           seller: accounts.user1.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
@@ -228,10 +234,22 @@ describe("Marketplace Listing & Trading", () => {
       assert.equal(listing.price.toString(), listingPrice.toString());
       assert.equal(listing.isActive, true);
       assert.isAbove(listing.createdAt.toNumber(), 0);
+
+      // NFT is escrowed in the listing vault
+      const [listingVault] = derivePDA(
+        [Buffer.from("listing_vault"), listingPDA.toBuffer()],
+        program.programId
+      );
+      const vaultBalance = await connection.getTokenAccountBalance(listingVault);
+      assert.equal(vaultBalance.value.amount, "1");
+      const sellerBalance = await connection.getTokenAccountBalance(
+        getAssociatedTokenAddressSync(couponMintKey, accounts.user1.publicKey)
+      );
+      assert.equal(sellerBalance.value.amount, "0");
     });
 
     it("Fails to list with zero price", async () => {
-      const { couponPDA: testCouponPDA } = await mintCouponForTest(
+      const { couponPDA: testCouponPDA, mint: testMint } = await mintCouponForTest(
         accounts.user1,
         new BN(1)
       );
@@ -247,8 +265,11 @@ describe("Marketplace Listing & Trading", () => {
           .accounts({
             listing: testListingPDA,
             coupon: testCouponPDA,
+            nftMint: testMint.publicKey,
+            sellerTokenAccount: getAssociatedTokenAddressSync(testMint.publicKey, accounts.user1.publicKey),
             seller: accounts.user1.publicKey,
             systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([accounts.user1])
           .rpc();
@@ -260,7 +281,7 @@ describe("Marketplace Listing & Trading", () => {
     });
 
     it("Fails to list when not owner", async () => {
-      const { couponPDA: user2CouponPDA } = await mintCouponForTest(
+      const { couponPDA: user2CouponPDA, mint: user2Mint } = await mintCouponForTest(
         accounts.user2,
         new BN(2)
       );
@@ -282,8 +303,11 @@ describe("Marketplace Listing & Trading", () => {
           .accounts({
             listing: user2ListingPDA,
             coupon: user2CouponPDA,
+            nftMint: user2Mint.publicKey,
+            sellerTokenAccount: getAssociatedTokenAddressSync(user2Mint.publicKey, accounts.user2.publicKey),
             seller: accounts.user1.publicKey,
             systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([accounts.user1])
           .rpc();
@@ -306,7 +330,7 @@ describe("Marketplace Listing & Trading", () => {
       );
 
       await program.methods
-        .redeemCoupon()
+        .redeemCoupon(null)
         .accounts({
           coupon: redeemedCouponPDA,
           nftMint: newMint.publicKey,
@@ -330,8 +354,11 @@ describe("Marketplace Listing & Trading", () => {
           .accounts({
             listing: redeemedListingPDA,
             coupon: redeemedCouponPDA,
+            nftMint: newMint.publicKey,
+            sellerTokenAccount: tokenAccount,
             seller: accounts.user1.publicKey,
             systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([accounts.user1])
           .rpc();
@@ -364,11 +391,15 @@ describe("Marketplace Listing & Trading", () => {
         .accounts({
           listing: listingPDA,
           coupon: couponPDA,
+          nftMint: couponMintKey,
           marketplace: accounts.marketplacePDA,
           seller: accounts.user1.publicKey,
           buyer: accounts.user2.publicKey,
           marketplaceAuthority: actualMarketplaceAuthority, // FIX: Use actual authority
+          merchant: accounts.merchant1PDA,
+          merchantAuthority: accounts.merchant1.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user2])
         .rpc();
@@ -376,8 +407,13 @@ describe("Marketplace Listing & Trading", () => {
       const coupon = await program.account.coupon.fetch(couponPDA);
       assert.equal(coupon.owner.toString(), accounts.user2.publicKey.toString());
 
-      const listing = await program.account.listing.fetch(listingPDA);
-      assert.equal(listing.isActive, false);
+      // Listing is closed and the NFT delivered to the buyer
+      const listingInfo = await connection.getAccountInfo(listingPDA);
+      assert.isNull(listingInfo);
+      const buyerBalance = await connection.getTokenAccountBalance(
+        getAssociatedTokenAddressSync(couponMintKey, accounts.user2.publicKey)
+      );
+      assert.equal(buyerBalance.value.amount, "1");
 
       const sellerBalanceAfter = await connection.getBalance(accounts.user1.publicKey);
       const marketplaceAuthorityBalanceAfter = await connection.getBalance(
@@ -402,11 +438,15 @@ describe("Marketplace Listing & Trading", () => {
           .accounts({
             listing: listingPDA,
             coupon: couponPDA,
+            nftMint: couponMintKey,
             marketplace: accounts.marketplacePDA,
             seller: accounts.user2.publicKey, // FIX: Now owned by user2
             buyer: accounts.user1.publicKey,
             marketplaceAuthority: actualMarketplaceAuthority, // FIX: Use actual authority
+            merchant: accounts.merchant1PDA,
+            merchantAuthority: accounts.merchant1.publicKey,
             systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([accounts.user1])
           .rpc();
@@ -418,6 +458,7 @@ describe("Marketplace Listing & Trading", () => {
         const hasExpectedError = 
           errorStr.includes("listinginactive") || 
           errorStr.includes("inactive") ||
+          errorStr.includes("accountnotinitialized") ||
           errorStr.includes("constraint");
         
         expect(hasExpectedError).to.be.true;
@@ -425,7 +466,7 @@ describe("Marketplace Listing & Trading", () => {
     });
 
     it("Fails to buy with insufficient funds", async () => {
-      const { couponPDA: highPriceCouponPDA } = await mintCouponForTest(
+      const { couponPDA: highPriceCouponPDA, mint: highPriceMint } = await mintCouponForTest(
         accounts.user1,
         new BN(4)
       );
@@ -442,8 +483,11 @@ describe("Marketplace Listing & Trading", () => {
         .accounts({
           listing: highPriceListingPDA,
           coupon: highPriceCouponPDA,
+          nftMint: highPriceMint.publicKey,
+          sellerTokenAccount: getAssociatedTokenAddressSync(highPriceMint.publicKey, accounts.user1.publicKey),
           seller: accounts.user1.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
@@ -462,11 +506,15 @@ describe("Marketplace Listing & Trading", () => {
           .accounts({
             listing: highPriceListingPDA,
             coupon: highPriceCouponPDA,
+            nftMint: highPriceMint.publicKey,
             marketplace: accounts.marketplacePDA,
             seller: accounts.user1.publicKey,
             buyer: poorUser.publicKey,
             marketplaceAuthority: actualMarketplaceAuthority, // FIX: Use actual authority
+            merchant: accounts.merchant1PDA,
+            merchantAuthority: accounts.merchant1.publicKey,
             systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([poorUser])
           .rpc();
@@ -480,7 +528,8 @@ describe("Marketplace Listing & Trading", () => {
 
   describe("Cancel Listing", () => {
     it("Cancels listing successfully", async () => {
-      const { couponPDA: newCouponPDA } = await mintCouponForTest(accounts.user1, new BN(5));
+      const { couponPDA: newCouponPDA, mint: newMint } = await mintCouponForTest(accounts.user1, new BN(5));
+      const sellerTokenAccount = getAssociatedTokenAddressSync(newMint.publicKey, accounts.user1.publicKey);
 
       const [newListingPDA] = derivePDA(
         [Buffer.from("listing"), newCouponPDA.toBuffer()],
@@ -492,8 +541,11 @@ describe("Marketplace Listing & Trading", () => {
         .accounts({
           listing: newListingPDA,
           coupon: newCouponPDA,
+          nftMint: newMint.publicKey,
+          sellerTokenAccount,
           seller: accounts.user1.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
@@ -506,17 +558,25 @@ describe("Marketplace Listing & Trading", () => {
         .cancelListing()
         .accounts({
           listing: newListingPDA,
+          coupon: newCouponPDA,
+          nftMint: newMint.publicKey,
+          sellerTokenAccount,
           seller: accounts.user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
 
-      const listingAfter = await program.account.listing.fetch(newListingPDA);
-      assert.equal(listingAfter.isActive, false);
+      // Listing is closed and the NFT is back with the seller
+      const listingAfter = await connection.getAccountInfo(newListingPDA);
+      assert.isNull(listingAfter);
+      const sellerBalance = await connection.getTokenAccountBalance(sellerTokenAccount);
+      assert.equal(sellerBalance.value.amount, "1");
     });
 
     it("Fails to cancel listing by non-seller", async () => {
-      const { couponPDA: newCouponPDA } = await mintCouponForTest(accounts.user1, new BN(6));
+      const { couponPDA: newCouponPDA, mint: newMint } = await mintCouponForTest(accounts.user1, new BN(6));
+      const sellerTokenAccount = getAssociatedTokenAddressSync(newMint.publicKey, accounts.user1.publicKey);
 
       const [newListingPDA] = derivePDA(
         [Buffer.from("listing"), newCouponPDA.toBuffer()],
@@ -528,8 +588,11 @@ describe("Marketplace Listing & Trading", () => {
         .accounts({
           listing: newListingPDA,
           coupon: newCouponPDA,
+          nftMint: newMint.publicKey,
+          sellerTokenAccount,
           seller: accounts.user1.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
@@ -539,7 +602,11 @@ describe("Marketplace Listing & Trading", () => {
           .cancelListing()
           .accounts({
             listing: newListingPDA,
+            coupon: newCouponPDA,
+            nftMint: newMint.publicKey,
+            sellerTokenAccount,
             seller: accounts.user2.publicKey, // Wrong seller
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([accounts.user2])
           .rpc();
@@ -551,7 +618,8 @@ describe("Marketplace Listing & Trading", () => {
     });
 
     it("Fails to cancel inactive listing", async () => {
-      const { couponPDA: newCouponPDA } = await mintCouponForTest(accounts.user1, new BN(7));
+      const { couponPDA: newCouponPDA, mint: newMint } = await mintCouponForTest(accounts.user1, new BN(7));
+      const sellerTokenAccount = getAssociatedTokenAddressSync(newMint.publicKey, accounts.user1.publicKey);
 
       const [newListingPDA] = derivePDA(
         [Buffer.from("listing"), newCouponPDA.toBuffer()],
@@ -563,8 +631,11 @@ describe("Marketplace Listing & Trading", () => {
         .accounts({
           listing: newListingPDA,
           coupon: newCouponPDA,
+          nftMint: newMint.publicKey,
+          sellerTokenAccount,
           seller: accounts.user1.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
@@ -574,7 +645,11 @@ describe("Marketplace Listing & Trading", () => {
         .cancelListing()
         .accounts({
           listing: newListingPDA,
+          coupon: newCouponPDA,
+          nftMint: newMint.publicKey,
+          sellerTokenAccount,
           seller: accounts.user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([accounts.user1])
         .rpc();
@@ -585,14 +660,19 @@ describe("Marketplace Listing & Trading", () => {
           .cancelListing()
           .accounts({
             listing: newListingPDA,
+            coupon: newCouponPDA,
+            nftMint: newMint.publicKey,
+            sellerTokenAccount,
             seller: accounts.user1.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([accounts.user1])
           .rpc();
         
         assert.fail("Should have thrown an error");
       } catch (error) {
-        expect(error.message).to.include("ListingInactive");
+        // The listing account is closed on cancel
+        expect(error.message).to.include("AccountNotInitialized");
       }
     });
   });
@@ -146,4 +146,55 @@ describe("Merchant Registration", () => {
       expect(error.message).to.include("InvalidCoordinates");
     }
   });
-});
\ No newline at end of file
+
+  it("Sets a resale royalty within the platform cap", async () => {
+    await program.methods
+      .setMerchantRoyalty(250)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+    assert.equal(merchant.royaltyBasisPoints, 250);
+  });
+
+  it("Fails to set a royalty above the platform cap", async () => {
+    const [platformConfigPDA] = derivePDA([Buffer.from("platform_config")], program.programId);
+    const config = await program.account.platformConfig.fetch(platformConfigPDA);
+
+    try {
+      await program.methods
+        .setMerchantRoyalty(config.settings.maxRoyaltyBasisPoints + 1)
+        .accounts({
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      assert.fail("Should have thrown an error");
+    } catch (error: any) {
+      expect(error.message).to.include("RoyaltyTooHigh");
+    }
+  });
+
+  it("Fails to set a royalty for another merchant", async () => {
+    try {
+      await program.methods
+        .setMerchantRoyalty(100)
+        .accounts({
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant2.publicKey,
+        })
+        .signers([accounts.merchant2])
+        .rpc();
+
+      assert.fail("Should have thrown an error");
+    } catch (error: any) {
+      expect(error.message).to.include("NotMerchantAuthority");
+    }
+  });
+});
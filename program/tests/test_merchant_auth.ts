@@ -0,0 +1,535 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  getCurrentTimestamp,
+  wait,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  LAMPORTS_PER_SOL,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+describe("Merchant Authorization", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let marketplaceAuthority: PublicKey;
+  let promotion1PDA: PublicKey;
+  let promotion2PDA: PublicKey;
+
+  const promotionFor = (merchantPDA: PublicKey) =>
+    derivePDA(
+      [Buffer.from("promotion"), merchantPDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    )[0];
+
+  const createPromotion = (promotion: PublicKey, merchantPDA: PublicKey, signer: Keypair) =>
+    program.methods
+      .createPromotion(25, 10, getExpiryTimestamp(30), "food", "Auth test", new BN(0.1 * LAMPORTS_PER_SOL))
+      .accounts({
+        promotion,
+        merchant: merchantPDA,
+        authority: signer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+
+  const mintCoupon = async (
+    promotion: PublicKey,
+    merchantPDA: PublicKey,
+    merchantSigner: Keypair,
+    recipient: Keypair,
+    merchantStaff: PublicKey | null = null
+  ): Promise<[PublicKey, Keypair]> => {
+    const promotionAccount = await program.account.promotion.fetch(promotion);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotion.toBuffer(), u32ToLeBytes(promotionAccount.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+    const [metadata] = deriveMetadataPDA(nftMint.publicKey);
+    const [masterEdition] = deriveMasterEditionPDA(nftMint.publicKey);
+    const merchant = await program.account.merchant.fetch(merchantPDA);
+
+    await program.methods
+      .mintCoupon(new BN(promotionAccount.currentSupply + 1), null)
+      .accounts({
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, recipient.publicKey),
+        metadata,
+        masterEdition,
+        promotion,
+        merchant: merchantPDA,
+        merchantStaff,
+        marketplace: accounts.marketplacePDA,
+        recipient: recipient.publicKey,
+        payer: recipient.publicKey,
+        authority: merchantSigner.publicKey,
+        merchantAuthority: merchant.authority,
+        marketplaceAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([recipient, nftMint, merchantSigner])
+      .rpc();
+
+    return [coupon, nftMint];
+  };
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+    marketplaceAuthority = (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority;
+
+    for (const [merchant, merchantPDA] of [
+      [accounts.merchant1, accounts.merchant1PDA],
+      [accounts.merchant2, accounts.merchant2PDA],
+    ] as const) {
+      await program.methods
+        .registerMerchant("Auth Bistro", "restaurant", null, null)
+        .accounts({
+          merchant: merchantPDA,
+          marketplace: accounts.marketplacePDA,
+          authority: merchant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([merchant])
+        .rpc();
+    }
+
+    promotion1PDA = promotionFor(accounts.merchant1PDA);
+    promotion2PDA = promotionFor(accounts.merchant2PDA);
+  });
+
+  it("Rejects a promotion created by a foreign signer", async () => {
+    try {
+      await createPromotion(promotion1PDA, accounts.merchant1PDA, accounts.merchant2);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("NotMerchantAuthority");
+    }
+    assert.isFalse(await accountExists(connection, promotion1PDA));
+
+    // The real owners can still create theirs
+    await createPromotion(promotion1PDA, accounts.merchant1PDA, accounts.merchant1);
+    await createPromotion(promotion2PDA, accounts.merchant2PDA, accounts.merchant2);
+  });
+
+  it("Rejects a mint signed by another merchant", async () => {
+    try {
+      await mintCoupon(promotion1PDA, accounts.merchant1PDA, accounts.merchant2, accounts.user1);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("NotMerchantAuthority");
+    }
+  });
+
+  it("Rejects a mint against another merchant's promotion", async () => {
+    try {
+      await mintCoupon(promotion2PDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("WrongMerchant");
+    }
+  });
+
+  it("Rejects a redemption signed by a foreign key", async () => {
+    const [coupon, nftMint] = await mintCoupon(promotion1PDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+
+    try {
+      await program.methods
+        .redeemCoupon(null)
+        .accounts({
+          coupon,
+          nftMint: nftMint.publicKey,
+          tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+          merchant: accounts.merchant1PDA,
+          user: accounts.user1.publicKey,
+          merchantAuthority: accounts.merchant2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.user1, accounts.merchant2])
+        .rpc();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("NotMerchantAuthority");
+    }
+
+    const couponAccount = await program.account.coupon.fetch(coupon);
+    assert.equal(couponAccount.isRedeemed, false);
+  });
+
+  it("Rejects promotion management through another merchant account", async () => {
+    try {
+      await program.methods
+        .setPromotionActive(false)
+        .accounts({
+          promotion: promotion1PDA,
+          merchant: accounts.merchant2PDA,
+          authority: accounts.merchant2.publicKey,
+        })
+        .signers([accounts.merchant2])
+        .rpc();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("WrongMerchant");
+    }
+  });
+
+  describe("Merchant Staff", () => {
+    const ROLE_REDEEM = 1 << 0;
+    const ALL_ROLES = 0b1111;
+
+    const cashier = Keypair.generate();
+    const manager = Keypair.generate();
+    let staffPDA: PublicKey;
+    let managerPDA: PublicKey;
+
+    const redeemAs = (coupon: PublicKey, nftMint: Keypair, signer: Keypair, merchantStaff: PublicKey | null) =>
+      program.methods
+        .redeemCoupon(null)
+        .accounts({
+          coupon,
+          nftMint: nftMint.publicKey,
+          tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+          merchant: accounts.merchant1PDA,
+          merchantStaff,
+          user: accounts.user1.publicKey,
+          merchantAuthority: signer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.user1, signer])
+        .rpc();
+
+    before(() => {
+      [staffPDA] = derivePDA(
+        [Buffer.from("merchant_staff"), accounts.merchant1PDA.toBuffer(), cashier.publicKey.toBuffer()],
+        program.programId
+      );
+      [managerPDA] = derivePDA(
+        [Buffer.from("merchant_staff"), accounts.merchant1PDA.toBuffer(), manager.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Rejects staff added by a foreign signer", async () => {
+      try {
+        await program.methods
+          .addMerchantStaff(cashier.publicKey, ROLE_REDEEM, null)
+          .accounts({
+            merchantStaff: staffPDA,
+            merchant: accounts.merchant1PDA,
+            authority: accounts.merchant2.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([accounts.merchant2])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+    });
+
+    it("Rejects an empty role set", async () => {
+      try {
+        await program.methods
+          .addMerchantStaff(cashier.publicKey, 0, null)
+          .accounts({
+            merchantStaff: staffPDA,
+            merchant: accounts.merchant1PDA,
+            authority: accounts.merchant1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([accounts.merchant1])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidStaffRoles");
+      }
+    });
+
+    it("Adds a cashier with the redeem role", async () => {
+      await program.methods
+        .addMerchantStaff(cashier.publicKey, ROLE_REDEEM, null)
+        .accounts({
+          merchantStaff: staffPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      const staff = await program.account.merchantStaff.fetch(staffPDA);
+      assert.ok(staff.merchant.equals(accounts.merchant1PDA));
+      assert.ok(staff.staff.equals(cashier.publicKey));
+      assert.equal(staff.roles, ROLE_REDEEM);
+      assert.isNull(staff.expiresAt);
+    });
+
+    it("Rejects a cashier redeeming without its staff account", async () => {
+      const [coupon, nftMint] = await mintCoupon(promotion1PDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+
+      try {
+        await redeemAs(coupon, nftMint, cashier, null);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+    });
+
+    it("Lets the cashier redeem a coupon", async () => {
+      const [coupon, nftMint] = await mintCoupon(promotion1PDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+
+      await redeemAs(coupon, nftMint, cashier, staffPDA);
+
+      const couponAccount = await program.account.coupon.fetch(coupon);
+      assert.equal(couponAccount.isRedeemed, true);
+    });
+
+    it("Rejects the cashier creating a promotion without that role", async () => {
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      const [promotion] = derivePDA(
+        [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated.toNumber())],
+        program.programId
+      );
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(cashier.publicKey, LAMPORTS_PER_SOL)
+      );
+
+      try {
+        await program.methods
+          .createPromotion(15, 10, getExpiryTimestamp(30), "food", "Cashier promo", new BN(0))
+          .accounts({
+            promotion,
+            merchant: accounts.merchant1PDA,
+            merchantStaff: staffPDA,
+            authority: cashier.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([cashier])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+    });
+
+    it("Rejects the cashier pausing a promotion without the promotion role", async () => {
+      try {
+        await program.methods
+          .setPromotionActive(false)
+          .accounts({
+            promotion: promotion1PDA,
+            merchant: accounts.merchant1PDA,
+            merchantStaff: staffPDA,
+            authority: cashier.publicKey,
+          })
+          .signers([cashier])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+    });
+
+    it("Rejects the cashier opening a group deal without the listings role", async () => {
+      const dealId = new BN(1);
+      const [groupDeal] = derivePDA(
+        [Buffer.from("group_deal"), promotion1PDA.toBuffer(), u64ToLeBytes(dealId)],
+        program.programId
+      );
+      const [escrowVault] = derivePDA(
+        [Buffer.from("group_escrow"), groupDeal.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createGroupDeal(dealId, 2, 5, new BN(LAMPORTS_PER_SOL), [], new BN(86400))
+          .accounts({
+            groupDeal,
+            escrowVault,
+            promotion: promotion1PDA,
+            merchant: accounts.merchant1PDA,
+            merchantStaff: staffPDA,
+            organizer: cashier.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([cashier])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+      assert.isFalse(await accountExists(connection, groupDeal));
+    });
+
+    it("Rejects the cashier minting a coupon without the issue role", async () => {
+      try {
+        await mintCoupon(promotion1PDA, accounts.merchant1PDA, cashier, accounts.user1, staffPDA);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+    });
+
+    it("Lets a manager with every role mint a coupon", async () => {
+      await program.methods
+        .addMerchantStaff(manager.publicKey, ALL_ROLES, null)
+        .accounts({
+          merchantStaff: managerPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(manager.publicKey, LAMPORTS_PER_SOL)
+      );
+
+      const [coupon] = await mintCoupon(promotion1PDA, accounts.merchant1PDA, manager, accounts.user1, managerPDA);
+
+      const couponAccount = await program.account.coupon.fetch(coupon);
+      assert.ok(couponAccount.owner.equals(accounts.user1.publicKey));
+    });
+
+    it("Keeps the royalty owner-only", async () => {
+      try {
+        await program.methods
+          .setMerchantRoyalty(100)
+          .accounts({
+            merchant: accounts.merchant1PDA,
+            authority: manager.publicKey,
+          })
+          .signers([manager])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+    });
+
+    it("Keeps branches owner-only", async () => {
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      const [branch] = derivePDA(
+        [Buffer.from("branch"), accounts.merchant1PDA.toBuffer(), u32ToLeBytes(merchant.branchCount)],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .addMerchantBranch("Annex", 40.7128, -74.006)
+          .accounts({
+            branch,
+            merchant: accounts.merchant1PDA,
+            authority: manager.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([manager])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+      assert.isFalse(await accountExists(connection, branch));
+    });
+
+    it("Keeps closing a promotion owner-only", async () => {
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      const [promotion] = derivePDA(
+        [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated.toNumber())],
+        program.programId
+      );
+      await program.methods
+        .createPromotion(15, 10, new BN(getCurrentTimestamp() + 3), "food", "Manager promo", new BN(0))
+        .accounts({
+          promotion,
+          merchant: accounts.merchant1PDA,
+          merchantStaff: managerPDA,
+          authority: manager.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([manager])
+        .rpc();
+      await wait(5000);
+
+      try {
+        await program.methods
+          .closePromotion()
+          .accounts({
+            promotion,
+            merchant: accounts.merchant1PDA,
+            authority: manager.publicKey,
+          })
+          .signers([manager])
+          .rpc();
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotMerchantAuthority");
+      }
+
+      await program.methods
+        .closePromotion()
+        .accounts({
+          promotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+      assert.isFalse(await accountExists(connection, promotion));
+    });
+
+    it("Revokes the cashier", async () => {
+      await program.methods
+        .revokeMerchantStaff()
+        .accounts({
+          merchantStaff: staffPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      assert.isFalse(await accountExists(connection, staffPDA));
+    });
+  });
+});
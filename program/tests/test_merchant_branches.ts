@@ -0,0 +1,392 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  getCurrentTimestamp,
+  geoCellId,
+  deriveGeoCellPDA,
+  deriveGeoCellEntryPDA,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  LAMPORTS_PER_SOL,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+describe("Merchant Branches", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let marketplaceAuthority: PublicKey;
+  let promotionPDA: PublicKey;
+
+  const promotionFor = (merchantPDA: PublicKey) =>
+    derivePDA(
+      [Buffer.from("promotion"), merchantPDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    )[0];
+
+  const createPromotion = (promotion: PublicKey, merchantPDA: PublicKey, signer: Keypair) =>
+    program.methods
+      .createPromotion(25, 10, getExpiryTimestamp(30), "food", "Auth test", new BN(0.1 * LAMPORTS_PER_SOL))
+      .accounts({
+        promotion,
+        merchant: merchantPDA,
+        authority: signer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+
+  const mintCoupon = async (
+    promotion: PublicKey,
+    merchantPDA: PublicKey,
+    merchantSigner: Keypair,
+    recipient: Keypair
+  ): Promise<[PublicKey, Keypair]> => {
+    const promotionAccount = await program.account.promotion.fetch(promotion);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotion.toBuffer(), u32ToLeBytes(promotionAccount.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+    const [metadata] = deriveMetadataPDA(nftMint.publicKey);
+    const [masterEdition] = deriveMasterEditionPDA(nftMint.publicKey);
+    const merchant = await program.account.merchant.fetch(merchantPDA);
+
+    await program.methods
+      .mintCoupon(new BN(promotionAccount.currentSupply + 1), null)
+      .accounts({
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, recipient.publicKey),
+        metadata,
+        masterEdition,
+        promotion,
+        merchant: merchantPDA,
+        marketplace: accounts.marketplacePDA,
+        recipient: recipient.publicKey,
+        payer: recipient.publicKey,
+        authority: merchantSigner.publicKey,
+        merchantAuthority: merchant.authority,
+        marketplaceAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([recipient, nftMint, merchantSigner])
+      .rpc();
+
+    return [coupon, nftMint];
+  };
+
+  const branchFor = (index: number) =>
+    derivePDA(
+      [Buffer.from("branch"), accounts.merchant1PDA.toBuffer(), u32ToLeBytes(index)],
+      program.programId
+    )[0];
+
+  const addBranch = (index: number, name: string, latitude: number, longitude: number, signer: Keypair) =>
+    program.methods
+      .addMerchantBranch(name, latitude, longitude)
+      .accounts({
+        branch: branchFor(index),
+        merchant: accounts.merchant1PDA,
+        authority: signer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+
+  const redeemAt = (coupon: PublicKey, nftMint: Keypair, branch: PublicKey | null) =>
+    program.methods
+      .redeemCoupon(null)
+      .accounts({
+        coupon,
+        promotion: promotionPDA,
+        branch,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+        merchant: accounts.merchant1PDA,
+        user: accounts.user1.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1, accounts.merchant1])
+      .rpc();
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+    marketplaceAuthority = (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority;
+
+    await program.methods
+      .registerMerchant("Branch Chain", "restaurant", null, null)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    [promotionPDA] = derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    );
+    await program.methods
+      .createPromotion(20, 10, getExpiryTimestamp(30), "food", "Branch promo", new BN(0.1 * LAMPORTS_PER_SOL))
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+  });
+
+  it("Adds branches at consecutive indices", async () => {
+    await addBranch(0, "Downtown", 40.7128, -74.006, accounts.merchant1);
+    await addBranch(1, "Airport", 40.6413, -73.7781, accounts.merchant1);
+
+    const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+    assert.equal(merchant.branchCount, 2);
+
+    const branch = await program.account.merchantBranch.fetch(branchFor(1));
+    assert.ok(branch.merchant.equals(accounts.merchant1PDA));
+    assert.equal(branch.index, 1);
+    assert.equal(branch.name, "Airport");
+    assert.equal(branch.location.latitude, 40641300);
+    assert.isTrue(branch.isActive);
+  });
+
+  it("Rejects a branch added by a foreign signer", async () => {
+    try {
+      await addBranch(2, "Rogue", 0, 0, accounts.merchant2);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("NotMerchantAuthority");
+    }
+  });
+
+  it("Deactivates a branch", async () => {
+    await program.methods
+      .updateMerchantBranch("Airport T4", 40.6413, -73.7781, false)
+      .accounts({
+        branch: branchFor(1),
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const branch = await program.account.merchantBranch.fetch(branchFor(1));
+    assert.equal(branch.name, "Airport T4");
+    assert.isFalse(branch.isActive);
+  });
+
+  it("Rejects promotion branches that do not exist", async () => {
+    try {
+      await program.methods
+        .setPromotionBranches([0, 5])
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("InvalidBranch");
+    }
+  });
+
+  it("Limits a promotion to branches 0 and 1", async () => {
+    await program.methods
+      .setPromotionBranches([0, 1])
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    assert.deepEqual(promotion.allowedBranches, [0, 1]);
+  });
+
+  it("Rejects redemption without a branch", async () => {
+    const [coupon, nftMint] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+
+    try {
+      await redeemAt(coupon, nftMint, null);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("BranchNotAllowed");
+    }
+  });
+
+  it("Rejects redemption at an inactive branch", async () => {
+    const [coupon, nftMint] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+
+    try {
+      await redeemAt(coupon, nftMint, branchFor(1));
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("BranchInactive");
+    }
+  });
+
+  it("Records the branch on a ticket redemption", async () => {
+    const [coupon, nftMint] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+    const nonce = new BN(getCurrentTimestamp());
+    const [ticketPDA] = derivePDA(
+      [Buffer.from("ticket"), coupon.toBuffer(), accounts.user1.publicKey.toBuffer(), u64ToLeBytes(nonce)],
+      program.programId
+    );
+
+    await program.methods
+      .generateRedemptionTicket(nonce, null, null)
+      .accounts({
+        ticket: ticketPDA,
+        coupon,
+        user: accounts.user1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1])
+      .rpc();
+    const { ticketHash } = await program.account.redemptionTicket.fetch(ticketPDA);
+
+    await program.methods
+      .verifyAndRedeemTicket(ticketHash, null)
+      .accounts({
+        ticket: ticketPDA,
+        coupon,
+        promotion: promotionPDA,
+        branch: branchFor(0),
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+        merchant: accounts.merchant1PDA,
+        user: accounts.user1.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1, accounts.user1])
+      .rpc();
+
+    const ticket = await program.account.redemptionTicket.fetch(ticketPDA);
+    assert.isTrue(ticket.isConsumed);
+    assert.ok(ticket.redeemedAtBranch.equals(branchFor(0)));
+  });
+
+  describe("Geofence", () => {
+    const generateTicket = async (coupon: PublicKey, latitude: number | null, longitude: number | null) => {
+      const nonce = new BN(getCurrentTimestamp() + Math.floor(Math.random() * 1_000_000));
+      const [ticket] = derivePDA(
+        [Buffer.from("ticket"), coupon.toBuffer(), accounts.user1.publicKey.toBuffer(), u64ToLeBytes(nonce)],
+        program.programId
+      );
+      await program.methods
+        .generateRedemptionTicket(nonce, latitude, longitude)
+        .accounts({
+          ticket,
+          coupon,
+          promotion: promotionPDA,
+          user: accounts.user1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.user1])
+        .rpc();
+      return ticket;
+    };
+
+    let coupon: PublicKey;
+
+    before(async () => {
+      [coupon] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+    });
+
+    it("Sets a 500m geofence around downtown", async () => {
+      const cellId = geoCellId(40.7128, -74.006);
+      await program.methods
+        .setPromotionGeofence(40.7128, -74.006, 500)
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          geoCell: deriveGeoCellPDA(cellId, program.programId)[0],
+          geoCellEntry: deriveGeoCellEntryPDA(cellId, 0, program.programId)[0],
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      const promotion = await program.account.promotion.fetch(promotionPDA);
+      assert.isTrue(promotion.isLocationBased);
+      assert.equal(promotion.radiusMeters, 500);
+      assert.equal(promotion.location.latitude, 40712800);
+      assert.equal(promotion.geoCellId.toString(), cellId.toString());
+    });
+
+    it("Requires a location for tickets", async () => {
+      try {
+        await generateTicket(coupon, null, null);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("LocationRequired");
+      }
+    });
+
+    it("Rejects a ticket generated outside the radius", async () => {
+      try {
+        // JFK airport, roughly 20km away
+        await generateTicket(coupon, 40.6413, -73.7781);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("LocationNotSupported");
+      }
+    });
+
+    it("Issues a ticket inside the radius", async () => {
+      const ticket = await generateTicket(coupon, 40.7131, -74.0065);
+
+      const ticketAccount = await program.account.redemptionTicket.fetch(ticket);
+      assert.equal(ticketAccount.redemptionLocation.latitude, 40713100);
+    });
+  });
+});
@@ -0,0 +1,190 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { randomBytes } from "crypto";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  buildMerkleTree,
+  promoCodeLeaf,
+  promoCodeCommitment,
+  wait,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+describe("Promo Codes", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let promotionPDA: PublicKey;
+
+  const CODES = ["SUMMER24", "WINTER24", "SPRING25"];
+
+  const commit = (claimer: Keypair, commitment: number[]) =>
+    program.methods
+      .commitPromoCode(commitment)
+      .accounts({
+        promoCodeCommit: derivePDA(
+          [Buffer.from("promo_code_commit"), promotionPDA.toBuffer(), claimer.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        promotion: promotionPDA,
+        claimer: claimer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([claimer])
+      .rpc();
+
+  const claim = async (claimer: Keypair, code: string, salt: Buffer, proof: number[][]) => {
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotionPDA.toBuffer(), u32ToLeBytes(promotion.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+
+    await program.methods
+      .claimWithCode(new BN(promotion.currentSupply + 1), code, [...salt], proof)
+      .accounts({
+        promoCodeCommit: derivePDA(
+          [Buffer.from("promo_code_commit"), promotionPDA.toBuffer(), claimer.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        promoCodeClaim: derivePDA(
+          [Buffer.from("promo_code"), promotionPDA.toBuffer(), promoCodeLeaf(code)],
+          program.programId
+        )[0],
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, claimer.publicKey),
+        metadata: deriveMetadataPDA(nftMint.publicKey)[0],
+        masterEdition: deriveMasterEditionPDA(nftMint.publicKey)[0],
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        claimer: claimer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([claimer, nftMint])
+      .rpc();
+
+    return coupon;
+  };
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+
+    await program.methods
+      .registerMerchant("Receipt Cafe", "cafe", null, null)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    [promotionPDA] = derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    );
+    await program.methods
+      .createPromotion(25, 10, getExpiryTimestamp(30), "cafe", "Receipt code", new BN(0))
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+  });
+
+  it("Claims a code revealed after its commitment", async () => {
+    const { proofs } = buildMerkleTree(CODES.map(promoCodeLeaf));
+    const salt = randomBytes(32);
+    await commit(accounts.user1, promoCodeCommitment("SUMMER24", accounts.user1.publicKey, salt));
+    await wait(1000);
+
+    const coupon = await claim(accounts.user1, "SUMMER24", salt, proofs[0]);
+    const couponAccount = await program.account.coupon.fetch(coupon);
+    assert.ok(couponAccount.owner.equals(accounts.user1.publicKey));
+  });
+
+  it("Rejects a code that was already claimed", async () => {
+    const { proofs } = buildMerkleTree(CODES.map(promoCodeLeaf));
+    const salt = randomBytes(32);
+    await commit(accounts.user2, promoCodeCommitment("SUMMER24", accounts.user2.publicKey, salt));
+    await wait(1000);
+
+    try {
+      await claim(accounts.user2, "SUMMER24", salt, proofs[0]);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("already in use");
+    }
+  });
+
+  it("Rejects a code copied from someone else's reveal", async () => {
+    // user2 committed to a different code above and copies user1's reveal
+    const { proofs } = buildMerkleTree(CODES.map(promoCodeLeaf));
+    const salt = randomBytes(32);
+    await commit(accounts.user1, promoCodeCommitment("WINTER24", accounts.user1.publicKey, salt));
+    await wait(1000);
+
+    try {
+      await claim(accounts.user2, "WINTER24", salt, proofs[1]);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("CommitmentMismatch");
+    }
+  });
+
+  it("Rejects a code that is not in the batch", async () => {
+    const { proofs } = buildMerkleTree(CODES.map(promoCodeLeaf));
+    const salt = randomBytes(32);
+    await commit(accounts.user2, promoCodeCommitment("AUTUMN25", accounts.user2.publicKey, salt));
+    await wait(1000);
+
+    try {
+      await claim(accounts.user2, "AUTUMN25", salt, proofs[2]);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("InvalidPromoCode");
+    }
+  });
+});
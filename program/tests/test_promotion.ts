@@ -11,7 +11,8 @@ import {
   derivePDA,
   accountExists,
   LAMPORTS_PER_SOL,
-  u64ToLeBytes
+  u64ToLeBytes,
+  wait
 } from "./setup";
 
 describe("Promotion Creation", () => {
@@ -446,4 +447,242 @@ describe("Promotion Creation", () => {
     const promotion = await program.account.promotion.fetch(promo);
     assert.equal(promotion.maxSupply, 1);
   });
-});
\ No newline at end of file
+});
+
+describe("Promotion Lifecycle", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let promotionPDA: anchor.web3.PublicKey;
+  let shortPromotionPDA: anchor.web3.PublicKey;
+
+  const promotionFor = (merchantPDA: anchor.web3.PublicKey) =>
+    derivePDA(
+      [Buffer.from("promotion"), merchantPDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    )[0];
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+
+    for (const [merchant, merchantPDA] of [
+      [accounts.merchant1, accounts.merchant1PDA],
+      [accounts.merchant2, accounts.merchant2PDA],
+    ] as const) {
+      await program.methods
+        .registerMerchant("Lifecycle Cafe", "restaurant", null, null)
+        .accounts({
+          merchant: merchantPDA,
+          marketplace: accounts.marketplacePDA,
+          authority: merchant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([merchant])
+        .rpc();
+    }
+
+    promotionPDA = promotionFor(accounts.merchant1PDA);
+    await program.methods
+      .createPromotion(20, 10, getExpiryTimestamp(30), "food", "Lifecycle promotion", new BN(LAMPORTS_PER_SOL))
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    // Expires a few seconds from now so it can be closed
+    shortPromotionPDA = promotionFor(accounts.merchant2PDA);
+    await program.methods
+      .createPromotion(20, 10, new BN(getCurrentTimestamp() + 3), "food", "Short promotion", new BN(LAMPORTS_PER_SOL))
+      .accounts({
+        promotion: shortPromotionPDA,
+        merchant: accounts.merchant2PDA,
+        authority: accounts.merchant2.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant2])
+      .rpc();
+  });
+
+  it("Updates the description and category", async () => {
+    await program.methods
+      .updatePromotionDetails("drinks", "Two-for-one smoothies")
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    assert.equal(promotion.category, "drinks");
+    assert.equal(promotion.description, "Two-for-one smoothies");
+  });
+
+  it("Pauses and resumes the promotion", async () => {
+    for (const isActive of [false, true]) {
+      await program.methods
+        .setPromotionActive(isActive)
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      const promotion = await program.account.promotion.fetch(promotionPDA);
+      assert.equal(promotion.isActive, isActive);
+    }
+  });
+
+  it("Extends the expiry, and shortens it only before any mint", async () => {
+    const before = await program.account.promotion.fetch(promotionPDA);
+    assert.equal(before.currentSupply, 0);
+    const later = before.expiryTimestamp.add(new BN(86400));
+
+    await program.methods
+      .extendPromotion(later)
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    let promotion = await program.account.promotion.fetch(promotionPDA);
+    assert.equal(promotion.expiryTimestamp.toString(), later.toString());
+
+    // Nothing minted yet, so the end can move back as long as it stays in the future
+    await program.methods
+      .extendPromotion(before.expiryTimestamp)
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    promotion = await program.account.promotion.fetch(promotionPDA);
+    assert.equal(promotion.expiryTimestamp.toString(), before.expiryTimestamp.toString());
+
+    try {
+      await program.methods
+        .extendPromotion(new BN(Math.floor(Date.now() / 1000) - 60))
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("InvalidExpiry");
+    }
+  });
+
+  it("Raises max supply but refuses to lower it", async () => {
+    await program.methods
+      .increasePromotionSupply(25)
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    assert.equal(promotion.maxSupply, 25);
+
+    try {
+      await program.methods
+        .increasePromotionSupply(5)
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("InvalidSupply");
+    }
+  });
+
+  it("Fails when another merchant tries to manage the promotion", async () => {
+    try {
+      await program.methods
+        .setPromotionActive(false)
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant2.publicKey,
+        })
+        .signers([accounts.merchant2])
+        .rpc();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("NotMerchantAuthority");
+    }
+  });
+
+  it("Fails to close a promotion that has not expired", async () => {
+    try {
+      await program.methods
+        .closePromotion()
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("PromotionNotExpired");
+    }
+  });
+
+  it("Closes an expired promotion and returns the rent", async () => {
+    await wait(5000);
+    const balanceBefore = await connection.getBalance(accounts.merchant2.publicKey);
+
+    await program.methods
+      .closePromotion()
+      .accounts({
+        promotion: shortPromotionPDA,
+        merchant: accounts.merchant2PDA,
+        authority: accounts.merchant2.publicKey,
+      })
+      .signers([accounts.merchant2])
+      .rpc();
+
+    assert.isFalse(await accountExists(connection, shortPromotionPDA));
+    assert.isAbove(await connection.getBalance(accounts.merchant2.publicKey), balanceBefore);
+  });
+});
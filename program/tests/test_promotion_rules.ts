@@ -0,0 +1,699 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  buildAllowList,
+  getCurrentTimestamp,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  LAMPORTS_PER_SOL,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync, getOrCreateAssociatedTokenAccount, transfer } from "@solana/spl-token";
+
+describe("Promotion Rules", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let marketplaceAuthority: PublicKey;
+  let promotionPDA: PublicKey;
+
+  const mintCoupon = async (
+    promotion: PublicKey,
+    merchantPDA: PublicKey,
+    merchantSigner: Keypair,
+    recipient: Keypair,
+    presaleAccounts: { presaleAllowance?: PublicKey; badgeNft?: PublicKey } = {},
+    allowListProof: { quantity: number; proof: number[][] } | null = null
+  ): Promise<[PublicKey, Keypair]> => {
+    const promotionAccount = await program.account.promotion.fetch(promotion);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotion.toBuffer(), u32ToLeBytes(promotionAccount.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+    const [metadata] = deriveMetadataPDA(nftMint.publicKey);
+    const [masterEdition] = deriveMasterEditionPDA(nftMint.publicKey);
+    const merchant = await program.account.merchant.fetch(merchantPDA);
+
+    await program.methods
+      .mintCoupon(new BN(promotionAccount.currentSupply + 1), allowListProof)
+      .accounts({
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, recipient.publicKey),
+        metadata,
+        masterEdition,
+        promotion,
+        merchant: merchantPDA,
+        marketplace: accounts.marketplacePDA,
+        recipient: recipient.publicKey,
+        payer: recipient.publicKey,
+        authority: merchantSigner.publicKey,
+        merchantAuthority: merchant.authority,
+        marketplaceAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+        ...presaleAccounts,
+      })
+      .signers([recipient, nftMint, merchantSigner])
+      .rpc();
+
+    return [coupon, nftMint];
+  };
+
+  const redeem = (
+    coupon: PublicKey,
+    nftMint: Keypair,
+    promotion: PublicKey = promotionPDA,
+    bill: { amount: BN; itemPrice: BN; quantity: number } | null = null
+  ) =>
+    program.methods
+      .redeemCoupon(bill)
+      .accounts({
+        coupon,
+        promotion,
+        metadata: deriveMetadataPDA(nftMint.publicKey)[0],
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+        merchant: accounts.merchant1PDA,
+        user: accounts.user1.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1, accounts.merchant1])
+      .rpc();
+
+  const setSchedule = (schedule: object | null) =>
+    program.methods
+      .setRedemptionSchedule(schedule as any)
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+    marketplaceAuthority = (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority;
+
+    await program.methods
+      .registerMerchant("Rules Cafe", "cafe", null, null)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    [promotionPDA] = derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    );
+    await program.methods
+      .createPromotion(15, 20, getExpiryTimestamp(30), "cafe", "Rules promo", new BN(0.01 * LAMPORTS_PER_SOL))
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+  });
+
+  describe("Redemption Schedule", () => {
+    const ALL_DAYS = 0b111_1111;
+
+    it("Rejects an invalid schedule", async () => {
+      try {
+        await setSchedule({
+          startTimestamp: new BN(0),
+          daysOfWeek: 0,
+          openMinute: 15 * 60,
+          closeMinute: 18 * 60,
+          utcOffsetMinutes: 0,
+        });
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidSchedule");
+      }
+    });
+
+    it("Rejects redemption outside the window", async () => {
+      // Every day, all day, but only from tomorrow
+      await setSchedule({
+        startTimestamp: new BN(getCurrentTimestamp() + 86_400),
+        daysOfWeek: ALL_DAYS,
+        openMinute: 0,
+        closeMinute: 1440,
+        utcOffsetMinutes: 0,
+      });
+      const promotion = await program.account.promotion.fetch(promotionPDA);
+      assert.equal(promotion.redemptionSchedule.daysOfWeek, ALL_DAYS);
+
+      const [coupon, nftMint] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      try {
+        await redeem(coupon, nftMint);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("OutsideRedemptionWindow");
+      }
+    });
+
+    it("Redeems inside the window", async () => {
+      await setSchedule({
+        startTimestamp: new BN(0),
+        daysOfWeek: ALL_DAYS,
+        openMinute: 0,
+        closeMinute: 1440,
+        utcOffsetMinutes: 0,
+      });
+
+      const [coupon, nftMint] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      await redeem(coupon, nftMint);
+
+      const couponAccount = await program.account.coupon.fetch(coupon);
+      assert.isTrue(couponAccount.isRedeemed);
+    });
+
+    it("Removes the schedule", async () => {
+      await setSchedule(null);
+
+      const promotion = await program.account.promotion.fetch(promotionPDA);
+      assert.isNull(promotion.redemptionSchedule);
+    });
+  });
+
+  describe("Launch Window", () => {
+    const presaleAllowancePDA = (wallet: PublicKey) =>
+      derivePDA([Buffer.from("presale_allowance"), promotionPDA.toBuffer(), wallet.toBuffer()], program.programId)[0];
+
+    const setLaunch = (startTimestamp: number, presale: object | null) =>
+      program.methods
+        .setPromotionLaunch(new BN(startTimestamp), presale as any)
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+    it("Rejects a pre-sale that does not open before the launch", async () => {
+      const start = getCurrentTimestamp() + 86_400;
+      try {
+        await setLaunch(start, { startTimestamp: new BN(start), badge: null });
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidLaunchWindow");
+      }
+    });
+
+    it("Rejects minting before the start", async () => {
+      await setLaunch(getCurrentTimestamp() + 86_400, { startTimestamp: new BN(getCurrentTimestamp() - 60), badge: null });
+
+      try {
+        await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("PromotionNotStarted");
+      }
+    });
+
+    it("Lets an allow-listed wallet mint during the pre-sale", async () => {
+      await program.methods
+        .addPresaleWallet(accounts.user1.publicKey)
+        .accounts({
+          presaleAllowance: presaleAllowancePDA(accounts.user1.publicKey),
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      const before = (await program.account.promotion.fetch(promotionPDA)).currentSupply;
+      await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1, {
+        presaleAllowance: presaleAllowancePDA(accounts.user1.publicKey),
+      });
+      assert.equal((await program.account.promotion.fetch(promotionPDA)).currentSupply, before + 1);
+    });
+
+    it("Rejects another wallet's allowance", async () => {
+      try {
+        await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user2, {
+          presaleAllowance: presaleAllowancePDA(accounts.user1.publicKey),
+        });
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ConstraintSeeds");
+      }
+    });
+
+    it("Removes a pre-sale wallet", async () => {
+      await program.methods
+        .removePresaleWallet()
+        .accounts({
+          presaleAllowance: presaleAllowancePDA(accounts.user1.publicKey),
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      assert.isFalse(await accountExists(connection, presaleAllowancePDA(accounts.user1.publicKey)));
+    });
+
+    it("Opens to everyone at the start", async () => {
+      await setLaunch(getCurrentTimestamp() - 60, null);
+
+      const promotion = await program.account.promotion.fetch(promotionPDA);
+      assert.isNull(promotion.presale);
+      await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user2);
+    });
+  });
+
+  describe("Per-wallet Limit", () => {
+    const walletMintsPDA = (wallet: PublicKey) =>
+      derivePDA([Buffer.from("wallet_mints"), promotionPDA.toBuffer(), wallet.toBuffer()], program.programId)[0];
+
+    const setMaxPerWallet = (maxPerWallet: number | null) =>
+      program.methods
+        .setMaxPerWallet(maxPerWallet)
+        .accounts({
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+    it("Rejects a zero limit", async () => {
+      try {
+        await setMaxPerWallet(0);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidWalletLimit");
+      }
+    });
+
+    it("Stops a wallet at the limit", async () => {
+      // Mints from the earlier tests already count towards the limit
+      const minted = (await program.account.walletMintCount.fetch(walletMintsPDA(accounts.user1.publicKey))).minted;
+      await setMaxPerWallet(minted + 1);
+
+      await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      const walletMints = await program.account.walletMintCount.fetch(walletMintsPDA(accounts.user1.publicKey));
+      assert.equal(walletMints.minted, minted + 1);
+      assert.ok(walletMints.wallet.equals(accounts.user1.publicKey));
+
+      try {
+        await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("WalletLimitReached");
+      }
+    });
+
+    it("Counts each wallet separately", async () => {
+      await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user2);
+    });
+
+    it("Lifts the limit", async () => {
+      await setMaxPerWallet(null);
+      await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+
+      const promotion = await program.account.promotion.fetch(promotionPDA);
+      assert.isNull(promotion.maxPerWallet);
+    });
+  });
+
+  describe("Allow-list", () => {
+    let allowListedPromotion: PublicKey;
+    const outsider = Keypair.generate();
+    const entries = () => [
+      { wallet: accounts.user1.publicKey, quantity: 1 },
+      { wallet: accounts.user2.publicKey, quantity: 2 },
+      { wallet: outsider.publicKey, quantity: 1 },
+    ];
+
+    before(async () => {
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      [allowListedPromotion] = derivePDA(
+        [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated)],
+        program.programId
+      );
+      await program.methods
+        .createPromotion(10, 20, getExpiryTimestamp(30), "cafe", "Members only", new BN(0))
+        .accounts({
+          promotion: allowListedPromotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      await program.methods
+        .setAllowList(buildAllowList(entries()).root)
+        .accounts({
+          promotion: allowListedPromotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+    });
+
+    const mintListed = (recipient: Keypair, proof: { quantity: number; proof: number[][] } | null) =>
+      mintCoupon(allowListedPromotion, accounts.merchant1PDA, accounts.merchant1, recipient, {}, proof);
+
+    it("Rejects a mint without a proof", async () => {
+      try {
+        await mintListed(accounts.user1, null);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotOnAllowList");
+      }
+    });
+
+    it("Rejects a proof claiming more than the leaf's quantity", async () => {
+      const { proofs } = buildAllowList(entries());
+      try {
+        await mintListed(accounts.user1, { quantity: 2, proof: proofs[0] });
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotOnAllowList");
+      }
+    });
+
+    it("Mints up to each leaf's quantity", async () => {
+      const { proofs } = buildAllowList(entries());
+
+      await mintListed(accounts.user1, { quantity: 1, proof: proofs[0] });
+      await mintListed(accounts.user2, { quantity: 2, proof: proofs[1] });
+      await mintListed(accounts.user2, { quantity: 2, proof: proofs[1] });
+
+      for (const [recipient, proof] of [
+        [accounts.user1, { quantity: 1, proof: proofs[0] }],
+        [accounts.user2, { quantity: 2, proof: proofs[1] }],
+      ] as const) {
+        try {
+          await mintListed(recipient, proof);
+          assert.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.message).to.include("AllowListClaimed");
+        }
+      }
+
+      const promotion = await program.account.promotion.fetch(allowListedPromotion);
+      assert.equal(promotion.currentSupply, 3);
+    });
+  });
+
+  describe("Discount Kind", () => {
+    let bogoPromotion: PublicKey;
+
+    const setDiscountKind = (promotion: PublicKey, discountKind: object) =>
+      program.methods
+        .setDiscountKind(discountKind as any)
+        .accounts({
+          promotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+    before(async () => {
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      [bogoPromotion] = derivePDA(
+        [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated)],
+        program.programId
+      );
+      await program.methods
+        .createPromotion(10, 20, getExpiryTimestamp(30), "cafe", "Two for one", new BN(0))
+        .accounts({
+          promotion: bogoPromotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+    });
+
+    it("Defaults to the created percentage", async () => {
+      const promotion = await program.account.promotion.fetch(bogoPromotion);
+      assert.deepEqual(promotion.discountKind, { percentage: { percent: 10 } });
+    });
+
+    it("Rejects an invalid kind", async () => {
+      try {
+        await setDiscountKind(bogoPromotion, { buyXGetY: { buyQuantity: 1, freeQuantity: 0 } });
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidDiscount");
+      }
+    });
+
+    it("Copies the kind into minted coupons", async () => {
+      await setDiscountKind(bogoPromotion, { buyXGetY: { buyQuantity: 1, freeQuantity: 1 } });
+
+      const promotion = await program.account.promotion.fetch(bogoPromotion);
+      assert.equal(promotion.discountPercentage, 0);
+
+      const [coupon] = await mintCoupon(bogoPromotion, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      const couponAccount = await program.account.coupon.fetch(coupon);
+      assert.deepEqual(couponAccount.discountKind, { buyXGetY: { buyQuantity: 1, freeQuantity: 1 } });
+    });
+
+    it("Locks the kind after the first mint", async () => {
+      try {
+        await setDiscountKind(bogoPromotion, {
+          fixedAmount: { amount: new BN(5_000_000), currencyMint: Keypair.generate().publicKey },
+        });
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("DiscountLocked");
+      }
+    });
+  });
+
+  describe("Punch Card", () => {
+    let punchCardPromotion: PublicKey;
+
+    const setUsesPerCoupon = (uses: number) =>
+      program.methods
+        .setUsesPerCoupon(uses)
+        .accounts({
+          promotion: punchCardPromotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+    const metadataName = async (nftMint: Keypair) => {
+      const metadata = await connection.getAccountInfo(deriveMetadataPDA(nftMint.publicKey)[0]);
+      // key (1) + update authority (32) + mint (32), then the borsh name
+      const length = metadata.data.readUInt32LE(65);
+      return metadata.data.subarray(69, 69 + length).toString().replace(/\0+$/, "");
+    };
+
+    before(async () => {
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      [punchCardPromotion] = derivePDA(
+        [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated)],
+        program.programId
+      );
+      await program.methods
+        .createPromotion(100, 20, getExpiryTimestamp(30), "gym", "3 visit pass", new BN(0))
+        .accounts({
+          promotion: punchCardPromotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+    });
+
+    it("Rejects zero uses", async () => {
+      try {
+        await setUsesPerCoupon(0);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidUses");
+      }
+    });
+
+    it("Counts down the uses and burns on the last", async () => {
+      await setUsesPerCoupon(3);
+      const [coupon, nftMint] = await mintCoupon(punchCardPromotion, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      assert.equal(await metadataName(nftMint), "Punch Card 3/3 uses left");
+
+      await redeem(coupon, nftMint, punchCardPromotion);
+      let couponAccount = await program.account.coupon.fetch(coupon);
+      assert.equal(couponAccount.usesTotal, 3);
+      assert.equal(couponAccount.usesRemaining, 2);
+      assert.isFalse(couponAccount.isRedeemed);
+      assert.equal(await metadataName(nftMint), "Punch Card 2/3 uses left");
+
+      const [firstUse] = derivePDA(
+        [Buffer.from("coupon_use"), coupon.toBuffer(), Buffer.from([3, 0])],
+        program.programId
+      );
+      const record = await program.account.couponUse.fetch(firstUse);
+      assert.equal(record.useNumber, 1);
+      assert.ok(record.redeemedBy.equals(accounts.merchant1.publicKey));
+
+      await redeem(coupon, nftMint, punchCardPromotion);
+      await redeem(coupon, nftMint, punchCardPromotion);
+      couponAccount = await program.account.coupon.fetch(coupon);
+      assert.equal(couponAccount.usesRemaining, 0);
+      assert.isTrue(couponAccount.isRedeemed);
+
+      const tokenAccount = getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey);
+      const balance = await connection.getTokenAccountBalance(tokenAccount);
+      assert.equal(balance.value.amount, "0");
+
+      try {
+        await redeem(coupon, nftMint, punchCardPromotion);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("CouponAlreadyRedeemed");
+      }
+    });
+
+    it("Rejects a use once the NFT has left the wallet", async () => {
+      await setUsesPerCoupon(2);
+      const [coupon, nftMint] = await mintCoupon(punchCardPromotion, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+
+      // A plain token transfer leaves Coupon.owner pointing at user1
+      const destination = await getOrCreateAssociatedTokenAccount(
+        connection, accounts.user1, nftMint.publicKey, accounts.user2.publicKey
+      );
+      await transfer(
+        connection,
+        accounts.user1,
+        getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user1.publicKey),
+        destination.address,
+        accounts.user1,
+        1
+      );
+
+      try {
+        await redeem(coupon, nftMint, punchCardPromotion);
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotCouponOwner");
+      }
+      const couponAccount = await program.account.coupon.fetch(coupon);
+      assert.equal(couponAccount.usesRemaining, 2);
+    });
+  });
+
+  describe("Savings", () => {
+    const userStatsPDA = () =>
+      derivePDA([Buffer.from("user_stats"), accounts.user1.publicKey.toBuffer()], program.programId)[0];
+
+    it("Records the discount on the reported bill", async () => {
+      const [coupon, nftMint] = await mintCoupon(promotionPDA, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      const savingsBefore = (await program.account.userStats.fetch(userStatsPDA())).totalSavings;
+      const merchantBefore = (await program.account.merchant.fetch(accounts.merchant1PDA)).totalDiscountGiven;
+      const promotionBefore = (await program.account.promotion.fetch(promotionPDA)).totalDiscountGiven;
+
+      // 15% off a 20 USDC bill
+      await redeem(coupon, nftMint, promotionPDA, { amount: new BN(20_000_000), itemPrice: new BN(0), quantity: 0 });
+
+      const userStats = await program.account.userStats.fetch(userStatsPDA());
+      assert.equal(userStats.totalSavings.sub(savingsBefore).toNumber(), 3_000_000);
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      assert.equal(merchant.totalDiscountGiven.sub(merchantBefore).toNumber(), 3_000_000);
+      const promotion = await program.account.promotion.fetch(promotionPDA);
+      assert.equal(promotion.totalDiscountGiven.sub(promotionBefore).toNumber(), 3_000_000);
+    });
+
+    it("Rejects a bill below the minimum spend", async () => {
+      const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+      const [minSpendPromotion] = derivePDA(
+        [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated)],
+        program.programId
+      );
+      await program.methods
+        .createPromotion(10, 20, getExpiryTimestamp(30), "cafe", "$10 off $50", new BN(0))
+        .accounts({
+          promotion: minSpendPromotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+      await program.methods
+        .setDiscountKind({
+          minimumSpend: {
+            minSpend: new BN(50_000_000),
+            amountOff: new BN(10_000_000),
+            currencyMint: Keypair.generate().publicKey,
+          },
+        } as any)
+        .accounts({
+          promotion: minSpendPromotion,
+          merchant: accounts.merchant1PDA,
+          authority: accounts.merchant1.publicKey,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+
+      const [coupon, nftMint] = await mintCoupon(minSpendPromotion, accounts.merchant1PDA, accounts.merchant1, accounts.user1);
+      try {
+        await redeem(coupon, nftMint, minSpendPromotion, { amount: new BN(30_000_000), itemPrice: new BN(0), quantity: 0 });
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("BelowMinimumSpend");
+      }
+    });
+  });
+});
@@ -93,7 +93,7 @@ describe("Redemption Tickets", () => {
           getExpiryTimestamp(7),
           "Test Promotion",
           "Test promotion for redemption tickets",
-          new BN(20 * LAMPORTS_PER_SOL)
+          new BN(1 * LAMPORTS_PER_SOL)
         )
         .accounts({
           promotion: promotionPDA,
@@ -138,7 +138,7 @@ describe("Redemption Tickets", () => {
       });
 
       await program.methods
-        .mintCoupon(new BN(1))
+        .mintCoupon(new BN(1), null)
         .accounts({
           coupon: couponPDA,
           nftMint: couponMint.publicKey,
@@ -152,6 +152,8 @@ describe("Redemption Tickets", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -374,7 +376,7 @@ describe("Redemption Tickets", () => {
       });
 
       await program.methods
-        .mintCoupon(new BN(2))
+        .mintCoupon(new BN(2), null)
         .accounts({
           coupon: newCouponPDA,
           nftMint: newCouponMint.publicKey,
@@ -388,6 +390,8 @@ describe("Redemption Tickets", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -444,7 +448,7 @@ describe("Redemption Tickets", () => {
   const userStatsBefore = await program.account.userStats.fetch(userStatsPDA);
 
   await program.methods
-    .verifyAndRedeemTicket(ticketHash)
+    .verifyAndRedeemTicket(ticketHash, null)
     .accounts({
       ticket: redeemTicketPDA,
       coupon: couponPDA,
@@ -530,7 +534,7 @@ describe("Redemption Tickets", () => {
       });
 
       await program.methods
-        .mintCoupon(new BN(3))
+        .mintCoupon(new BN(3), null)
         .accounts({
           coupon: testCouponPDA,
           nftMint: testCouponMint.publicKey,
@@ -544,6 +548,8 @@ describe("Redemption Tickets", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -588,7 +594,7 @@ describe("Redemption Tickets", () => {
 
   try {
     await program.methods
-      .verifyAndRedeemTicket(wrongHash)
+      .verifyAndRedeemTicket(wrongHash, null)
       .accounts({
         ticket: testTicketPDA,
         coupon: testCouponPDA,
@@ -674,7 +680,7 @@ describe("Redemption Tickets", () => {
   });
 
   await program.methods
-    .mintCoupon(new BN(4))
+    .mintCoupon(new BN(4), null)
     .accounts({
       coupon: wrongMerchantCouponPDA,
       nftMint: wrongMerchantCouponMint.publicKey,
@@ -688,6 +694,8 @@ describe("Redemption Tickets", () => {
       userStats: userStatsPDA,
       payer: accounts.user1.publicKey,
       authority: accounts.merchant1.publicKey,
+      merchantAuthority: accounts.merchant1.publicKey,
+      marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -734,7 +742,7 @@ describe("Redemption Tickets", () => {
   // TEST SCENARIO 1: Try to redeem with wrong merchant PDA
   try {
     await program.methods
-      .verifyAndRedeemTicket(wrongMerchantTicket.ticketHash)
+      .verifyAndRedeemTicket(wrongMerchantTicket.ticketHash, null)
       .accounts({
         ticket: wrongMerchantTicketPDA,
         coupon: wrongMerchantCouponPDA,
@@ -766,7 +774,7 @@ describe("Redemption Tickets", () => {
   // TEST SCENARIO 2: Try to redeem with correct merchant PDA but wrong authority signature
   try {
     await program.methods
-      .verifyAndRedeemTicket(wrongMerchantTicket.ticketHash)
+      .verifyAndRedeemTicket(wrongMerchantTicket.ticketHash, null)
       .accounts({
         ticket: wrongMerchantTicketPDA,
         coupon: wrongMerchantCouponPDA,
@@ -841,7 +849,7 @@ describe("Redemption Tickets", () => {
       });
 
       await program.methods
-        .mintCoupon(new BN(5))
+        .mintCoupon(new BN(5), null)
         .accounts({
           coupon: cancelCouponPDA,
           nftMint: cancelCouponMint.publicKey,
@@ -855,6 +863,8 @@ describe("Redemption Tickets", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -942,7 +952,7 @@ describe("Redemption Tickets", () => {
       });
 
       await program.methods
-        .mintCoupon(new BN(6))
+        .mintCoupon(new BN(6), null)
         .accounts({
           coupon: notOwnedCouponPDA,
           nftMint: notOwnedCouponMint.publicKey,
@@ -956,6 +966,8 @@ describe("Redemption Tickets", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -1040,7 +1052,7 @@ describe("Redemption Tickets", () => {
       });
 
       await program.methods
-        .mintCoupon(new BN(7))
+        .mintCoupon(new BN(7), null)
         .accounts({
           coupon: multiTicketCouponPDA,
           nftMint: multiTicketCouponMint.publicKey,
@@ -1054,6 +1066,8 @@ describe("Redemption Tickets", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
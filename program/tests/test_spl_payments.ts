@@ -0,0 +1,395 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  getAssociatedTokenAddressSync,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+
+describe("SPL Token Payments", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  // 6-decimal stablecoin stand-in
+  const USDC = 1_000_000;
+  const promotionPrice = new BN(10 * USDC);
+
+  let accounts: TestAccounts;
+  let marketplaceAuthority: PublicKey;
+  let usdcMint: PublicKey;
+  let promotionPDA: PublicKey;
+  let couponPDA: PublicKey;
+  let couponMintKey: PublicKey;
+
+  const usdcAccount = (owner: PublicKey) =>
+    getAssociatedTokenAddressSync(usdcMint, owner, true);
+
+  const usdcBalance = async (owner: PublicKey): Promise<bigint> =>
+    (await getAccount(connection, usdcAccount(owner))).amount;
+
+  const mintCouponWithUsdc = async (recipient: Keypair): Promise<[PublicKey, PublicKey]> => {
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotionPDA.toBuffer(), u32ToLeBytes(promotion.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+    const [metadata] = deriveMetadataPDA(nftMint.publicKey);
+    const [masterEdition] = deriveMasterEditionPDA(nftMint.publicKey);
+
+    await program.methods
+      .mintCoupon(new BN(promotion.currentSupply + 1), null)
+      .accounts({
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, recipient.publicKey),
+        metadata,
+        masterEdition,
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        recipient: recipient.publicKey,
+        payer: recipient.publicKey,
+        authority: accounts.merchant1.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        marketplaceAuthority,
+        paymentMint: usdcMint,
+        payerPaymentAccount: usdcAccount(recipient.publicKey),
+        merchantPaymentAccount: usdcAccount(accounts.merchant1.publicKey),
+        marketplacePaymentAccount: usdcAccount(marketplaceAuthority),
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([recipient, nftMint, accounts.merchant1])
+      .rpc();
+
+    return [coupon, nftMint.publicKey];
+  };
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+    marketplaceAuthority = (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority;
+
+    await program.methods
+      .registerMerchant("Stablecoin Store", "retail", null, null)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    // Payment mint plus a funded token account for everyone who pays or gets paid
+    usdcMint = await createMint(connection, accounts.user1, accounts.user1.publicKey, null, 6);
+    for (const owner of [
+      accounts.user1.publicKey,
+      accounts.user2.publicKey,
+      accounts.merchant1.publicKey,
+      marketplaceAuthority,
+    ]) {
+      await getOrCreateAssociatedTokenAccount(connection, accounts.user1, usdcMint, owner, true);
+    }
+    await mintTo(connection, accounts.user1, usdcMint, usdcAccount(accounts.user1.publicKey), accounts.user1, 100 * USDC);
+    await mintTo(connection, accounts.user1, usdcMint, usdcAccount(accounts.user2.publicKey), accounts.user1, 100 * USDC);
+
+    const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+    [promotionPDA] = derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated)],
+      program.programId
+    );
+  });
+
+  it("Creates a promotion priced in an SPL token", async () => {
+    await program.methods
+      .createPromotion(20, 10, getExpiryTimestamp(30), "retail", "Paid in USDC", promotionPrice)
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        paymentMint: usdcMint,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    assert.equal(promotion.paymentMint.toString(), usdcMint.toString());
+  });
+
+  it("Mints a coupon paid in the promotion's token", async () => {
+    const marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+    const price = BigInt(promotionPrice.toString());
+    const fee = price * BigInt(marketplace.feeBasisPoints) / BigInt(10000);
+
+    const payerBefore = await usdcBalance(accounts.user1.publicKey);
+    const merchantBefore = await usdcBalance(accounts.merchant1.publicKey);
+    const feeBefore = await usdcBalance(marketplaceAuthority);
+
+    [couponPDA, couponMintKey] = await mintCouponWithUsdc(accounts.user1);
+
+    assert.equal(payerBefore - (await usdcBalance(accounts.user1.publicKey)), price);
+    assert.equal((await usdcBalance(accounts.merchant1.publicKey)) - merchantBefore, price - fee);
+    assert.equal((await usdcBalance(marketplaceAuthority)) - feeBefore, fee);
+  });
+
+  it("Fails to mint a token-priced coupon without payment accounts", async () => {
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotionPDA.toBuffer(), u32ToLeBytes(promotion.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+    const [metadata] = deriveMetadataPDA(nftMint.publicKey);
+    const [masterEdition] = deriveMasterEditionPDA(nftMint.publicKey);
+
+    try {
+      await program.methods
+        .mintCoupon(new BN(promotion.currentSupply + 1), null)
+        .accounts({
+          coupon,
+          nftMint: nftMint.publicKey,
+          tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, accounts.user2.publicKey),
+          metadata,
+          masterEdition,
+          promotion: promotionPDA,
+          merchant: accounts.merchant1PDA,
+          marketplace: accounts.marketplacePDA,
+          recipient: accounts.user2.publicKey,
+          payer: accounts.user2.publicKey,
+          authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([accounts.user2, nftMint, accounts.merchant1])
+        .rpc();
+      assert.fail("Should have required the SPL payment accounts");
+    } catch (error) {
+      expect(error.message).to.include("MissingPaymentAccount");
+    }
+  });
+
+  it("Sells a listing for an SPL token and pays the merchant royalty", async () => {
+    const listingPrice = new BN(15 * USDC);
+    const [listingPDA] = derivePDA([Buffer.from("listing"), couponPDA.toBuffer()], program.programId);
+
+    await program.methods
+      .listForSale(listingPrice)
+      .accounts({
+        listing: listingPDA,
+        coupon: couponPDA,
+        nftMint: couponMintKey,
+        sellerTokenAccount: getAssociatedTokenAddressSync(couponMintKey, accounts.user1.publicKey),
+        paymentMint: usdcMint,
+        seller: accounts.user1.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([accounts.user1])
+      .rpc();
+
+    const listing = await program.account.listing.fetch(listingPDA);
+    assert.equal(listing.paymentMint.toString(), usdcMint.toString());
+
+    // Merchant takes a cut of the resale
+    await program.methods
+      .setMerchantRoyalty(500)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+    const price = BigInt(listingPrice.toString());
+    const fee = price * BigInt(marketplace.feeBasisPoints) / BigInt(10000);
+    // Royalty applies only to the premium over what the seller paid at mint
+    const paid = BigInt((await program.account.coupon.fetch(couponPDA)).lastSalePrice.toString());
+    const royalty = (price - paid) * BigInt(500) / BigInt(10000);
+    const sellerBefore = await usdcBalance(accounts.user1.publicKey);
+    const buyerBefore = await usdcBalance(accounts.user2.publicKey);
+    const merchantBefore = await usdcBalance(accounts.merchant1.publicKey);
+
+    await program.methods
+      .buyListing()
+      .accounts({
+        listing: listingPDA,
+        coupon: couponPDA,
+        nftMint: couponMintKey,
+        marketplace: accounts.marketplacePDA,
+        seller: accounts.user1.publicKey,
+        buyer: accounts.user2.publicKey,
+        marketplaceAuthority,
+        merchant: accounts.merchant1PDA,
+        merchantAuthority: accounts.merchant1.publicKey,
+        paymentMint: usdcMint,
+        buyerPaymentAccount: usdcAccount(accounts.user2.publicKey),
+        sellerPaymentAccount: usdcAccount(accounts.user1.publicKey),
+        marketplacePaymentAccount: usdcAccount(marketplaceAuthority),
+        merchantPaymentAccount: usdcAccount(accounts.merchant1.publicKey),
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([accounts.user2])
+      .rpc();
+
+    const coupon = await program.account.coupon.fetch(couponPDA);
+    assert.equal(coupon.owner.toString(), accounts.user2.publicKey.toString());
+    assert.equal(coupon.lastSalePrice.toString(), listingPrice.toString());
+    assert.equal(buyerBefore - (await usdcBalance(accounts.user2.publicKey)), price);
+    assert.equal((await usdcBalance(accounts.user1.publicKey)) - sellerBefore, price - fee - royalty);
+    assert.equal((await usdcBalance(accounts.merchant1.publicKey)) - merchantBefore, royalty);
+  });
+
+  it("Rejects a payment account owned by someone else", async () => {
+    const [coupon, nftMint] = await mintCouponWithUsdc(accounts.user1);
+    const [listingPDA] = derivePDA([Buffer.from("listing"), coupon.toBuffer()], program.programId);
+
+    await program.methods
+      .listForSale(new BN(5 * USDC))
+      .accounts({
+        listing: listingPDA,
+        coupon,
+        nftMint,
+        sellerTokenAccount: getAssociatedTokenAddressSync(nftMint, accounts.user1.publicKey),
+        paymentMint: usdcMint,
+        seller: accounts.user1.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([accounts.user1])
+      .rpc();
+
+    try {
+      await program.methods
+        .buyListing()
+        .accounts({
+          listing: listingPDA,
+          coupon,
+          nftMint,
+          marketplace: accounts.marketplacePDA,
+          seller: accounts.user1.publicKey,
+          buyer: accounts.user2.publicKey,
+          marketplaceAuthority,
+          merchant: accounts.merchant1PDA,
+          merchantAuthority: accounts.merchant1.publicKey,
+          paymentMint: usdcMint,
+          buyerPaymentAccount: usdcAccount(accounts.user2.publicKey),
+          // Proceeds redirected away from the seller
+          sellerPaymentAccount: usdcAccount(accounts.user2.publicKey),
+          marketplacePaymentAccount: usdcAccount(marketplaceAuthority),
+          merchantPaymentAccount: usdcAccount(accounts.merchant1.publicKey),
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([accounts.user2])
+        .rpc();
+      assert.fail("Should have rejected the seller payment account");
+    } catch (error) {
+      expect(error.message).to.include("InvalidPaymentAccount");
+    }
+  });
+
+  it("Escrows group deal payments in the promotion's token", async () => {
+    const dealId = new BN(Date.now());
+    const [groupDealPDA] = derivePDA(
+      [Buffer.from("group_deal"), promotionPDA.toBuffer(), u64ToLeBytes(dealId)],
+      program.programId
+    );
+    const [escrowVaultPDA] = derivePDA(
+      [Buffer.from("group_escrow"), groupDealPDA.toBuffer()],
+      program.programId
+    );
+    const basePrice = new BN(4 * USDC);
+
+    await program.methods
+      .createGroupDeal(dealId, 2, 5, basePrice, [], new BN(86400))
+      .accounts({
+        groupDeal: groupDealPDA,
+        escrowVault: escrowVaultPDA,
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        organizer: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    const groupDeal = await program.account.groupDeal.fetch(groupDealPDA);
+    assert.equal(groupDeal.paymentMint.toString(), usdcMint.toString());
+
+    // The escrow PDA holds the tokens in its own associated token account
+    await getOrCreateAssociatedTokenAccount(connection, accounts.user1, usdcMint, escrowVaultPDA, true);
+    const [participantPDA] = derivePDA(
+      [Buffer.from("participant"), groupDealPDA.toBuffer(), accounts.user1.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .joinGroupDeal()
+      .accounts({
+        participant: participantPDA,
+        groupDeal: groupDealPDA,
+        escrowVault: escrowVaultPDA,
+        user: accounts.user1.publicKey,
+        paymentMint: usdcMint,
+        userPaymentAccount: usdcAccount(accounts.user1.publicKey),
+        escrowPaymentAccount: usdcAccount(escrowVaultPDA),
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1])
+      .rpc();
+
+    assert.equal((await usdcBalance(escrowVaultPDA)).toString(), basePrice.toString());
+  });
+});
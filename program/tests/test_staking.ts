@@ -131,7 +131,7 @@ describe("Staking System", () => {
     const couponExists = await accountExists(connection, couponPDA);
     if (!couponExists) {
       await program.methods
-        .mintCoupon(new BN(1))
+        .mintCoupon(new BN(1), null)
         .accounts({
           coupon: couponPDA,
           nftMint: couponMint.publicKey,
@@ -145,6 +145,8 @@ describe("Staking System", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
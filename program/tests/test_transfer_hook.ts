@@ -0,0 +1,314 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey, Transaction, sendAndConfirmTransaction } from "@solana/web3.js";
+import { assert } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  derivePDA,
+  accountExists,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  LAMPORTS_PER_SOL,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+} from "./setup";
+import {
+  TOKEN_2022_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createTransferCheckedWithTransferHookInstruction,
+  getAccount,
+} from "@solana/spl-token";
+
+describe("Token-2022 Transfer Hook", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let promotionPDA: PublicKey;
+  let couponPDA: PublicKey;
+  let couponMint: Keypair;
+
+  const mintHookedCoupon = async (recipient: Keypair): Promise<[PublicKey, Keypair]> => {
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotionPDA.toBuffer(), u32ToLeBytes(promotion.currentSupply)],
+      program.programId
+    );
+    const mint = Keypair.generate();
+    const marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+
+    await program.methods
+      .mintHookedCoupon(new BN(promotion.currentSupply + 1), null)
+      .accounts({
+        coupon,
+        nftMint: mint.publicKey,
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        recipient: recipient.publicKey,
+        merchantAuthority: accounts.merchant1.publicKey,
+        marketplaceAuthority: marketplace.authority,
+        payer: recipient.publicKey,
+        authority: accounts.merchant1.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([recipient, mint, accounts.merchant1])
+      .rpc();
+
+    return [coupon, mint];
+  };
+
+  const hookedTransferIx = (mint: PublicKey, source: PublicKey, destination: PublicKey, authority: PublicKey) =>
+    createTransferCheckedWithTransferHookInstruction(
+      connection,
+      source,
+      mint,
+      destination,
+      authority,
+      BigInt(1),
+      0,
+      [],
+      "confirmed",
+      TOKEN_2022_PROGRAM_ID
+    );
+
+  const walletTransfer = async (mint: PublicKey, from: Keypair, to: PublicKey) => {
+    const source = getAssociatedTokenAddressSync(mint, from.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const destination = getAssociatedTokenAddressSync(mint, to, false, TOKEN_2022_PROGRAM_ID);
+    const transferIx = await hookedTransferIx(mint, source, destination, from.publicKey);
+    const tx = new Transaction().add(
+      createAssociatedTokenAccountIdempotentInstruction(
+        from.publicKey,
+        destination,
+        to,
+        mint,
+        TOKEN_2022_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID
+      ),
+      transferIx
+    );
+    await sendAndConfirmTransaction(connection, tx, [from]);
+  };
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+
+    if (!(await accountExists(connection, accounts.merchant1PDA))) {
+      await program.methods
+        .registerMerchant("Hook Cafe", "restaurant", null, null)
+        .accounts({
+          merchant: accounts.merchant1PDA,
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.merchant1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.merchant1])
+        .rpc();
+    }
+
+    const merchant = await program.account.merchant.fetch(accounts.merchant1PDA);
+    [promotionPDA] = derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(merchant.totalCouponsCreated)],
+      program.programId
+    );
+
+    await program.methods
+      .createPromotion(30, 10, getExpiryTimestamp(30), "food", "Hooked promotion", new BN(0.1 * LAMPORTS_PER_SOL))
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+  });
+
+  it("Mints a Token-2022 coupon with the transfer hook", async () => {
+    [couponPDA, couponMint] = await mintHookedCoupon(accounts.user1);
+
+    const coupon = await program.account.coupon.fetch(couponPDA);
+    assert.equal(coupon.owner.toString(), accounts.user1.publicKey.toString());
+    assert.equal(coupon.mint.toString(), couponMint.publicKey.toString());
+    assert.deepEqual(coupon.lock, { none: {} });
+
+    const tokenAccount = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user1.publicKey, false, TOKEN_2022_PROGRAM_ID),
+      "confirmed",
+      TOKEN_2022_PROGRAM_ID
+    );
+    assert.equal(tokenAccount.amount.toString(), "1");
+  });
+
+  it("Updates Coupon.owner on a plain wallet transfer", async () => {
+    await walletTransfer(couponMint.publicKey, accounts.user1, accounts.user2.publicKey);
+
+    const coupon = await program.account.coupon.fetch(couponPDA);
+    assert.equal(coupon.owner.toString(), accounts.user2.publicKey.toString());
+  });
+
+  it("Rejects transfers while the coupon is auctioned", async () => {
+    const auctionId = new BN(Date.now());
+    const [auctionPDA] = derivePDA(
+      [Buffer.from("auction"), couponPDA.toBuffer(), u64ToLeBytes(auctionId)],
+      program.programId
+    );
+
+    await program.methods
+      .createAuction(
+        auctionId,
+        { english: {} },
+        new BN(0.5 * LAMPORTS_PER_SOL),
+        new BN(0.4 * LAMPORTS_PER_SOL),
+        new BN(3600),
+        false,
+        new BN(0.1 * LAMPORTS_PER_SOL)
+      )
+      .accounts({
+        auction: auctionPDA,
+        coupon: couponPDA,
+        seller: accounts.user2.publicKey,
+      })
+      .signers([accounts.user2])
+      .rpc();
+
+    try {
+      await walletTransfer(couponMint.publicKey, accounts.user2, accounts.user1.publicKey);
+      assert.fail("Should have rejected transfer of an auctioned coupon");
+    } catch (error) {
+      assert.include(error.toString(), "0x1787"); // CouponLocked, raised inside the hook
+    }
+
+    await program.methods
+      .cancelAuction()
+      .accounts({
+        auction: auctionPDA,
+        coupon: couponPDA,
+        seller: accounts.user2.publicKey,
+      })
+      .signers([accounts.user2])
+      .rpc();
+
+    await walletTransfer(couponMint.publicKey, accounts.user2, accounts.user1.publicKey);
+    const coupon = await program.account.coupon.fetch(couponPDA);
+    assert.equal(coupon.owner.toString(), accounts.user1.publicKey.toString());
+  });
+
+  it("Sells a hooked coupon through a listing", async () => {
+    const [listingPDA] = derivePDA([Buffer.from("listing"), couponPDA.toBuffer()], program.programId);
+    const [listingVault] = derivePDA([Buffer.from("listing_vault"), listingPDA.toBuffer()], program.programId);
+    const sellerAta = getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user1.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const buyerAta = getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user2.publicKey, false, TOKEN_2022_PROGRAM_ID);
+
+    await program.methods
+      .listForSale(new BN(0.2 * LAMPORTS_PER_SOL))
+      .accounts({
+        listing: listingPDA,
+        coupon: couponPDA,
+        nftMint: couponMint.publicKey,
+        sellerTokenAccount: sellerAta,
+        listingVault,
+        seller: accounts.user1.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.user1])
+      .rpc();
+
+    // Listed coupons only move into their own vault
+    try {
+      await walletTransfer(couponMint.publicKey, accounts.user1, accounts.user2.publicKey);
+      assert.fail("Should have rejected transfer of a listed coupon");
+    } catch (error) {
+      assert.include(error.toString(), "0x1787"); // CouponLocked, raised inside the hook
+    }
+
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(await hookedTransferIx(couponMint.publicKey, sellerAta, listingVault, accounts.user1.publicKey)),
+      [accounts.user1]
+    );
+    let coupon = await program.account.coupon.fetch(couponPDA);
+    assert.equal(coupon.owner.toString(), accounts.user1.publicKey.toString());
+    assert.deepEqual(coupon.lock, { listed: {} });
+
+    // The listing approves the buyer, who takes the NFT out in the same transaction
+    const marketplace = await program.account.marketplace.fetch(accounts.marketplacePDA);
+    const buyIx = await program.methods
+      .buyListing()
+      .accounts({
+        listing: listingPDA,
+        coupon: couponPDA,
+        nftMint: couponMint.publicKey,
+        listingVault,
+        buyerTokenAccount: buyerAta,
+        marketplace: accounts.marketplacePDA,
+        seller: accounts.user1.publicKey,
+        buyer: accounts.user2.publicKey,
+        marketplaceAuthority: marketplace.authority,
+        merchant: accounts.merchant1PDA,
+        merchantAuthority: accounts.merchant1.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(
+        buyIx,
+        await hookedTransferIx(couponMint.publicKey, listingVault, buyerAta, accounts.user2.publicKey)
+      ),
+      [accounts.user2]
+    );
+
+    coupon = await program.account.coupon.fetch(couponPDA);
+    assert.equal(coupon.owner.toString(), accounts.user2.publicKey.toString());
+    assert.deepEqual(coupon.lock, { none: {} });
+    const buyerAccount = await getAccount(connection, buyerAta, "confirmed", TOKEN_2022_PROGRAM_ID);
+    assert.equal(buyerAccount.amount.toString(), "1");
+  });
+
+  it("Rejects direct calls outside of a token transfer", async () => {
+    const source = getAssociatedTokenAddressSync(couponMint.publicKey, accounts.user1.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [extraAccountMetaList] = derivePDA(
+      [Buffer.from("extra-account-metas"), couponMint.publicKey.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .transferHook(new BN(1))
+        .accounts({
+          sourceToken: source,
+          mint: couponMint.publicKey,
+          destinationToken: source,
+          owner: accounts.user1.publicKey,
+          extraAccountMetaList,
+          coupon: couponPDA,
+        })
+        .rpc();
+      assert.fail("Should have rejected a direct hook call");
+    } catch (error) {
+      assert.include(error.toString(), "NotTransferring");
+    }
+  });
+});
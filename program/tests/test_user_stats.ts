@@ -124,7 +124,7 @@ describe("UserStats System", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(1))
+        .mintCoupon(new BN(1), null)
         .accounts({
           coupon: couponPDA,
           nftMint: mintKeypair.publicKey,
@@ -138,6 +138,8 @@ describe("UserStats System", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -189,7 +191,7 @@ describe("UserStats System", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(2))
+        .mintCoupon(new BN(2), null)
         .accounts({
           coupon: couponPDA,
           nftMint: mintKeypair.publicKey,
@@ -203,6 +205,8 @@ describe("UserStats System", () => {
           userStats: userStatsPDA,
           payer: accounts.user1.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -463,7 +467,7 @@ describe("UserStats System", () => {
       );
 
       await program.methods
-        .mintCoupon(new BN(100))
+        .mintCoupon(new BN(100), null)
         .accounts({
           coupon: couponPDA,
           nftMint: mintKeypair.publicKey,
@@ -477,6 +481,8 @@ describe("UserStats System", () => {
           userStats: user2StatsPDA,
           payer: accounts.user2.publicKey,
           authority: accounts.merchant1.publicKey,
+          merchantAuthority: accounts.merchant1.publicKey,
+          marketplaceAuthority: (await program.account.marketplace.fetch(accounts.marketplacePDA)).authority,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
@@ -0,0 +1,186 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN, web3 } from "@coral-xyz/anchor";
+import { DiscountPlatform } from "../target/types/discount_platform";
+import { SystemProgram, Keypair, PublicKey, Ed25519Program } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import {
+  setupTestAccounts,
+  TestAccounts,
+  getExpiryTimestamp,
+  getCurrentTimestamp,
+  derivePDA,
+  deriveMetadataPDA,
+  deriveMasterEditionPDA,
+  accountExists,
+  u32ToLeBytes,
+  u64ToLeBytes,
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./setup";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+describe("Mint Vouchers", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiscountPlatform as Program<DiscountPlatform>;
+  const connection = provider.connection;
+
+  let accounts: TestAccounts;
+  let promotionPDA: PublicKey;
+
+  type Voucher = { promotion: PublicKey; recipient: PublicKey; nonce: BN; expiresAt: BN };
+
+  // Mirrors MintVoucher::message
+  const voucherMessage = (voucher: Voucher) =>
+    Buffer.concat([
+      Buffer.from("discount_platform:mint_voucher:v1"),
+      voucher.promotion.toBuffer(),
+      voucher.recipient.toBuffer(),
+      voucher.nonce.toArrayLike(Buffer, "le", 8),
+      voucher.expiresAt.toTwos(64).toArrayLike(Buffer, "le", 8),
+    ]);
+
+  const newVoucher = (nonce: number, expiresAt = getCurrentTimestamp() + 3600): Voucher => ({
+    promotion: promotionPDA,
+    recipient: accounts.user1.publicKey,
+    nonce: new BN(nonce),
+    expiresAt: new BN(expiresAt),
+  });
+
+  const claim = async (voucher: Voucher, signer: Keypair | null) => {
+    const promotion = await program.account.promotion.fetch(promotionPDA);
+    const [coupon] = derivePDA(
+      [Buffer.from("coupon"), promotionPDA.toBuffer(), u32ToLeBytes(promotion.currentSupply)],
+      program.programId
+    );
+    const nftMint = Keypair.generate();
+
+    const preInstructions = signer
+      ? [Ed25519Program.createInstructionWithPrivateKey({ privateKey: signer.secretKey, message: voucherMessage(voucher) })]
+      : [];
+
+    await program.methods
+      .claimWithVoucher(new BN(promotion.currentSupply + 1), voucher)
+      .accounts({
+        voucherNonce: derivePDA(
+          [Buffer.from("voucher_nonce"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(voucher.nonce)],
+          program.programId
+        )[0],
+        coupon,
+        nftMint: nftMint.publicKey,
+        tokenAccount: getAssociatedTokenAddressSync(nftMint.publicKey, voucher.recipient),
+        metadata: deriveMetadataPDA(nftMint.publicKey)[0],
+        masterEdition: deriveMasterEditionPDA(nftMint.publicKey)[0],
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        recipient: voucher.recipient,
+        payer: accounts.user1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .preInstructions(preInstructions)
+      .signers([accounts.user1, nftMint])
+      .rpc();
+
+    return coupon;
+  };
+
+  before(async () => {
+    accounts = await setupTestAccounts(program, connection);
+
+    if (!(await accountExists(connection, accounts.marketplacePDA))) {
+      await program.methods
+        .initialize()
+        .accounts({
+          marketplace: accounts.marketplacePDA,
+          authority: accounts.marketplaceAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([accounts.marketplaceAuthority])
+        .rpc();
+    }
+
+    await program.methods
+      .registerMerchant("Voucher Cafe", "cafe", null, null)
+      .accounts({
+        merchant: accounts.merchant1PDA,
+        marketplace: accounts.marketplacePDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+
+    [promotionPDA] = derivePDA(
+      [Buffer.from("promotion"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(0)],
+      program.programId
+    );
+    await program.methods
+      .createPromotion(25, 10, getExpiryTimestamp(30), "cafe", "Sign-up gift", new BN(0))
+      .accounts({
+        promotion: promotionPDA,
+        merchant: accounts.merchant1PDA,
+        authority: accounts.merchant1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([accounts.merchant1])
+      .rpc();
+  });
+
+  it("Mints a coupon for a merchant-signed voucher", async () => {
+    const coupon = await claim(newVoucher(1), accounts.merchant1);
+
+    const couponAccount = await program.account.coupon.fetch(coupon);
+    assert.ok(couponAccount.owner.equals(accounts.user1.publicKey));
+
+    const [voucherNoncePDA] = derivePDA(
+      [Buffer.from("voucher_nonce"), accounts.merchant1PDA.toBuffer(), u64ToLeBytes(1)],
+      program.programId
+    );
+    const voucherNonce = await program.account.voucherNonce.fetch(voucherNoncePDA);
+    assert.ok(voucherNonce.signer.equals(accounts.merchant1.publicKey));
+  });
+
+  it("Rejects a replayed voucher", async () => {
+    try {
+      await claim(newVoucher(1), accounts.merchant1);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("already in use");
+    }
+  });
+
+  it("Rejects a voucher signed by someone else", async () => {
+    try {
+      await claim(newVoucher(2), accounts.merchant2);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("InvalidVoucherSigner");
+    }
+  });
+
+  it("Rejects an expired voucher", async () => {
+    try {
+      await claim(newVoucher(3, getCurrentTimestamp() - 60), accounts.merchant1);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("VoucherExpired");
+    }
+  });
+
+  it("Rejects a claim without the ed25519 instruction", async () => {
+    try {
+      await claim(newVoucher(4), null);
+      assert.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.message).to.include("MissingVoucherSignature");
+    }
+  });
+});